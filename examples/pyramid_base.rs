@@ -4,7 +4,7 @@
 //! base test files from [here], and save
 //! them in a new folder named "pyramid_base_test_files", in the package's directory.
 //!
-//! For each tree type (currently treap, splay, and avl) the code will so this:
+//! For each tree type (currently treap, splay, avl and scapegoat) the code will so this:
 //! * It will look for the test files in "package/pyramid_base_test_files/".
 //! * It will sort them by difficulty based on their name. run the solution on them (with the specific tree type).
 //!   Tests with `p > 30_000` will be skipped by immediately returning 0 (they're a bit too slow).
@@ -25,6 +25,7 @@ use std::time::Instant;
 
 use example_data::{AddAction, SizedSummary};
 use trees::avl::*;
+use trees::scapegoat::*;
 use trees::splay::*;
 use trees::treap::*;
 
@@ -160,7 +161,7 @@ impl std::default::Default for SizeMinSummary {
 }
 
 impl SizedSummary for SizeMinSummary {
-    fn size(self) -> usize {
+    fn size(&self) -> usize {
         self.size
     }
 }
@@ -443,5 +444,8 @@ fn main() -> Result<(), Error> {
     println!("starting avl\n");
     check_all_tests::<AVLTree<_>>()?;
     println!("done avl\n");
+    println!("starting scapegoat\n");
+    check_all_tests::<ScapegoatTree<_>>()?;
+    println!("done scapegoat\n");
     Ok(())
 }
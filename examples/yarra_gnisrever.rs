@@ -17,8 +17,10 @@ use grove::*;
 
 use example_data::RevAction;
 use trees::avl::*;
+use trees::red_black::*;
 use trees::splay::*;
 use trees::treap::*;
+use trees::weight_balanced::*;
 
 const MODULUS: I = 1_000_000_000;
 
@@ -97,12 +99,14 @@ impl Interval {
 
         res
     }
+}
 
-    // split into the index first values and the rest. i.e.,
-    // spliting [6,5,4] with index=1 gives [6], [5,4]
-    fn split_at_index(&self, index2: usize) -> (Interval, Interval) {
-        let index = index2 as I;
-        assert!(0 < index2 && index2 < self.size());
+// split into the offset first values and the rest. i.e.,
+// spliting [6,5,4] with offset=1 gives [6], [5,4]
+impl SplittableValue for Interval {
+    fn split_at(&self, offset: usize) -> (Interval, Interval) {
+        let index = offset as I;
+        assert!(0 < offset && offset < self.size());
         if self.start <= self.end {
             (
                 Interval {
@@ -164,7 +168,7 @@ impl Default for SegmentSize {
 }
 
 impl SizedSummary for SegmentSize {
-    fn size(self) -> usize {
+    fn size(&self) -> usize {
         self.size
     }
 }
@@ -189,35 +193,9 @@ impl Data for RevData {
     type Value = Interval;
 }
 
-// splits a segment inside the tree
-fn search_split<TR: SplittableTreeRef<RevData>>(tree: TR, index: usize) -> TR::T {
-    let mut walker = tree.walker();
-    // using an empty range so that we'll only end up at a node
-    // if we actually need to split that node
-    walker.search_subtree(index..index);
-
-    let left = walker.left_summary().size;
-    let v2option = walker.with_value(|val| {
-        let (v1, v2) = val.split_at_index(index - left);
-        *val = v1;
-        v2
-    });
-
-    if let Some(v2) = v2option {
-        walker.next_empty().unwrap(); // not at an empty position
-        walker.insert(v2).unwrap();
-        walker.go_to_root();
-        // after insertion, walker might be at some arbitrary location,
-        // depending on the tree type
-        walker.search_subtree(index..index);
-    }
-
-    walker.split_right().unwrap()
-}
-
 fn yarra<T: ConcatenableTree<RevData>>(n: usize, k: usize) -> I
 where
-    for<'b> &'b mut T: SplittableTreeRef<RevData, T = T>,
+    for<'b> &'b mut T: SplittableTreeRef<RevData, T = T> + ModifiableTreeRef<RevData>,
 {
     let inter = Interval {
         start: 0,
@@ -234,8 +212,12 @@ where
         if sn != tn {
             let (low, high) = if sn < tn { (sn, tn + 1) } else { (tn, sn + 1) };
 
-            let mut mid = search_split(&mut tree, low);
-            let right = search_split(&mut mid, high - low);
+            // `slice_exact` splits the intervals that contain the edges of the segment
+            let mut mid = tree.slice_exact(low..low).split_right().unwrap();
+            let right = mid
+                .slice_exact(high - low..high - low)
+                .split_right()
+                .unwrap();
             mid.act_subtree(RevAction { to_reverse: true });
             mid.concatenate_right(right);
             tree.concatenate_right(mid);
@@ -269,19 +251,29 @@ where
 
 pub fn main() {
     println!("splay:");
-    let res = yarra::<SplayTree<_>>(1000_000_000_000_000_000, 1000_000);
+    let res = yarra::<SplayTree<_>>(1_000_000_000_000_000_000, 1_000_000);
     assert_eq!(res, 563917241);
     println!("done splay\n");
 
     println!("avl:");
-    let res = yarra::<AVLTree<_>>(1000_000_000_000_000_000, 1000_000);
+    let res = yarra::<AVLTree<_>>(1_000_000_000_000_000_000, 1_000_000);
     assert_eq!(res, 563917241);
     println!("done avl\n");
 
+    println!("red-black:");
+    let res = yarra::<RBTree<_>>(1_000_000_000_000_000_000, 1_000_000);
+    assert_eq!(res, 563917241);
+    println!("done red-black\n");
+
     println!("treap:");
-    let res = yarra::<Treap<_>>(1000_000_000_000_000_000, 1000_000);
+    let res = yarra::<Treap<_>>(1_000_000_000_000_000_000, 1_000_000);
     assert_eq!(res, 563917241);
     println!("done treap\n");
+
+    println!("weight-balanced:");
+    let res = yarra::<WBTree<_>>(1_000_000_000_000_000_000, 1_000_000);
+    assert_eq!(res, 563917241);
+    println!("done weight-balanced\n");
 }
 
 #[test]
@@ -316,3 +308,25 @@ pub fn yarra_avl() {
         assert_eq!(res, 275481640);
     }
 }
+
+#[test]
+pub fn yarra_rb() {
+    let res = yarra::<RBTree<_>>(100, 100);
+    assert_eq!(res, 246597);
+    #[cfg(not(miri))] // miri is too slow for this
+    {
+        let res = yarra::<RBTree<_>>(10000, 10000);
+        assert_eq!(res, 275481640);
+    }
+}
+
+#[test]
+pub fn yarra_wb() {
+    let res = yarra::<WBTree<_>>(100, 100);
+    assert_eq!(res, 246597);
+    #[cfg(not(miri))] // miri is too slow for this
+    {
+        let res = yarra::<WBTree<_>>(10000, 10000);
+        assert_eq!(res, 275481640);
+    }
+}
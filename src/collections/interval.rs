@@ -0,0 +1,313 @@
+//! An interval map, with overlap and stabbing queries.
+//!
+//! An [`IntervalMap`] stores half-open intervals with values, sorted by their start points,
+//! and keyed by them through the [`Keyed`] trait. The summary of every segment keeps
+//! the maximum end point of its intervals.
+//!
+//! Finding all of the intervals that overlap a query can't be expressed with a [`Locator`],
+//! since a locator only sees the summaries to the left and right of a node, and can't skip
+//! a subtree. Instead, the queries walk the tree directly, and skip every subtree
+//! whose maximum end point is before the query, and every subtree whose intervals start
+//! after it. This takes `O((k + 1)*log n)` time, where `k` is the number of intervals found.
+
+use crate::example_data::Unit;
+use crate::locators::{ByKey, RightEdgeOf};
+use crate::treap::Treap;
+use crate::trees::basic_tree::{BasicNode, BasicTree};
+use crate::*;
+use std::ops::{Add, Bound, Range};
+
+/// An interval in an [`IntervalMap`], together with its value.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct IntervalEntry<K, V> {
+    /// The interval.
+    pub range: Range<K>,
+    /// The value associated with the interval.
+    pub value: V,
+}
+
+/// Intervals are keyed by their start points.
+impl<K: Ord, V> Keyed<K> for IntervalEntry<K, V> {
+    fn get_key(&self) -> &K {
+        &self.range.start
+    }
+}
+
+/// The summary of a segment of intervals.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct IntervalSummary<K> {
+    /// The maximum end point of the intervals in the segment,
+    /// or [`None`] if the segment is empty.
+    pub max_end: Option<K>,
+    /// The number of intervals in the segment.
+    pub size: usize,
+}
+
+impl<K> Default for IntervalSummary<K> {
+    fn default() -> Self {
+        IntervalSummary {
+            max_end: None,
+            size: 0,
+        }
+    }
+}
+
+impl<K: Ord> Add for IntervalSummary<K> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        IntervalSummary {
+            max_end: std::cmp::max(self.max_end, other.max_end),
+            size: self.size + other.size,
+        }
+    }
+}
+
+impl<K> SizedSummary for IntervalSummary<K> {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<K: Copy, V> ToSummary<IntervalSummary<K>> for IntervalEntry<K, V> {
+    fn to_summary(&self) -> IntervalSummary<K> {
+        IntervalSummary {
+            max_end: Some(self.range.end),
+            size: 1,
+        }
+    }
+}
+
+/// The data stored in the tree of an [`IntervalMap`].
+pub type IntervalData<K, V> = (IntervalEntry<K, V>, IntervalSummary<K>, Unit);
+
+/// A map from half-open intervals to values, stored in a tree of type `T`.
+/// By default, the tree is a [`Treap`].
+///
+/// Several intervals may overlap, and the same interval may be inserted several times.
+///```
+/// use grove::collections::IntervalMap;
+///
+/// let mut meetings: IntervalMap<u32, &str> = IntervalMap::new();
+/// meetings.insert(9..11, "standup");
+/// meetings.insert(10..12, "review");
+/// meetings.insert(14..15, "retro");
+///
+/// let at_10: Vec<_> = meetings.stabbing(10).map(|(_, name)| *name).collect();
+/// assert_eq!(at_10, vec!["standup", "review"]);
+///
+/// let afternoon: Vec<_> = meetings.overlapping(11..16).map(|(range, _)| range.clone()).collect();
+/// assert_eq!(afternoon, vec![10..12, 14..15]);
+///
+/// assert!(!meetings.any_overlap(12..14));
+/// assert_eq!(meetings.remove(&(10..12)), Some("review"));
+/// assert!(!meetings.any_overlap(11..14));
+///```
+pub struct IntervalMap<K, V, T = Treap<IntervalData<K, V>>> {
+    tree: T,
+    phantom: std::marker::PhantomData<(K, V)>,
+}
+
+impl<K, V, T> IntervalMap<K, V, T>
+where
+    K: Ord + Copy,
+    T: SomeTree<IntervalData<K, V>> + AsBasicTree<IntervalData<K, V>>,
+    for<'a> &'a mut T: ModifiableTreeRef<IntervalData<K, V>>,
+{
+    /// Creates an empty interval map.
+    pub fn new() -> Self {
+        IntervalMap::from_tree(Default::default())
+    }
+
+    /// Creates an interval map of the intervals in the tree.
+    /// The intervals have to be sorted by their start points.
+    pub fn from_tree(tree: T) -> Self {
+        IntervalMap {
+            tree,
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the tree the intervals are stored in.
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the tree the intervals are stored in.
+    pub fn into_tree(self) -> T {
+        self.tree
+    }
+
+    /// Returns the number of intervals in the map.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Inserts the interval with its value.
+    /// Intervals with the same start point are kept in the order they were inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the interval is empty.
+    pub fn insert(&mut self, range: Range<K>, value: V) {
+        assert!(range.start < range.end, "intervals can't be empty");
+        let start = range.start;
+        self.tree
+            .slice(RightEdgeOf(ByKey(..=&start)))
+            .insert(IntervalEntry { range, value })
+            .unwrap();
+    }
+
+    /// Removes an interval that is equal to the given interval, and returns its value.
+    /// If there is no such interval, returns [`None`].
+    pub fn remove(&mut self, range: &Range<K>) -> Option<V> {
+        let first = self.tree.segment_summary(ByKey(..&range.start)).size;
+        let offset = self
+            .tree
+            .iter_locator(ByKey((&range.start,)))
+            .position(|entry| entry.range.end == range.end)?;
+        Some(self.tree.slice(first + offset).delete()?.value)
+    }
+
+    /// Iterates over the intervals in the map, sorted by their start points.
+    /// See [`SomeTree::iter`] as to why this requires mutable access.
+    pub fn iter(&mut self) -> impl Iterator<Item = (&Range<K>, &V)> + '_ {
+        self.tree.iter().map(|entry| (&entry.range, &entry.value))
+    }
+
+    /// Iterates over the intervals that overlap the given range,
+    /// sorted by their start points.
+    pub fn overlapping(&self, range: Range<K>) -> Overlapping<'_, K, V, T::AlgData> {
+        Overlapping::new(
+            self.tree.as_basic_tree(),
+            range.start,
+            Bound::Excluded(range.end),
+        )
+    }
+
+    /// Iterates over the intervals that contain the given point,
+    /// sorted by their start points.
+    pub fn stabbing(&self, point: K) -> Overlapping<'_, K, V, T::AlgData> {
+        Overlapping::new(self.tree.as_basic_tree(), point, Bound::Included(point))
+    }
+
+    /// Returns true if any interval overlaps the given range.
+    pub fn any_overlap(&self, range: Range<K>) -> bool {
+        self.overlapping(range).next().is_some()
+    }
+}
+
+impl<K, V, T> Default for IntervalMap<K, V, T>
+where
+    K: Ord + Copy,
+    T: SomeTree<IntervalData<K, V>> + AsBasicTree<IntervalData<K, V>>,
+    for<'a> &'a mut T: ModifiableTreeRef<IntervalData<K, V>>,
+{
+    fn default() -> Self {
+        IntervalMap::new()
+    }
+}
+
+impl<K, V, T> std::iter::FromIterator<(Range<K>, V)> for IntervalMap<K, V, T>
+where
+    K: Ord + Copy,
+    T: SomeTree<IntervalData<K, V>> + AsBasicTree<IntervalData<K, V>>,
+    for<'a> &'a mut T: ModifiableTreeRef<IntervalData<K, V>>,
+{
+    /// # Panics
+    ///
+    /// Panics if any of the intervals is empty.
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut entries: Vec<_> = iter
+            .into_iter()
+            .map(|(range, value)| {
+                assert!(range.start < range.end, "intervals can't be empty");
+                IntervalEntry { range, value }
+            })
+            .collect();
+        // a stable sort, in order to keep the insertion order of equal start points
+        entries.sort_by_key(|entry| entry.range.start);
+        IntervalMap::from_tree(entries.into_iter().collect())
+    }
+}
+
+impl<K, V, T> Extend<(Range<K>, V)> for IntervalMap<K, V, T>
+where
+    K: Ord + Copy,
+    T: SomeTree<IntervalData<K, V>> + AsBasicTree<IntervalData<K, V>>,
+    for<'a> &'a mut T: ModifiableTreeRef<IntervalData<K, V>>,
+{
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.insert(range, value);
+        }
+    }
+}
+
+/// An iterator over the intervals of an [`IntervalMap`] that overlap a query.
+/// Returned by [`IntervalMap::overlapping`] and [`IntervalMap::stabbing`].
+pub struct Overlapping<'a, K: Ord + Copy, V, A> {
+    /// The nodes whose left subtrees were already visited,
+    /// and whose values and right subtrees weren't.
+    stack: Vec<&'a BasicNode<IntervalData<K, V>, A>>,
+    /// The found intervals must end after this point.
+    start: K,
+    /// The found intervals must start before this bound.
+    end: Bound<K>,
+}
+
+impl<'a, K: Ord + Copy, V, A> Overlapping<'a, K, V, A> {
+    fn new(tree: &'a BasicTree<IntervalData<K, V>, A>, start: K, end: Bound<K>) -> Self {
+        let mut res = Overlapping {
+            stack: vec![],
+            start,
+            end,
+        };
+        res.push_left_path(tree);
+        res
+    }
+
+    /// Pushes the nodes on the leftmost path of the subtree,
+    /// stopping at subtrees that only have intervals that end before the query.
+    fn push_left_path(&mut self, mut tree: &'a BasicTree<IntervalData<K, V>, A>) {
+        // intervals are never acted upon, so the summaries and values are clean
+        while tree.subtree_summary().max_end > Some(self.start) {
+            let node = tree.node().unwrap();
+            self.stack.push(node);
+            tree = &node.left;
+        }
+    }
+
+    /// Returns true if an interval with this start point starts before the query's end.
+    fn starts_before_end(&self, start: &K) -> bool {
+        match &self.end {
+            Bound::Included(end) => start <= end,
+            Bound::Excluded(end) => start < end,
+            Bound::Unbounded => true,
+        }
+    }
+}
+
+impl<'a, K: Ord + Copy, V, A> Iterator for Overlapping<'a, K, V, A> {
+    type Item = (&'a Range<K>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.stack.pop()?;
+            let entry = &node.node_value;
+            if !self.starts_before_end(&entry.range.start) {
+                // all of the remaining intervals start after this one
+                self.stack.clear();
+                return None;
+            }
+            self.push_left_path(&node.right);
+            if entry.range.end > self.start {
+                return Some((&entry.range, &entry.value));
+            }
+        }
+    }
+}
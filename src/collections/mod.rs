@@ -0,0 +1,18 @@
+//! The collections module.
+//! This module contains high-level collection types that are built on the trees
+//! in the [`trees`](crate::trees) module, and are generic over the kind of tree that is used.
+//!
+//! These types hide the [`Data`](crate::Data) trait and the locators behind a familiar
+//! interface, for the common cases where you don't need custom summaries or actions.
+
+pub mod interval;
+pub mod ordered;
+pub mod range_map;
+pub mod rope;
+pub mod seq;
+
+pub use interval::IntervalMap;
+pub use ordered::{OrderedMap, OrderedSet};
+pub use range_map::{RangeMap, RangeSet};
+pub use rope::Rope;
+pub use seq::Seq;
@@ -0,0 +1,716 @@
+//! Ordered sets and maps, with an interface similar to [`BTreeSet`] and [`BTreeMap`].
+//!
+//! An [`OrderedSet`] or an [`OrderedMap`] stores its values in a tree, sorted by their keys,
+//! and uses the [`Keyed`] trait together with [`ByKey`] locators to search the tree.
+//! The keys of an [`OrderedMap`] are the first elements of its key-value pairs.
+//!
+//! Beyond the standard collections, they can find the `i`-th value and the rank of a key,
+//! and they can summarize and act on ranges of keys, using the summaries and actions
+//! of their [`Data`] parameter. By default, this is [`SizeData`], which keeps only the sizes
+//! of segments.
+//!
+//! As with [`SomeTree::iter`], most queries require mutable access, since looking
+//! at a value applies the actions that are pending above it.
+//!
+//! [`BTreeSet`]: std::collections::BTreeSet
+//! [`BTreeMap`]: std::collections::BTreeMap
+
+use crate::example_data::SizeData;
+use crate::locators::ByKey;
+use crate::treap::Treap;
+use crate::*;
+use std::marker::PhantomData;
+use std::ops::{Bound, Range, RangeBounds};
+
+/// Returns the number of values whose keys are smaller than `key`.
+fn count_below<K, D, T>(tree: &mut T, key: &K) -> usize
+where
+    K: Ord,
+    D: Data,
+    D::Value: Keyed<K>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: SomeTreeRef<D>,
+{
+    tree.segment_summary(ByKey(..key)).size()
+}
+
+/// Returns the number of values whose keys are smaller than or equal to `key`.
+fn count_up_to<K, D, T>(tree: &mut T, key: &K) -> usize
+where
+    K: Ord,
+    D: Data,
+    D::Value: Keyed<K>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: SomeTreeRef<D>,
+{
+    tree.segment_summary(ByKey(..=key)).size()
+}
+
+/// Returns the range of indices of the values whose keys are in the given range.
+/// If the range is decreasing, the result is empty.
+fn index_range<K, D, T, R>(tree: &mut T, range: R) -> Range<usize>
+where
+    K: Ord,
+    D: Data,
+    D::Value: Keyed<K>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: SomeTreeRef<D>,
+    R: RangeBounds<K>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(key) => count_below(tree, key),
+        Bound::Excluded(key) => count_up_to(tree, key),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(key) => count_up_to(tree, key),
+        Bound::Excluded(key) => count_below(tree, key),
+        Bound::Unbounded => tree.subtree_summary().size(),
+    };
+    start..end.max(start)
+}
+
+/// Sorts the values by their keys, and keeps only the last value for every key.
+fn sort_and_dedup<K: Ord, Value: Keyed<K>>(mut values: Vec<Value>) -> Vec<Value> {
+    // the sort is stable, so the last value for every key stays last
+    values.sort_by(|a, b| a.get_key().cmp(b.get_key()));
+    let mut res: Vec<Value> = Vec::with_capacity(values.len());
+    for value in values {
+        match res.last_mut() {
+            Some(last) if last.get_key() == value.get_key() => *last = value,
+            _ => res.push(value),
+        }
+    }
+    res
+}
+
+/// An ordered set of keys, stored in a tree of type `T`.
+/// By default, the tree is a [`Treap`].
+///```
+/// use grove::collections::OrderedSet;
+///
+/// let mut set: OrderedSet<i32> = [8, 1, 4, 6, 2, 7].iter().cloned().collect();
+/// assert!(set.insert(5));
+/// assert!(!set.insert(5));
+/// assert!(set.remove(&2));
+/// assert!(set.contains(&5));
+///
+/// assert_eq!(set.first(), Some(&1));
+/// assert_eq!(set.nth(2), Some(&5));
+/// assert_eq!(set.rank(&6), 3);
+/// assert_eq!(set.range(4..7).cloned().collect::<Vec<_>>(), vec![4, 5, 6]);
+/// assert_eq!(set.range_summary(5..).size, 4);
+/// assert_eq!(set.pop_last(), Some(8));
+/// assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 4, 5, 6, 7]);
+///```
+pub struct OrderedSet<K, D = SizeData<K>, T = Treap<D>> {
+    tree: T,
+    phantom: PhantomData<(K, D)>,
+}
+
+impl<K, D, T> OrderedSet<K, D, T>
+where
+    K: Ord,
+    D: Data<Value = K>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        OrderedSet {
+            tree: Default::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a set of the values in the tree.
+    /// The values have to be sorted, without duplicates.
+    pub fn from_tree(tree: T) -> Self {
+        OrderedSet {
+            tree,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the tree the keys are stored in.
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the tree the keys are stored in.
+    pub fn into_tree(self) -> T {
+        self.tree
+    }
+
+    /// Returns the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size()
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Adds the key to the set.
+    /// Returns true if the key wasn't already in the set.
+    pub fn insert(&mut self, key: K) -> bool {
+        let mut walker = self.tree.search(ByKey((&key,)));
+        if !walker.is_empty() {
+            return false;
+        }
+        walker.insert(key).unwrap();
+        true
+    }
+
+    /// Removes the key from the set.
+    /// Returns true if the key was in the set.
+    pub fn remove(&mut self, key: &K) -> bool {
+        self.take(key).is_some()
+    }
+
+    /// Removes the key from the set, and returns the key that was stored in the set.
+    pub fn take(&mut self, key: &K) -> Option<K> {
+        self.tree.search(ByKey((key,))).delete()
+    }
+
+    /// Returns true if the key is in the set.
+    pub fn contains(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the key that is stored in the set and is equal to the given key.
+    pub fn get(&mut self, key: &K) -> Option<&K> {
+        self.tree.iter_locator(ByKey((key,))).next()
+    }
+
+    /// Returns the smallest key in the set.
+    pub fn first(&mut self) -> Option<&K> {
+        self.nth(0)
+    }
+
+    /// Returns the largest key in the set.
+    pub fn last(&mut self) -> Option<&K> {
+        let index = self.len().checked_sub(1)?;
+        self.nth(index)
+    }
+
+    /// Removes the smallest key in the set and returns it.
+    pub fn pop_first(&mut self) -> Option<K> {
+        self.tree.slice(0).delete()
+    }
+
+    /// Removes the largest key in the set and returns it.
+    pub fn pop_last(&mut self) -> Option<K> {
+        let index = self.len().checked_sub(1)?;
+        self.tree.slice(index).delete()
+    }
+
+    /// Iterates over the keys of the set, in increasing order.
+    pub fn iter(&mut self) -> impl Iterator<Item = &K> + '_ {
+        self.tree.iter()
+    }
+
+    /// Iterates over the keys of the set that are in the given range, in increasing order.
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> impl Iterator<Item = &K> + '_ {
+        let indices = index_range(&mut self.tree, range);
+        self.tree.iter_locator(indices)
+    }
+
+    /// Returns the `index`-th smallest key in the set, starting from zero.
+    pub fn nth(&mut self, index: usize) -> Option<&K> {
+        self.tree.iter_locator(index).next()
+    }
+
+    /// Returns the number of keys in the set that are smaller than the given key.
+    pub fn rank(&mut self, key: &K) -> usize {
+        count_below(&mut self.tree, key)
+    }
+
+    /// Returns the summary of the keys in the given range.
+    pub fn range_summary<R: RangeBounds<K>>(&mut self, range: R) -> D::Summary {
+        let keys = ByKey((range.start_bound(), range.end_bound()));
+        self.tree.segment_summary(keys)
+    }
+
+    /// Applies the action on the keys in the given range.
+    /// The action must keep all of the keys in the set in sorted order, without duplicates.
+    pub fn range_act<R: RangeBounds<K>>(&mut self, range: R, action: D::Action) {
+        let keys = ByKey((range.start_bound(), range.end_bound()));
+        self.tree.act_segment(action, keys);
+    }
+}
+
+impl<K, D, T> Default for OrderedSet<K, D, T>
+where
+    K: Ord,
+    D: Data<Value = K>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    fn default() -> Self {
+        OrderedSet::new()
+    }
+}
+
+impl<K, D, T> std::iter::FromIterator<K> for OrderedSet<K, D, T>
+where
+    K: Ord,
+    D: Data<Value = K>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> Self {
+        let keys = sort_and_dedup(iter.into_iter().collect());
+        OrderedSet::from_tree(keys.into_iter().collect())
+    }
+}
+
+impl<K, D, T> Extend<K> for OrderedSet<K, D, T>
+where
+    K: Ord,
+    D: Data<Value = K>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert(key);
+        }
+    }
+}
+
+impl<K, D, T> IntoIterator for OrderedSet<K, D, T>
+where
+    K: Ord,
+    D: Data<Value = K>,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: SomeTreeRef<D>,
+{
+    type Item = K;
+    type IntoIter = T::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
+
+/// An ordered map from keys to values, stored in a tree of type `T`
+/// as key-value pairs. By default, the tree is a [`Treap`].
+///```
+/// use grove::collections::OrderedMap;
+///
+/// let mut counts: OrderedMap<&str, usize> = OrderedMap::new();
+/// for word in "the cat saw the other cat and the dog".split(' ') {
+///     counts.entry(word).and_modify(|count| *count += 1).or_insert(1);
+/// }
+/// assert_eq!(counts.get(&"the"), Some(&3));
+/// assert_eq!(counts.insert("cow", 5), None);
+/// assert_eq!(counts.insert("cow", 2), Some(5));
+/// assert_eq!(counts.remove(&"saw"), Some(1));
+///
+/// assert_eq!(counts.first_key_value(), Some((&"and", &1)));
+/// assert_eq!(counts.nth(2), Some((&"cow", &2)));
+/// assert_eq!(counts.rank(&"dog"), 3);
+/// let keys: Vec<_> = counts.range("c".."o").map(|(key, _)| *key).collect();
+/// assert_eq!(keys, vec!["cat", "cow", "dog"]);
+///```
+pub struct OrderedMap<K, V, D = SizeData<(K, V)>, T = Treap<D>> {
+    tree: T,
+    phantom: PhantomData<(K, V, D)>,
+}
+
+impl<K, V, D, T> OrderedMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        OrderedMap {
+            tree: Default::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a map of the key-value pairs in the tree.
+    /// The pairs have to be sorted by their keys, without duplicate keys.
+    pub fn from_tree(tree: T) -> Self {
+        OrderedMap {
+            tree,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the tree the key-value pairs are stored in.
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the tree the key-value pairs are stored in.
+    pub fn into_tree(self) -> T {
+        self.tree
+    }
+
+    /// Returns the number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size()
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Inserts the key-value pair into the map.
+    /// If the key was already in the map, its value is replaced, and the old value is returned.
+    /// The key itself isn't replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    /// Removes the key from the map, and returns its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        Some(self.remove_entry(key)?.1)
+    }
+
+    /// Removes the key from the map, and returns the stored key and its value.
+    pub fn remove_entry(&mut self, key: &K) -> Option<(K, V)> {
+        self.tree.search(ByKey((key,))).delete()
+    }
+
+    /// Returns true if the key is in the map.
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns the value of the key.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        Some(self.get_key_value(key)?.1)
+    }
+
+    /// Returns the stored key that is equal to the given key, and its value.
+    pub fn get_key_value(&mut self, key: &K) -> Option<(&K, &V)> {
+        let (key, value) = self.tree.iter_locator(ByKey((key,))).next()?;
+        Some((key, value))
+    }
+
+    /// Lets you modify the value of the key.
+    /// Returns [`None`] if the key isn't in the map.
+    pub fn with_value<F, R>(&mut self, key: &K, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut V) -> R,
+    {
+        self.tree
+            .search(ByKey((key,)))
+            .with_value(|(_, value)| f(value))
+    }
+
+    /// Gets the entry of the key in the map, for in-place manipulation.
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, D, T> {
+        let walker = self.tree.search(ByKey((&key,)));
+        if walker.is_empty() {
+            Entry::Vacant(VacantEntry { key, walker })
+        } else {
+            Entry::Occupied(OccupiedEntry { walker })
+        }
+    }
+
+    /// Returns the pair with the smallest key in the map.
+    pub fn first_key_value(&mut self) -> Option<(&K, &V)> {
+        self.nth(0)
+    }
+
+    /// Returns the pair with the largest key in the map.
+    pub fn last_key_value(&mut self) -> Option<(&K, &V)> {
+        let index = self.len().checked_sub(1)?;
+        self.nth(index)
+    }
+
+    /// Removes the pair with the smallest key in the map and returns it.
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        self.tree.slice(0).delete()
+    }
+
+    /// Removes the pair with the largest key in the map and returns it.
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let index = self.len().checked_sub(1)?;
+        self.tree.slice(index).delete()
+    }
+
+    /// Iterates over the key-value pairs of the map, in increasing order of keys.
+    pub fn iter(&mut self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.tree.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Iterates over the keys of the map, in increasing order.
+    pub fn keys(&mut self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Iterates over the values of the map, in increasing order of keys.
+    pub fn values(&mut self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Iterates over the key-value pairs of the map whose keys are in the given range,
+    /// in increasing order of keys.
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> impl Iterator<Item = (&K, &V)> + '_ {
+        let indices = index_range(&mut self.tree, range);
+        self.tree
+            .iter_locator(indices)
+            .map(|(key, value)| (key, value))
+    }
+
+    /// Returns the pair with the `index`-th smallest key in the map, starting from zero.
+    pub fn nth(&mut self, index: usize) -> Option<(&K, &V)> {
+        let (key, value) = self.tree.iter_locator(index).next()?;
+        Some((key, value))
+    }
+
+    /// Returns the number of keys in the map that are smaller than the given key.
+    pub fn rank(&mut self, key: &K) -> usize {
+        count_below(&mut self.tree, key)
+    }
+
+    /// Returns the summary of the key-value pairs whose keys are in the given range.
+    pub fn range_summary<R: RangeBounds<K>>(&mut self, range: R) -> D::Summary {
+        let keys = ByKey((range.start_bound(), range.end_bound()));
+        self.tree.segment_summary(keys)
+    }
+
+    /// Applies the action on the key-value pairs whose keys are in the given range.
+    /// The action must keep all of the keys in the map in sorted order, without duplicates.
+    pub fn range_act<R: RangeBounds<K>>(&mut self, range: R, action: D::Action) {
+        let keys = ByKey((range.start_bound(), range.end_bound()));
+        self.tree.act_segment(action, keys);
+    }
+}
+
+impl<K, V, D, T> Default for OrderedMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    fn default() -> Self {
+        OrderedMap::new()
+    }
+}
+
+/// If a key appears more than once, the last value is kept, as in [`Extend`].
+impl<K, V, D, T> std::iter::FromIterator<(K, V)> for OrderedMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let pairs = sort_and_dedup(iter.into_iter().collect());
+        OrderedMap::from_tree(pairs.into_iter().collect())
+    }
+}
+
+impl<K, V, D, T> Extend<(K, V)> for OrderedMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V, D, T> IntoIterator for OrderedMap<K, V, D, T>
+where
+    K: Ord,
+    D: Data<Value = (K, V)>,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: SomeTreeRef<D>,
+{
+    type Item = (K, V);
+    type IntoIter = T::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
+
+/// An entry of a key in an [`OrderedMap`], returned by [`OrderedMap::entry`].
+/// The entry holds a walker at the key's position in the tree.
+pub enum Entry<'a, K, V, D, T>
+where
+    D: Data<Value = (K, V)>,
+    T: 'a,
+    &'a mut T: ModifiableTreeRef<D>,
+{
+    /// The key is not in the map.
+    Vacant(VacantEntry<'a, K, V, D, T>),
+    /// The key is in the map.
+    Occupied(OccupiedEntry<'a, K, V, D, T>),
+}
+
+/// An entry of a key that is not in an [`OrderedMap`].
+pub struct VacantEntry<'a, K, V, D, T>
+where
+    D: Data<Value = (K, V)>,
+    T: 'a,
+    &'a mut T: ModifiableTreeRef<D>,
+{
+    key: K,
+    walker: <&'a mut T as ModifiableTreeRef<D>>::ModifiableWalker,
+}
+
+/// An entry of a key that is in an [`OrderedMap`].
+pub struct OccupiedEntry<'a, K, V, D, T>
+where
+    D: Data<Value = (K, V)>,
+    T: 'a,
+    &'a mut T: ModifiableTreeRef<D>,
+{
+    walker: <&'a mut T as ModifiableTreeRef<D>>::ModifiableWalker,
+}
+
+impl<'a, K, V, D, T> Entry<'a, K, V, D, T>
+where
+    D: Data<Value = (K, V)>,
+    D::Summary: SizedSummary,
+    T: 'a,
+    &'a mut T: ModifiableTreeRef<D>,
+{
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts the value if the key is not in the map.
+    /// Returns the entry, which is now occupied.
+    pub fn or_insert(self, default: V) -> OccupiedEntry<'a, K, V, D, T> {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of the function if the key is not in the map.
+    /// Returns the entry, which is now occupied.
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> OccupiedEntry<'a, K, V, D, T> {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Occupied(entry) => entry,
+        }
+    }
+
+    /// Inserts the default value if the key is not in the map.
+    /// Returns the entry, which is now occupied.
+    pub fn or_default(self) -> OccupiedEntry<'a, K, V, D, T>
+    where
+        V: Default,
+    {
+        self.or_insert_with(Default::default)
+    }
+
+    /// Modifies the value if the key is in the map.
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            entry.with_value(f);
+        }
+        self
+    }
+}
+
+impl<'a, K, V, D, T> VacantEntry<'a, K, V, D, T>
+where
+    D: Data<Value = (K, V)>,
+    D::Summary: SizedSummary,
+    T: 'a,
+    &'a mut T: ModifiableTreeRef<D>,
+{
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the key of the entry, without inserting it.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+
+    /// Inserts the key with the given value.
+    /// Returns the entry, which is now occupied.
+    pub fn insert(mut self, value: V) -> OccupiedEntry<'a, K, V, D, T> {
+        let index = self.walker.left_summary().size();
+        self.walker.insert((self.key, value)).unwrap();
+        // the walker may have moved while rebalancing
+        self.walker.go_to_root();
+        self.walker.search_subtree(index);
+        OccupiedEntry {
+            walker: self.walker,
+        }
+    }
+}
+
+impl<'a, K, V, D, T> OccupiedEntry<'a, K, V, D, T>
+where
+    D: Data<Value = (K, V)>,
+    T: 'a,
+    &'a mut T: ModifiableTreeRef<D>,
+{
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.walker.value().unwrap().0
+    }
+
+    /// Returns the value of the entry.
+    pub fn get(&self) -> &V {
+        &self.walker.value().unwrap().1
+    }
+
+    /// Lets you modify the value of the entry.
+    pub fn with_value<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut V) -> R,
+    {
+        self.walker.with_value(|(_, value)| f(value)).unwrap()
+    }
+
+    /// Replaces the value of the entry, and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        self.with_value(|old| std::mem::replace(old, value))
+    }
+
+    /// Removes the entry from the map, and returns its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry from the map, and returns its key and value.
+    pub fn remove_entry(mut self) -> (K, V) {
+        self.walker.delete().unwrap()
+    }
+}
@@ -0,0 +1,511 @@
+//! Maps and sets of disjoint ranges.
+//!
+//! A [`RangeMap`] assigns values to disjoint half-open ranges of keys, and a [`RangeSet`]
+//! is a set of disjoint half-open ranges. Touching ranges with equal values are always merged
+//! into a single range, so every map has a single representation.
+//!
+//! The ranges are stored in a tree, sorted by their start points. Updating a range of keys
+//! splits the ranges at its edges, splits the tree into the ranges before, inside and after it,
+//! and concatenates the parts back together, merging the ranges at the seams.
+//! This takes `O(log n)` time, besides the time it takes to handle the ranges inside it.
+
+use crate::example_data::Unit;
+use crate::locators::{ByKey, LeftEdgeOf, LocResult};
+use crate::treap::Treap;
+use crate::*;
+use std::ops::{Add, Range, Sub};
+
+/// A range in a [`RangeMap`], together with its value.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct RangeEntry<K, V> {
+    /// The range.
+    pub range: Range<K>,
+    /// The value of the keys in the range.
+    pub value: V,
+}
+
+/// Ranges are keyed by their start points.
+impl<K: Ord, V> Keyed<K> for RangeEntry<K, V> {
+    fn get_key(&self) -> &K {
+        &self.range.start
+    }
+}
+
+/// The summary of a segment of ranges.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub struct RangeSummary<K> {
+    /// The total length of the ranges in the segment.
+    pub covered: K,
+    /// The number of ranges in the segment.
+    pub size: usize,
+}
+
+impl<K: Add<Output = K>> Add for RangeSummary<K> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        RangeSummary {
+            covered: self.covered + other.covered,
+            size: self.size + other.size,
+        }
+    }
+}
+
+impl<K> SizedSummary for RangeSummary<K> {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl<K: Copy + Sub<Output = K>, V> ToSummary<RangeSummary<K>> for RangeEntry<K, V> {
+    fn to_summary(&self) -> RangeSummary<K> {
+        RangeSummary {
+            covered: self.range.end - self.range.start,
+            size: 1,
+        }
+    }
+}
+
+/// The data stored in the tree of a [`RangeMap`].
+pub type RangeData<K, V> = (RangeEntry<K, V>, RangeSummary<K>, Unit);
+
+/// Returns a locator for the range that contains the point.
+fn containing<K: Ord + Copy, V>(
+    point: K,
+) -> impl Fn(&RangeSummary<K>, &RangeEntry<K, V>, &RangeSummary<K>) -> LocResult + Clone {
+    move |_, entry, _| {
+        if entry.range.end <= point {
+            LocResult::GoRight
+        } else if entry.range.start > point {
+            LocResult::GoLeft
+        } else {
+            LocResult::Accept
+        }
+    }
+}
+
+/// Returns a locator for the ranges that overlap the range.
+fn overlapping<K: Ord + Copy, V>(
+    range: Range<K>,
+) -> impl Fn(&RangeSummary<K>, &RangeEntry<K, V>, &RangeSummary<K>) -> LocResult + Clone {
+    move |_, entry, _| {
+        if entry.range.end <= range.start {
+            LocResult::GoRight
+        } else if entry.range.start >= range.end {
+            LocResult::GoLeft
+        } else {
+            LocResult::Accept
+        }
+    }
+}
+
+/// Pushes the entry to the end of the vector, merging it with the last entry if they
+/// touch and have equal values.
+fn push_merging<K: Ord + Copy, V: PartialEq>(
+    entries: &mut Vec<RangeEntry<K, V>>,
+    entry: RangeEntry<K, V>,
+) {
+    match entries.last_mut() {
+        Some(last) if last.range.end == entry.range.start && last.value == entry.value => {
+            last.range.end = entry.range.end;
+        }
+        _ => entries.push(entry),
+    }
+}
+
+/// A map from disjoint half-open ranges of keys to values, stored in a tree of type `T`.
+/// By default, the tree is a [`Treap`].
+///
+/// Keys have to support addition and subtraction, in order to compute the lengths of ranges.
+///```
+/// use grove::collections::RangeMap;
+///
+/// let mut owners: RangeMap<u32, &str> = RangeMap::new();
+/// owners.assign(0..10, "alice");
+/// owners.assign(5..15, "bob");
+/// owners.insert_range(0..20, "carol"); // only fills the gaps
+/// owners.remove_range(8..9);
+/// assert_eq!(owners.get(3), Some(&"alice"));
+/// assert_eq!(owners.get(8), None);
+/// assert_eq!(owners.covered_len(), 19);
+///
+/// let ranges: Vec<_> = owners.iter().map(|(range, owner)| (range.clone(), *owner)).collect();
+/// assert_eq!(ranges, vec![(0..5, "alice"), (5..8, "bob"), (9..15, "bob"), (15..20, "carol")]);
+/// assert_eq!(owners.gaps(0..30).collect::<Vec<_>>(), vec![8..9, 20..30]);
+///```
+pub struct RangeMap<K, V, T = Treap<RangeData<K, V>>> {
+    tree: T,
+    phantom: std::marker::PhantomData<(K, V)>,
+}
+
+impl<K, V, T> RangeMap<K, V, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    V: Clone + PartialEq,
+    T: ConcatenableTree<RangeData<K, V>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, V>, T = T>,
+{
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        RangeMap {
+            tree: Default::default(),
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns the tree the ranges are stored in.
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the tree the ranges are stored in.
+    pub fn into_tree(self) -> T {
+        self.tree
+    }
+
+    /// Returns the number of ranges in the map.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size
+    }
+
+    /// Returns true if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the total length of the ranges in the map.
+    pub fn covered_len(&self) -> K {
+        self.tree.subtree_summary().covered
+    }
+
+    /// Returns the value of the key, or [`None`] if the key isn't in any range.
+    /// See [`SomeTree::iter`] as to why this requires mutable access.
+    pub fn get(&mut self, key: K) -> Option<&V> {
+        self.get_range_value(key).map(|(_, value)| value)
+    }
+
+    /// Returns the range that contains the key and its value,
+    /// or [`None`] if the key isn't in any range.
+    pub fn get_range_value(&mut self, key: K) -> Option<(&Range<K>, &V)> {
+        let entry = self.tree.iter_locator(containing(key)).next()?;
+        Some((&entry.range, &entry.value))
+    }
+
+    /// Iterates over the ranges and their values, in order.
+    /// See [`SomeTree::iter`] as to why this requires mutable access.
+    pub fn iter(&mut self) -> impl Iterator<Item = (&Range<K>, &V)> + '_ {
+        self.tree.iter().map(|entry| (&entry.range, &entry.value))
+    }
+
+    /// Iterates over the maximal ranges inside the given range that aren't covered by the map,
+    /// in order.
+    pub fn gaps(&mut self, range: Range<K>) -> impl Iterator<Item = Range<K>> + '_ {
+        let Range { start, end } = range;
+        let mut cursor = start;
+        // the empty range at the end makes sure the last gap is found
+        let mut ranges = self
+            .tree
+            .iter_locator(overlapping(start..end))
+            .map(|entry| entry.range.clone())
+            .chain(std::iter::once(end..end));
+        std::iter::from_fn(move || loop {
+            let next = ranges.next()?;
+            let gap = cursor..next.start;
+            cursor = std::cmp::max(cursor, next.end);
+            if gap.start < gap.end {
+                return Some(gap);
+            }
+        })
+    }
+
+    /// Assigns the value to all of the keys in the range,
+    /// overwriting the values that are already there.
+    /// If the range is empty, nothing happens.
+    pub fn assign(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        let (left, _, right) = self.extract(range.clone());
+        self.assemble(left, vec![RangeEntry { range, value }], right);
+    }
+
+    /// Assigns the value to the keys in the range that don't have values yet,
+    /// keeping the values that are already there.
+    /// If the range is empty, nothing happens.
+    pub fn insert_range(&mut self, range: Range<K>, value: V) {
+        if range.start >= range.end {
+            return;
+        }
+        let (left, mid, right) = self.extract(range.clone());
+        let mut entries = vec![];
+        let mut cursor = range.start;
+        for entry in mid {
+            if cursor < entry.range.start {
+                let gap = cursor..entry.range.start;
+                push_merging(
+                    &mut entries,
+                    RangeEntry {
+                        range: gap,
+                        value: value.clone(),
+                    },
+                );
+            }
+            cursor = entry.range.end;
+            push_merging(&mut entries, entry);
+        }
+        if cursor < range.end {
+            let gap = cursor..range.end;
+            push_merging(&mut entries, RangeEntry { range: gap, value });
+        }
+        self.assemble(left, entries, right);
+    }
+
+    /// Removes the keys in the range from the map.
+    /// If the range is empty, nothing happens.
+    pub fn remove_range(&mut self, range: Range<K>) {
+        if range.start >= range.end {
+            return;
+        }
+        let (mut left, _, right) = self.extract(range);
+        left.concatenate_right(right);
+        self.tree = left;
+    }
+
+    /// If there is a range that contains the point, and doesn't start at it,
+    /// splits it in two at the point.
+    fn split_at(&mut self, point: K) {
+        let mut walker = self.tree.search(containing(point));
+        let right = walker.with_value(|entry| {
+            if entry.range.start == point {
+                return None;
+            }
+            let right = RangeEntry {
+                range: point..entry.range.end,
+                value: entry.value.clone(),
+            };
+            entry.range.end = point;
+            Some(right)
+        });
+        if let Some(Some(right)) = right {
+            walker.next_empty().unwrap(); // not at an empty position
+            walker.insert(right).unwrap();
+        }
+    }
+
+    /// Splits the tree into the ranges before, inside and after the given range,
+    /// splitting the ranges that cross its edges.
+    /// Leaves the map empty.
+    fn extract(&mut self, range: Range<K>) -> (T, T, T) {
+        self.split_at(range.start);
+        self.split_at(range.end);
+        let mut mid = self
+            .tree
+            .slice(LeftEdgeOf(ByKey(&range.start..)))
+            .split_right()
+            .unwrap();
+        let right = mid
+            .slice(LeftEdgeOf(ByKey(&range.end..)))
+            .split_right()
+            .unwrap();
+        (std::mem::take(&mut self.tree), mid, right)
+    }
+
+    /// Concatenates the trees and the ranges back into the map,
+    /// merging the ranges at the seams if they touch and have equal values.
+    /// The ranges in `mid` should already be merged.
+    fn assemble(&mut self, mut left: T, mut mid: Vec<RangeEntry<K, V>>, mut right: T) {
+        if let Some(first) = mid.first_mut() {
+            let len = left.subtree_summary().size;
+            if len > 0 {
+                let mut walker = left.search(len - 1);
+                let value = walker.value().unwrap();
+                if value.range.end == first.range.start && value.value == first.value {
+                    first.range.start = walker.delete().unwrap().range.start;
+                }
+            }
+        }
+        if let Some(last) = mid.last_mut() {
+            let mut walker = right.search(0);
+            if let Some(value) = walker.value() {
+                if value.range.start == last.range.end && value.value == last.value {
+                    last.range.end = walker.delete().unwrap().range.end;
+                }
+            }
+        }
+        left.concatenate_right(mid.into_iter().collect());
+        left.concatenate_right(right);
+        self.tree = left;
+    }
+}
+
+impl<K, V, T> Default for RangeMap<K, V, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    V: Clone + PartialEq,
+    T: ConcatenableTree<RangeData<K, V>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, V>, T = T>,
+{
+    fn default() -> Self {
+        RangeMap::new()
+    }
+}
+
+impl<K, V, T> std::iter::FromIterator<(Range<K>, V)> for RangeMap<K, V, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    V: Clone + PartialEq,
+    T: ConcatenableTree<RangeData<K, V>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, V>, T = T>,
+{
+    /// Later ranges overwrite earlier ranges, as in [`RangeMap::assign`].
+    fn from_iter<I: IntoIterator<Item = (Range<K>, V)>>(iter: I) -> Self {
+        let mut map = RangeMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K, V, T> Extend<(Range<K>, V)> for RangeMap<K, V, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    V: Clone + PartialEq,
+    T: ConcatenableTree<RangeData<K, V>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, V>, T = T>,
+{
+    /// Later ranges overwrite earlier ranges, as in [`RangeMap::assign`].
+    fn extend<I: IntoIterator<Item = (Range<K>, V)>>(&mut self, iter: I) {
+        for (range, value) in iter {
+            self.assign(range, value);
+        }
+    }
+}
+
+/// A set of keys, represented as disjoint half-open ranges, stored in a tree of type `T`.
+/// By default, the tree is a [`Treap`].
+///
+/// This is a [`RangeMap`] whose values are `()`.
+///```
+/// use grove::collections::RangeSet;
+///
+/// let mut free: RangeSet<u64> = RangeSet::new();
+/// free.insert_range(0..100);
+/// free.remove_range(10..20);
+/// free.insert_range(15..30);
+/// assert_eq!(free.iter().cloned().collect::<Vec<_>>(), vec![0..10, 15..100]);
+/// assert!(free.contains(15));
+/// assert!(!free.contains(12));
+/// assert_eq!(free.covered_len(), 95);
+/// assert_eq!(free.gaps(0..200).collect::<Vec<_>>(), vec![10..15, 100..200]);
+///```
+pub struct RangeSet<K, T = Treap<RangeData<K, ()>>> {
+    map: RangeMap<K, (), T>,
+}
+
+impl<K, T> RangeSet<K, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    T: ConcatenableTree<RangeData<K, ()>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, ()>, T = T>,
+{
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        RangeSet {
+            map: RangeMap::new(),
+        }
+    }
+
+    /// Returns the tree the ranges are stored in.
+    pub fn tree(&self) -> &T {
+        self.map.tree()
+    }
+
+    /// Returns the tree the ranges are stored in.
+    pub fn into_tree(self) -> T {
+        self.map.into_tree()
+    }
+
+    /// Returns the number of ranges in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns true if the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the total length of the ranges in the set.
+    pub fn covered_len(&self) -> K {
+        self.map.covered_len()
+    }
+
+    /// Returns true if the key is in the set.
+    /// See [`SomeTree::iter`] as to why this requires mutable access.
+    pub fn contains(&mut self, key: K) -> bool {
+        self.map.get(key).is_some()
+    }
+
+    /// Returns the range in the set that contains the key,
+    /// or [`None`] if the key isn't in the set.
+    pub fn get_range(&mut self, key: K) -> Option<&Range<K>> {
+        self.map.get_range_value(key).map(|(range, _)| range)
+    }
+
+    /// Iterates over the ranges of the set, in order.
+    /// See [`SomeTree::iter`] as to why this requires mutable access.
+    pub fn iter(&mut self) -> impl Iterator<Item = &Range<K>> + '_ {
+        self.map.iter().map(|(range, _)| range)
+    }
+
+    /// Iterates over the maximal ranges inside the given range that aren't in the set,
+    /// in order.
+    pub fn gaps(&mut self, range: Range<K>) -> impl Iterator<Item = Range<K>> + '_ {
+        self.map.gaps(range)
+    }
+
+    /// Adds the keys in the range to the set.
+    pub fn insert_range(&mut self, range: Range<K>) {
+        self.map.assign(range, ());
+    }
+
+    /// Removes the keys in the range from the set.
+    pub fn remove_range(&mut self, range: Range<K>) {
+        self.map.remove_range(range);
+    }
+}
+
+impl<K, T> Default for RangeSet<K, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    T: ConcatenableTree<RangeData<K, ()>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, ()>, T = T>,
+{
+    fn default() -> Self {
+        RangeSet::new()
+    }
+}
+
+impl<K, T> std::iter::FromIterator<Range<K>> for RangeSet<K, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    T: ConcatenableTree<RangeData<K, ()>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, ()>, T = T>,
+{
+    fn from_iter<I: IntoIterator<Item = Range<K>>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<K, T> Extend<Range<K>> for RangeSet<K, T>
+where
+    K: Ord + Copy + Default + Add<Output = K> + Sub<Output = K>,
+    T: ConcatenableTree<RangeData<K, ()>>,
+    for<'a> &'a mut T: SplittableTreeRef<RangeData<K, ()>, T = T>,
+{
+    fn extend<I: IntoIterator<Item = Range<K>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert_range(range);
+        }
+    }
+}
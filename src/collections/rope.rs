@@ -0,0 +1,626 @@
+//! A text buffer type, with UTF-8 aware indexing.
+//!
+//! A [`Rope`] stores its text in a tree of string chunks. The summaries of the chunks
+//! count bytes, chars, UTF-16 code units and line breaks, so that positions in
+//! each of these metrics can be located and converted to each other in `O(log n)` time.
+//! Inserting and removing text anywhere in the rope is done by splitting and concatenating
+//! the tree, and takes `O(log n)` time as well, besides copying the inserted text.
+
+use crate::locators::LocResult;
+use crate::treap::Treap;
+use crate::*;
+use example_data::Unit;
+use std::ops::{Add, Bound, Range, RangeBounds};
+
+/// The maximum number of bytes in a chunk.
+/// Chunks are split and merged so that they don't get any longer than this.
+const MAX_CHUNK_BYTES: usize = 512;
+
+/// The metrics by which positions in a [`Rope`] can be measured.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Metric {
+    /// Positions are measured in bytes of the UTF-8 encoding of the text.
+    Bytes,
+    /// Positions are measured in unicode scalar values, i.e., [`char`]s.
+    Chars,
+    /// Positions are measured in code units of the UTF-16 encoding of the text.
+    Utf16,
+    /// Positions are measured in line breaks, i.e., `'\n'` characters.
+    LineBreaks,
+}
+
+/// The summary of a piece of text.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+pub struct TextSummary {
+    /// The number of bytes in the text.
+    pub bytes: usize,
+    /// The number of chars in the text.
+    pub chars: usize,
+    /// The number of UTF-16 code units in the text.
+    pub utf16: usize,
+    /// The number of line breaks in the text.
+    pub line_breaks: usize,
+}
+
+impl TextSummary {
+    /// Computes the summary of a string.
+    pub fn of(text: &str) -> Self {
+        text.chars()
+            .map(TextSummary::of_char)
+            .fold(Default::default(), Add::add)
+    }
+
+    /// Computes the summary of a single char.
+    pub fn of_char(c: char) -> Self {
+        TextSummary {
+            bytes: c.len_utf8(),
+            chars: 1,
+            utf16: c.len_utf16(),
+            line_breaks: (c == '\n') as usize,
+        }
+    }
+
+    /// Returns the size of the text in the given metric.
+    pub fn get(&self, metric: Metric) -> usize {
+        match metric {
+            Metric::Bytes => self.bytes,
+            Metric::Chars => self.chars,
+            Metric::Utf16 => self.utf16,
+            Metric::LineBreaks => self.line_breaks,
+        }
+    }
+}
+
+impl Add for TextSummary {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        TextSummary {
+            bytes: self.bytes + other.bytes,
+            chars: self.chars + other.chars,
+            utf16: self.utf16 + other.utf16,
+            line_breaks: self.line_breaks + other.line_breaks,
+        }
+    }
+}
+
+/// A piece of the text of a [`Rope`], together with its summary.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Chunk {
+    text: String,
+    summary: TextSummary,
+}
+
+impl Chunk {
+    fn new(text: String) -> Self {
+        let summary = TextSummary::of(&text);
+        Chunk { text, summary }
+    }
+
+    /// Returns the text of the chunk.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the summary of the chunk's text.
+    pub fn summary(&self) -> TextSummary {
+        self.summary
+    }
+
+    /// Modifies the text, and then recomputes the summary.
+    fn modify<F: FnOnce(&mut String)>(&mut self, f: F) {
+        f(&mut self.text);
+        self.summary = TextSummary::of(&self.text);
+    }
+}
+
+impl ToSummary<TextSummary> for Chunk {
+    fn to_summary(&self) -> TextSummary {
+        self.summary
+    }
+}
+
+/// The data stored in the tree of a [`Rope`].
+pub type RopeData = (Chunk, TextSummary, Unit);
+
+/// A locator for the chunks that contain the given positions, measured in the given metric.
+/// `ByMetric(metric, pos)` locates the chunk that contains position `pos`,
+/// and `ByMetric(metric, start..end)` locates the chunks that overlap the range.
+///
+/// Positions in the [`Metric::LineBreaks`] metric are the indices of the line breaks:
+/// `ByMetric(Metric::LineBreaks, i)` locates the chunk that contains the `i`-th line break.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct ByMetric<R>(pub Metric, pub R);
+
+impl Locator<RopeData> for ByMetric<Range<usize>> {
+    fn locate(&self, left: &TextSummary, chunk: &Chunk, _right: &TextSummary) -> LocResult {
+        let ByMetric(metric, range) = self;
+        let start = left.get(*metric);
+        let end = start + chunk.summary.get(*metric);
+        if end <= range.start {
+            LocResult::GoRight
+        } else if start >= range.end {
+            LocResult::GoLeft
+        } else {
+            LocResult::Accept
+        }
+    }
+}
+
+impl Locator<RopeData> for ByMetric<usize> {
+    fn locate(&self, left: &TextSummary, chunk: &Chunk, right: &TextSummary) -> LocResult {
+        ByMetric(self.0, self.1..self.1 + 1).locate(left, chunk, right)
+    }
+}
+
+/// A locator for the gap between chunks at the given char index,
+/// which must be on a chunk boundary.
+fn char_boundary(index: usize) -> ByMetric<Range<usize>> {
+    ByMetric(Metric::Chars, index..index)
+}
+
+/// Returns the summary of the longest prefix of the text whose size in the metric
+/// is at most `pos`.
+fn chunk_prefix_summary(text: &str, metric: Metric, pos: usize) -> TextSummary {
+    let mut acc = TextSummary::default();
+    for c in text.chars() {
+        let next = acc + TextSummary::of_char(c);
+        if next.get(metric) > pos {
+            break;
+        }
+        acc = next;
+    }
+    acc
+}
+
+/// Returns the byte index of the char at the given char index,
+/// or the length of the text if the index is at its end.
+fn byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(index, _)| index)
+}
+
+/// Splits the text into chunks that aren't longer than [`MAX_CHUNK_BYTES`].
+fn into_chunks(mut text: &str) -> impl Iterator<Item = Chunk> + '_ {
+    std::iter::from_fn(move || {
+        if text.is_empty() {
+            return None;
+        }
+        let mut end = text.len().min(MAX_CHUNK_BYTES);
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, rest) = text.split_at(end);
+        text = rest;
+        Some(Chunk::new(chunk.to_string()))
+    })
+}
+
+/// Resolves a range of char indices into its start and end.
+///
+/// # Panics
+///
+/// Panics if the range is decreasing or ends after `len`.
+fn char_range<R: RangeBounds<usize>>(range: R, len: usize) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end + 1,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "char range starts at {} but ends at {}",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "char range end (is {}) should be <= len (is {})",
+        end,
+        len
+    );
+    start..end
+}
+
+/// A text buffer, stored as a tree of string chunks.
+/// By default, the tree is a [`Treap`], but any tree that can be split and concatenated
+/// can be used, such as a [`SplayTree`](crate::splay::SplayTree).
+///
+/// All indices are char indices, unless stated otherwise.
+///```
+/// use grove::collections::Rope;
+///
+/// let mut rope: Rope = Rope::from("hello\nworld");
+/// rope.insert_str(5, ", dear");
+/// rope.insert_str(12, "big wide\n");
+/// assert_eq!(rope.len_lines(), 3);
+/// assert_eq!(rope.line_to_char(2), 21);
+/// assert_eq!(rope.char_to_line(15), 1);
+///
+/// rope.remove(5..11);
+/// assert_eq!(rope.slice(6..).to_string(), "big wide\nworld");
+/// assert_eq!(rope.to_string(), "hello\nbig wide\nworld");
+///
+/// let mut emoji: Rope = Rope::from("a😀b");
+/// assert_eq!(emoji.len_chars(), 3);
+/// assert_eq!(emoji.len_bytes(), 6);
+/// assert_eq!(emoji.char_to_utf16(2), 3);
+///```
+pub struct Rope<T = Treap<RopeData>> {
+    tree: T,
+}
+
+impl<T> Rope<T>
+where
+    T: ConcatenableTree<RopeData>,
+    for<'a> &'a mut T: SplittableTreeRef<RopeData, T = T>,
+{
+    /// Creates an empty rope.
+    pub fn new() -> Self {
+        Rope {
+            tree: Default::default(),
+        }
+    }
+
+    /// Returns the tree the chunks are stored in.
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// Returns the tree the chunks are stored in.
+    pub fn into_tree(self) -> T {
+        self.tree
+    }
+
+    /// Returns the summary of the whole text.
+    pub fn summary(&self) -> TextSummary {
+        self.tree.subtree_summary()
+    }
+
+    /// Returns the length of the text in bytes.
+    pub fn len_bytes(&self) -> usize {
+        self.summary().bytes
+    }
+
+    /// Returns the length of the text in chars.
+    pub fn len_chars(&self) -> usize {
+        self.summary().chars
+    }
+
+    /// Returns the length of the text in UTF-16 code units.
+    pub fn len_utf16(&self) -> usize {
+        self.summary().utf16
+    }
+
+    /// Returns the number of lines in the text.
+    /// This is one more than the number of line breaks, so an empty text has one line.
+    pub fn len_lines(&self) -> usize {
+        self.summary().line_breaks + 1
+    }
+
+    /// Returns true if the text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Iterates over the chunks of the text, in order.
+    /// See [`SomeTree::iter`] as to why this requires mutable access.
+    pub fn chunks(&mut self) -> impl Iterator<Item = &str> + '_ {
+        self.tree.iter().map(Chunk::as_str)
+    }
+
+    /// Returns the summary of the longest prefix of the text whose size in the metric
+    /// is at most `pos`.
+    pub fn prefix_summary(&mut self, metric: Metric, pos: usize) -> TextSummary {
+        let walker = self.tree.search(ByMetric(metric, pos));
+        match walker.value() {
+            None => walker.left_summary() + walker.right_summary(),
+            Some(chunk) => {
+                let left = walker.left_summary();
+                left + chunk_prefix_summary(&chunk.text, metric, pos - left.get(metric))
+            }
+        }
+    }
+
+    /// Converts a char index to a byte index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_index > len_chars`.
+    pub fn char_to_byte(&mut self, char_index: usize) -> usize {
+        self.assert_char_index(char_index);
+        self.prefix_summary(Metric::Chars, char_index).bytes
+    }
+
+    /// Converts a byte index to a char index.
+    /// If the byte index is in the middle of a char, returns the index of that char.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_index > len_bytes`.
+    pub fn byte_to_char(&mut self, byte_index: usize) -> usize {
+        let len = self.len_bytes();
+        assert!(
+            byte_index <= len,
+            "byte index (is {}) should be <= len_bytes (is {})",
+            byte_index,
+            len
+        );
+        self.prefix_summary(Metric::Bytes, byte_index).chars
+    }
+
+    /// Converts a char index to a UTF-16 code unit index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_index > len_chars`.
+    pub fn char_to_utf16(&mut self, char_index: usize) -> usize {
+        self.assert_char_index(char_index);
+        self.prefix_summary(Metric::Chars, char_index).utf16
+    }
+
+    /// Converts a UTF-16 code unit index to a char index.
+    /// If the index is in the middle of a surrogate pair, returns the index of its char.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `utf16_index > len_utf16`.
+    pub fn utf16_to_char(&mut self, utf16_index: usize) -> usize {
+        let len = self.len_utf16();
+        assert!(
+            utf16_index <= len,
+            "utf16 index (is {}) should be <= len_utf16 (is {})",
+            utf16_index,
+            len
+        );
+        self.prefix_summary(Metric::Utf16, utf16_index).chars
+    }
+
+    /// Returns the index of the line that contains the char at the given index.
+    /// The end of the text is considered to be a part of the last line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_index > len_chars`.
+    pub fn char_to_line(&mut self, char_index: usize) -> usize {
+        self.assert_char_index(char_index);
+        self.prefix_summary(Metric::Chars, char_index).line_breaks
+    }
+
+    /// Returns the char index of the start of the given line.
+    /// If `line == len_lines`, returns `len_chars`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line > len_lines`.
+    pub fn line_to_char(&mut self, line: usize) -> usize {
+        let len = self.len_lines();
+        assert!(
+            line <= len,
+            "line index (is {}) should be <= len_lines (is {})",
+            line,
+            len
+        );
+        if line == 0 {
+            0
+        } else if line == len {
+            self.len_chars()
+        } else {
+            // the prefix ends right before the line break that ends the previous line
+            self.prefix_summary(Metric::LineBreaks, line - 1).chars + 1
+        }
+    }
+
+    /// Inserts the text at the given char index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_index > len_chars`.
+    pub fn insert_str(&mut self, char_index: usize, text: &str) {
+        self.assert_char_index(char_index);
+        if text.is_empty() {
+            return;
+        }
+        // try to insert the text into the chunk that contains the previous char,
+        // so that repeated insertions at the same place don't create many small chunks.
+        let mut walker = self
+            .tree
+            .search(ByMetric(Metric::Chars, char_index.saturating_sub(1)));
+        let offset = char_index - walker.left_summary().chars;
+        let inserted = walker.with_value(|chunk| {
+            if chunk.text.len() + text.len() > MAX_CHUNK_BYTES {
+                return false;
+            }
+            chunk.modify(|s| s.insert_str(byte_index(s, offset), text));
+            true
+        });
+        drop(walker);
+        if inserted != Some(true) {
+            let right = self.split_off(char_index);
+            self.append(into_chunks(text).collect());
+            self.append(right);
+        }
+    }
+
+    /// Removes the text in the given range of chars.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is decreasing or ends after `len_chars`.
+    pub fn remove<R: RangeBounds<usize>>(&mut self, range: R) {
+        let Range { start, end } = char_range(range, self.len_chars());
+        if start == end {
+            return;
+        }
+        // if the range is strictly inside one chunk, remove it from the chunk in place.
+        let mut walker = self.tree.search(ByMetric(Metric::Chars, start));
+        let offset = start - walker.left_summary().chars;
+        let removed = walker.with_value(|chunk| {
+            if offset == 0 && end - start == chunk.summary.chars
+                || offset + end - start > chunk.summary.chars
+            {
+                return false;
+            }
+            chunk.modify(|s| {
+                s.replace_range(
+                    byte_index(s, offset)..byte_index(s, offset + end - start),
+                    "",
+                )
+            });
+            true
+        });
+        drop(walker);
+        if removed != Some(true) {
+            let right = self.split_off(end);
+            self.split_off(start);
+            self.append(right);
+        }
+    }
+
+    /// Returns a copy of the text in the given range of chars.
+    /// This takes `O(log n)` time, besides copying the text itself.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is decreasing or ends after `len_chars`.
+    pub fn slice<R: RangeBounds<usize>>(&mut self, range: R) -> Self {
+        let Range { start, end } = char_range(range, self.len_chars());
+        let right = self.split_off(end);
+        let mut mid = self.split_off(start);
+        let res = Rope {
+            tree: mid.tree.iter().cloned().collect(),
+        };
+        self.append(mid);
+        self.append(right);
+        res
+    }
+
+    /// Splits the rope in two at the given char index.
+    /// Returns the text from the index onwards, and keeps the text before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_index > len_chars`.
+    pub fn split_off(&mut self, char_index: usize) -> Self {
+        self.assert_char_index(char_index);
+        self.split_chunk_at(char_index);
+        let right = self
+            .tree
+            .slice(char_boundary(char_index))
+            .split_right()
+            .unwrap();
+        Rope { tree: right }
+    }
+
+    /// Appends the text of `other` to the end of this rope.
+    pub fn append(&mut self, other: Self) {
+        let seam = self.len_chars();
+        self.tree.concatenate_right(other.tree);
+        self.merge_chunks_at(seam);
+    }
+
+    /// Makes sure that the given char index is on a chunk boundary,
+    /// by splitting the chunk that contains it, if there is one.
+    fn split_chunk_at(&mut self, char_index: usize) {
+        let mut walker = self.tree.search(ByMetric(Metric::Chars, char_index));
+        let offset = char_index - walker.left_summary().chars;
+        if offset == 0 {
+            return;
+        }
+        let right = walker
+            .with_value(|chunk| {
+                let mut right = String::new();
+                chunk.modify(|s| right = s.split_off(byte_index(s, offset)));
+                Chunk::new(right)
+            })
+            .unwrap();
+        walker.next_empty().unwrap();
+        walker.insert(right).unwrap();
+    }
+
+    /// If the given char index is on the boundary between two chunks that fit together
+    /// in one chunk, merges them.
+    fn merge_chunks_at(&mut self, char_index: usize) {
+        if char_index == 0 {
+            return;
+        }
+        let mut walker = self.tree.search(ByMetric(Metric::Chars, char_index));
+        let right_len = match walker.value() {
+            Some(chunk) if walker.left_summary().chars == char_index => chunk.text.len(),
+            _ => return,
+        };
+        walker.previous_filled().unwrap();
+        if walker.value().unwrap().text.len() + right_len > MAX_CHUNK_BYTES {
+            return;
+        }
+        walker.next_filled().unwrap();
+        let right = walker.delete().unwrap();
+        drop(walker);
+        self.tree
+            .search(ByMetric(Metric::Chars, char_index - 1))
+            .with_value(|chunk| chunk.modify(|s| s.push_str(&right.text)))
+            .unwrap();
+    }
+
+    fn assert_char_index(&self, char_index: usize) {
+        let len = self.len_chars();
+        assert!(
+            char_index <= len,
+            "char index (is {}) should be <= len_chars (is {})",
+            char_index,
+            len
+        );
+    }
+}
+
+impl<T> Default for Rope<T>
+where
+    T: ConcatenableTree<RopeData>,
+    for<'a> &'a mut T: SplittableTreeRef<RopeData, T = T>,
+{
+    fn default() -> Self {
+        Rope::new()
+    }
+}
+
+impl<T> From<&str> for Rope<T>
+where
+    T: ConcatenableTree<RopeData>,
+    for<'a> &'a mut T: SplittableTreeRef<RopeData, T = T>,
+{
+    fn from(text: &str) -> Self {
+        into_chunks(text).collect()
+    }
+}
+
+impl<T> std::iter::FromIterator<Chunk> for Rope<T>
+where
+    T: ConcatenableTree<RopeData>,
+    for<'a> &'a mut T: SplittableTreeRef<RopeData, T = T>,
+{
+    fn from_iter<I: IntoIterator<Item = Chunk>>(iter: I) -> Self {
+        Rope {
+            tree: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> std::fmt::Display for Rope<T>
+where
+    T: ConcatenableTree<RopeData> + AsBasicTree<RopeData>,
+    for<'a> &'a mut T: SplittableTreeRef<RopeData, T = T>,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // chunks are never acted upon, so they are all clean.
+        // iterating instead of recursing, since splay trees can be very deep
+        for chunk in self.tree.as_basic_tree().iter_imm() {
+            f.write_str(&chunk.stored().text)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! A sequence type with a [`Vec`]-like interface.
+//!
+//! A [`Seq`] stores its values in a balanced tree, indexed by position.
+//! Accessing, inserting and removing values anywhere in the sequence take `O(log n)` time,
+//! and so do splitting a sequence in two and appending two sequences.
+
+use crate::example_data::SizeData;
+use crate::treap::Treap;
+use crate::trees::basic_tree::{BasicNode, BasicTree};
+use crate::*;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+
+/// A sequence of values, stored in a tree of type `Tree`.
+/// By default, the tree is a [`Treap`].
+///```
+/// use grove::collections::Seq;
+///
+/// let mut seq: Seq<char> = "grove".chars().collect();
+/// seq.insert(2, 'a');
+/// seq.push_front('!');
+/// assert_eq!(seq.remove(4), 'o');
+/// assert_eq!(seq[2], 'r');
+/// *seq.get_mut(0).unwrap() = '?';
+///
+/// let mut tail = seq.split_off(3);
+/// assert_eq!(tail.pop_back(), Some('e'));
+/// seq.append(&mut tail);
+/// seq.extend("st".chars());
+/// assert_eq!(seq.into_iter().collect::<String>(), "?gravst");
+///```
+pub struct Seq<T, Tree = Treap<SizeData<T>>> {
+    tree: Tree,
+    phantom: PhantomData<T>,
+}
+
+impl<T, Tree> Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    /// Creates an empty sequence.
+    pub fn new() -> Self {
+        Seq::from_tree(Default::default())
+    }
+
+    /// Creates a sequence of the values in the tree.
+    pub fn from_tree(tree: Tree) -> Self {
+        Seq {
+            tree,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the tree the values are stored in.
+    pub fn tree(&self) -> &Tree {
+        &self.tree
+    }
+
+    /// Returns the tree the values are stored in.
+    pub fn into_tree(self) -> Tree {
+        self.tree
+    }
+
+    /// Returns the number of values in the sequence.
+    pub fn len(&self) -> usize {
+        self.tree.subtree_summary().size
+    }
+
+    /// Returns true if the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Iterates over the values of the sequence.
+    /// See [`SomeTree::iter`] as to why this requires mutable access.
+    pub fn iter(
+        &mut self,
+    ) -> basic_tree::iterators::IterLocator<'_, SizeData<T>, std::ops::RangeFull, Tree::TreeData>
+    {
+        self.tree.iter()
+    }
+}
+
+impl<T, Tree> Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>> + AsBasicTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    /// Returns the value at the given index,
+    /// or [`None`] if the index is out of bounds.
+    ///
+    /// This only reads the tree, so a [`SplayTree`](crate::splay::SplayTree) isn't splayed,
+    /// which may undermine its complexity guarantees. With splay trees, prefer [`Seq::get_mut`].
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let node = node_at(self.tree.as_basic_tree(), index)?;
+        // values of `SizeData` are never acted upon, so the value is clean
+        Some(&node.node_value)
+    }
+
+    /// Returns a mutable reference to the value at the given index,
+    /// or [`None`] if the index is out of bounds.
+    /// The node that holds the value is rebuilt when the reference is dropped.
+    ///
+    /// This goes down the tree with a walker, so splay trees splay the value to the root.
+    pub fn get_mut(&mut self, index: usize) -> Option<ValueMut<'_, T, Tree::AlgData>> {
+        if index >= self.len() {
+            return None;
+        }
+        // the walker is dropped before taking the node, so that a splay tree splays it
+        // to the root. Then finding the node again is quick.
+        drop(self.tree.search(index));
+        let node = node_at_mut(self.tree.as_basic_tree_mut(sealed::Token(())), index)?;
+        Some(ValueMut { node })
+    }
+}
+
+impl<T, Tree> Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>>,
+    for<'a> &'a mut Tree: ModifiableTreeRef<SizeData<T>>,
+{
+    /// Inserts the value at the given index, shifting all of the values after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        self.tree.slice(index..index).insert(value).unwrap();
+    }
+
+    /// Removes the value at the given index and returns it, shifting all of the values after it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+        assert!(
+            index < len,
+            "removal index (is {}) should be < len (is {})",
+            index,
+            len
+        );
+        self.tree.slice(index).delete().unwrap()
+    }
+
+    /// Inserts the value at the start of the sequence.
+    pub fn push_front(&mut self, value: T) {
+        self.insert(0, value);
+    }
+
+    /// Inserts the value at the end of the sequence.
+    pub fn push_back(&mut self, value: T) {
+        let len = self.len();
+        self.insert(len, value);
+    }
+
+    /// Removes the first value and returns it, or [`None`] if the sequence is empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.tree.slice(0).delete()
+    }
+
+    /// Removes the last value and returns it, or [`None`] if the sequence is empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let len = self.len();
+        self.tree.slice(len.checked_sub(1)?).delete()
+    }
+}
+
+impl<T, Tree> Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SplittableTreeRef<SizeData<T>, T = Tree>,
+{
+    /// Splits the sequence in two at the given index.
+    /// Returns the values from the index onwards, and keeps the values before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let len = self.len();
+        assert!(
+            at <= len,
+            "`at` split index (is {}) should be <= len (is {})",
+            at,
+            len
+        );
+        Seq::from_tree(self.tree.slice(at..at).split_right().unwrap())
+    }
+}
+
+impl<T, Tree> Seq<T, Tree>
+where
+    Tree: ConcatenableTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    /// Moves all of the values of `other` to the end of this sequence, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        self.tree.concatenate_right(std::mem::take(&mut other.tree));
+    }
+}
+
+/// A mutable reference to a value in a [`Seq`], returned by [`Seq::get_mut`].
+/// When it is dropped, the node that holds the value is rebuilt.
+///
+/// The summaries of the node's ancestors don't need to be rebuilt,
+/// since the summary of a [`SizeData`] value doesn't depend on the value.
+pub struct ValueMut<'a, T, A> {
+    node: &'a mut BasicNode<SizeData<T>, A>,
+}
+
+impl<'a, T, A> Deref for ValueMut<'a, T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node.node_value
+    }
+}
+
+impl<'a, T, A> DerefMut for ValueMut<'a, T, A> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.node.node_value_mut()
+    }
+}
+
+impl<'a, T, A> Drop for ValueMut<'a, T, A> {
+    fn drop(&mut self) {
+        self.node.rebuild();
+    }
+}
+
+/// Finds the node at the given index.
+fn node_at<T, A>(
+    mut tree: &BasicTree<SizeData<T>, A>,
+    mut index: usize,
+) -> Option<&BasicNode<SizeData<T>, A>> {
+    loop {
+        let node = tree.node()?;
+        let left_size = node.left.subtree_summary().size;
+        match index.cmp(&left_size) {
+            Ordering::Less => tree = &node.left,
+            Ordering::Equal => return Some(node),
+            Ordering::Greater => {
+                index -= left_size + 1;
+                tree = &node.right;
+            }
+        }
+    }
+}
+
+/// Finds the node at the given index, and accesses it.
+fn node_at_mut<T, A>(
+    mut tree: &mut BasicTree<SizeData<T>, A>,
+    mut index: usize,
+) -> Option<&mut BasicNode<SizeData<T>, A>> {
+    loop {
+        let node = tree.node_mut()?;
+        node.access();
+        let left_size = node.left.subtree_summary().size;
+        match index.cmp(&left_size) {
+            Ordering::Less => tree = &mut node.left,
+            Ordering::Equal => return Some(node),
+            Ordering::Greater => {
+                index -= left_size + 1;
+                tree = &mut node.right;
+            }
+        }
+    }
+}
+
+impl<T, Tree> std::ops::Index<usize> for Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>> + AsBasicTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        let len = self.len();
+        self.get(index).unwrap_or_else(|| {
+            panic!(
+                "index out of bounds: the len is {} but the index is {}",
+                len, index
+            )
+        })
+    }
+}
+
+impl<T, Tree> Default for Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    fn default() -> Self {
+        Seq::new()
+    }
+}
+
+impl<T, Tree> std::iter::FromIterator<T> for Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Seq::from_tree(iter.into_iter().collect())
+    }
+}
+
+impl<T, Tree> Extend<T> for Seq<T, Tree>
+where
+    Tree: ConcatenableTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let other: Tree = iter.into_iter().collect();
+        self.tree.concatenate_right(other);
+    }
+}
+
+impl<T, Tree> IntoIterator for Seq<T, Tree>
+where
+    Tree: SomeTree<SizeData<T>>,
+    for<'a> &'a mut Tree: SomeTreeRef<SizeData<T>>,
+{
+    type Item = T;
+    type IntoIter = Tree::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.into_iter()
+    }
+}
@@ -10,7 +10,7 @@ use super::*;
 /// A trait for summary instances which keep track of the size of segments.
 pub trait SizedSummary {
     /// The size of the segment
-    fn size(self) -> usize;
+    fn size(&self) -> usize;
 }
 
 /// A trait for values that are keyed by a key type `Key`. When using keyed values, we assume
@@ -36,6 +36,13 @@ impl<T: Ord> Keyed<T> for T {
     }
 }
 
+/// Key-value pairs are keyed by their first element.
+impl<K: Ord, V> Keyed<K> for (K, V) {
+    fn get_key(&self) -> &K {
+        &self.0
+    }
+}
+
 // Some common instantiations and examples
 
 /// [`Data`] instance for just plain values.
@@ -49,6 +56,22 @@ pub type SizeData<V> = (V, Size, Unit);
 /// the maximum, minimum, size and sum of a whole segment at once.
 pub type StdNum = (I, NumSummary, RevAffineAction);
 
+/// A Data marker for segment tree beats on numbers. One can add a constant to a whole segment,
+/// and set all of the values of a segment that are bigger (chmin) or smaller (chmax)
+/// than a constant to that constant, in amortized `O(log^2 n)` time.
+/// One can query the sum, size, maximum and minimum of a whole segment at once.
+///```
+/// use grove::{SomeTree, treap::Treap};
+/// use grove::example_data::{ChminChmaxAction, ChminChmaxSum};
+///
+/// let mut tree: Treap<ChminChmaxSum> = vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter().collect();
+/// tree.act_segment(ChminChmaxAction::chmin(4), ..);
+/// assert_eq!(tree.segment_summary(..).sum, 3 + 1 + 4 + 1 + 4 + 4 + 2 + 4);
+/// tree.act_segment(ChminChmaxAction::chmax(2), 2..6);
+/// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![3, 1, 4, 2, 4, 4, 2, 4]);
+///```
+pub type ChminChmaxSum = (I, BeatsSummary, ChminChmaxAction);
+
 // ----------------- particular summaries and actions -------------------
 // from here, each struct is packaged into its own internal module.
 // mostly in order to reduce clutter / separate the different structs.
@@ -72,8 +95,8 @@ mod unit {
     }
 
     impl Action for Unit {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
     }
 
@@ -110,7 +133,7 @@ mod size {
     }
 
     impl SizedSummary for Size {
-        fn size(self) -> usize {
+        fn size(&self) -> usize {
             self.size
         }
     }
@@ -148,15 +171,21 @@ mod rev_action {
     }
 
     impl Action for RevAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
 
-        fn to_reverse(self) -> bool {
+        fn to_reverse(&self) -> bool {
             self.to_reverse
         }
     }
 
+    impl ReversibleAction for RevAction {
+        fn reversal() -> Self {
+            RevAction { to_reverse: true }
+        }
+    }
+
     impl Acts<Unit> for RevAction {
         fn act_inplace(&self, _val: &mut Unit) {}
     }
@@ -200,8 +229,8 @@ mod add_action {
     }
 
     impl Action for AddAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
     }
 
@@ -274,7 +303,7 @@ mod num_summary {
     }
 
     impl SizedSummary for NumSummary {
-        fn size(self) -> usize {
+        fn size(&self) -> usize {
             self.size as usize
         }
     }
@@ -323,15 +352,24 @@ mod rev_add_action {
     }
 
     impl Action for RevAddAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
 
-        fn to_reverse(self) -> bool {
+        fn to_reverse(&self) -> bool {
             self.to_reverse.to_reverse()
         }
     }
 
+    impl ReversibleAction for RevAddAction {
+        fn reversal() -> Self {
+            RevAddAction {
+                to_reverse: RevAction::reversal(),
+                add: AddAction::default(),
+            }
+        }
+    }
+
     impl<T> Acts<T> for RevAddAction
     where
         RevAction: Acts<T>,
@@ -359,15 +397,24 @@ mod rev_affine_action {
     }
 
     impl Action for RevAffineAction {
-        fn is_identity(self) -> bool {
-            self == Default::default()
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
         }
 
-        fn to_reverse(self) -> bool {
+        fn to_reverse(&self) -> bool {
             self.to_reverse
         }
     }
 
+    impl ReversibleAction for RevAffineAction {
+        fn reversal() -> Self {
+            RevAffineAction {
+                to_reverse: true,
+                ..Default::default()
+            }
+        }
+    }
+
     impl Add for RevAffineAction {
         type Output = Self;
         fn add(self, other: Self) -> Self {
@@ -495,7 +542,7 @@ mod poly_num {
     }
 
     impl<const D: usize> SizedSummary for PolyNum<D> {
-        fn size(self) -> usize {
+        fn size(&self) -> usize {
             self.size
         }
     }
@@ -578,3 +625,239 @@ mod poly_num {
         }
     }
 }
+
+pub use chmin_chmax::*;
+mod chmin_chmax {
+    use super::*;
+    use std::cmp::{Ordering, Reverse};
+
+    /// A summary for segment tree beats: the sum of a segment, and its two largest
+    /// and two smallest distinct values.
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct BeatsSummary {
+        /// The size of the segment.
+        pub size: I,
+        /// The sum of all values in the segment.
+        pub sum: I,
+        /// The maximum of all values in the segment. [`None`] if the segment is empty.
+        pub max: Option<I>,
+        /// The largest value in the segment that is smaller than the maximum.
+        /// [`None`] if there is no such value.
+        pub second_max: Option<I>,
+        /// The number of times the maximum appears in the segment.
+        pub max_count: I,
+        /// The minimum of all values in the segment. [`None`] if the segment is empty.
+        pub min: Option<I>,
+        /// The smallest value in the segment that is bigger than the minimum.
+        /// [`None`] if there is no such value.
+        pub second_min: Option<I>,
+        /// The number of times the minimum appears in the segment.
+        pub min_count: I,
+    }
+
+    /// Merges the largest values of two segments, their second largest values,
+    /// and the number of times their largest values appear.
+    fn merge_top<K: Ord + Copy>(
+        (top1, second1, count1): (Option<K>, Option<K>, I),
+        (top2, second2, count2): (Option<K>, Option<K>, I),
+    ) -> (Option<K>, Option<K>, I) {
+        match top1.cmp(&top2) {
+            Ordering::Equal => (top1, std::cmp::max(second1, second2), count1 + count2),
+            Ordering::Greater => (top1, std::cmp::max(second1, top2), count1),
+            Ordering::Less => (top2, std::cmp::max(top1, second2), count2),
+        }
+    }
+
+    impl Add for BeatsSummary {
+        type Output = Self;
+        fn add(self, other: Self) -> Self {
+            let (max, second_max, max_count) = merge_top(
+                (self.max, self.second_max, self.max_count),
+                (other.max, other.second_max, other.max_count),
+            );
+            // `Reverse` turns the minimums into maximums, with `None` as the smallest
+            let reversed = |summary: Self| {
+                (
+                    summary.min.map(Reverse),
+                    summary.second_min.map(Reverse),
+                    summary.min_count,
+                )
+            };
+            let (min, second_min, min_count) = merge_top(reversed(self), reversed(other));
+            BeatsSummary {
+                size: self.size + other.size,
+                sum: self.sum + other.sum,
+                max,
+                second_max,
+                max_count,
+                min: min.map(|Reverse(min)| min),
+                second_min: second_min.map(|Reverse(min)| min),
+                min_count,
+            }
+        }
+    }
+
+    impl SizedSummary for BeatsSummary {
+        fn size(&self) -> usize {
+            self.size as usize
+        }
+    }
+
+    impl ToSummary<BeatsSummary> for I {
+        fn to_summary(&self) -> BeatsSummary {
+            BeatsSummary {
+                size: 1,
+                sum: *self,
+                max: Some(*self),
+                second_max: None,
+                max_count: 1,
+                min: Some(*self),
+                second_min: None,
+                min_count: 1,
+            }
+        }
+    }
+
+    /// Actions that add a constant to every value, and then clamp them between a lower bound
+    /// and an upper bound. That is, the composition of adding, chmax and chmin actions.
+    ///
+    /// These actions can't be applied on a [`BeatsSummary`] of a segment if they change
+    /// more than its largest and smallest values, or if they make distinct values equal.
+    /// Then, they are applied on the parts of the segment separately.
+    /// See [`Acts::can_act`].
+    #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug, Default)]
+    pub struct ChminChmaxAction {
+        /// A constant to add to all the values in the segment.
+        pub add: I,
+        /// The values that are smaller than this bound after the addition are set to it.
+        pub lower: Option<I>,
+        /// The values that are bigger than this bound after the addition are set to it.
+        /// Has to be at least `lower`.
+        pub upper: Option<I>,
+    }
+
+    impl ChminChmaxAction {
+        /// The action that adds `add` to every value.
+        pub fn add(add: I) -> Self {
+            ChminChmaxAction {
+                add,
+                ..Default::default()
+            }
+        }
+
+        /// The action that replaces every value `x` with `min(x, bound)`.
+        pub fn chmin(bound: I) -> Self {
+            ChminChmaxAction {
+                upper: Some(bound),
+                ..Default::default()
+            }
+        }
+
+        /// The action that replaces every value `x` with `max(x, bound)`.
+        pub fn chmax(bound: I) -> Self {
+            ChminChmaxAction {
+                lower: Some(bound),
+                ..Default::default()
+            }
+        }
+
+        /// Clamps a value between the bounds, without adding.
+        fn clamp(&self, value: I) -> I {
+            let value = self
+                .lower
+                .map_or(value, |lower| std::cmp::max(value, lower));
+            self.upper
+                .map_or(value, |upper| std::cmp::min(value, upper))
+        }
+
+        /// Applies the action on the summary, if the result can be computed.
+        fn try_act(&self, summary: &BeatsSummary) -> Option<BeatsSummary> {
+            let shift = |value: Option<I>| value.map(|value| value + self.add);
+            let mut res = BeatsSummary {
+                sum: summary.sum + self.add * summary.size,
+                max: shift(summary.max),
+                second_max: shift(summary.second_max),
+                min: shift(summary.min),
+                second_min: shift(summary.second_min),
+                ..*summary
+            };
+            if let (Some(lower), Some(min)) = (self.lower, res.min) {
+                if min < lower {
+                    // only the minimum may change, and it may not reach the next value
+                    if res.second_min.is_some_and(|second_min| second_min <= lower) {
+                        return None;
+                    }
+                    res.sum += (lower - min) * res.min_count;
+                    res.min = Some(lower);
+                    if res.max == Some(min) {
+                        res.max = Some(lower);
+                    }
+                    if res.second_max == Some(min) {
+                        res.second_max = Some(lower);
+                    }
+                }
+            }
+            if let (Some(upper), Some(max)) = (self.upper, res.max) {
+                if max > upper {
+                    // only the maximum may change, and it may not reach the next value
+                    if res.second_max.is_some_and(|second_max| second_max >= upper) {
+                        return None;
+                    }
+                    res.sum -= (max - upper) * res.max_count;
+                    res.max = Some(upper);
+                    if res.min == Some(max) {
+                        res.min = Some(upper);
+                    }
+                    if res.second_min == Some(max) {
+                        res.second_min = Some(upper);
+                    }
+                }
+            }
+            Some(res)
+        }
+    }
+
+    impl Add for ChminChmaxAction {
+        type Output = Self;
+        /// Adding `other.add` and then `self.add` can be done at once,
+        /// by moving `other`'s bounds by `self.add`. Then, clamping between `other`'s bounds
+        /// and then between `self`'s bounds is the same as clamping between
+        /// `other`'s bounds clamped by `self`'s bounds.
+        fn add(self, other: Self) -> Self {
+            let shift = |bound: Option<I>| bound.map(|bound| bound + self.add);
+            ChminChmaxAction {
+                add: self.add + other.add,
+                lower: shift(other.lower)
+                    .map(|lower| self.clamp(lower))
+                    .or(self.lower),
+                upper: shift(other.upper)
+                    .map(|upper| self.clamp(upper))
+                    .or(self.upper),
+            }
+        }
+    }
+
+    impl Action for ChminChmaxAction {
+        fn is_identity(&self) -> bool {
+            *self == Default::default()
+        }
+    }
+
+    impl Acts<I> for ChminChmaxAction {
+        fn act_inplace(&self, value: &mut I) {
+            *value = self.clamp(*value + self.add);
+        }
+    }
+
+    impl Acts<BeatsSummary> for ChminChmaxAction {
+        fn act_inplace(&self, summary: &mut BeatsSummary) {
+            *summary = self
+                .try_act(summary)
+                .expect("the action can't be applied on this summary");
+        }
+
+        fn can_act(&self, summary: &BeatsSummary) -> bool {
+            self.try_act(summary).is_some()
+        }
+    }
+}
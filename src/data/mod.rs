@@ -67,6 +67,10 @@ use std::ops::Add;
 /// [`Self::Summary`]`: `[`Default`].
 /// * Test actions for being the identity. This is represented by [`Action::is_identity()`].
 ///
+/// Summaries and actions only have to be [`Clone`], so they can own heap data, such as
+/// the `k` largest values of a segment. The trees clone them only where a summary or
+/// action is used more than once, so that [`Copy`] types lose nothing.
+///
 /// # Rules
 /// In order for the segment trees to work correctly, all of these operations must play nicely with each other.
 /// Most of the rules are imposed in the documentation of the [`Action`] trait. In addition, any instance must
@@ -85,7 +89,7 @@ pub trait Data {
     type Value: ToSummary<Self::Summary>;
     /// The summaries of values over segments. When querying a segment,
     /// you get a summary of the segment, represented by a value of type `Self::Summary`.
-    type Summary: Copy + Default + Add<Output = Self::Summary>;
+    type Summary: Clone + Default + Add<Output = Self::Summary>;
     /// The actions you can perform on the values
     type Action: Action + Acts<Self::Value> + Acts<Self::Summary>;
 }
@@ -95,7 +99,7 @@ pub trait Data {
 impl<V, S, A> Data for (V, S, A)
 where
     V: ToSummary<S>,
-    S: Copy + Default + Add<Output = S>,
+    S: Clone + Default + Add<Output = S>,
     A: Action + Acts<V> + Acts<S>,
 {
     type Value = V;
@@ -152,24 +156,47 @@ where
 ///   action.act(value).to_summary() === action.act(value.to_summary())
 ///   ```
 ///
-pub trait Action: Copy + Default + Add<Output = Self> {
+pub trait Action: Clone + Default + Add<Output = Self> {
     /// Test whether this action is the identity action.
-    fn is_identity(self) -> bool;
+    fn is_identity(&self) -> bool;
 
     /// This function should be implemented if you want to be able to reverse subsegments of your tree.
     /// The default implementation always returns `false`.
     ///
     /// This function should return whether this action reverses the segment it is applied to.
-    fn to_reverse(self) -> bool {
+    fn to_reverse(&self) -> bool {
         false
     }
 }
 
+/// Actions that can reverse segments, e.g., [`example_data::RevAction`].
+/// Needed by algorithms that reverse parts of the tree by themselves,
+/// like [`LinkCutForest::make_root`](crate::link_cut::LinkCutForest::make_root).
+pub trait ReversibleAction: Action {
+    /// Returns the action that reverses a segment, and doesn't change the values.
+    /// It should satisfy `Self::reversal().to_reverse() == true`.
+    fn reversal() -> Self;
+}
+
 /// Trait representation actions on a type `V`. If `A: Acts<V>` that means that given any `action: A`,
 /// we can apply it to any `val: V`. This trait is used to represent the actions on
 /// values and summaries used by segment trees.
 ///
 /// Morally `Action` should be a supertrait of this trait.
+///
+/// # Failing actions
+///
+/// Some actions can't always compute the summary of a segment after the action from
+/// the summary before it. For example, setting every value above `x` to `x` (chmin)
+/// can be computed from a summary of the maximum, the second maximum and the count
+/// of the maximum, only if the second maximum is smaller than `x`.
+/// Such actions should override [`Acts::can_act`] to return `false` on these summaries.
+/// Then, instead of applying the action on a whole subtree at once, the trees apply it
+/// on the subtree's root value and on its two subtrees separately, recursively.
+/// This is the technique known as "segment tree beats". See [`example_data::ChminChmaxSum`].
+///
+/// If an action can act on a summary of a segment, it must be able to act on the summaries
+/// of all of its subsegments, and on the summary of every single value.
 pub trait Acts<V> {
     /// Act on a value in-place.
     fn act_inplace(&self, object: &mut V);
@@ -178,6 +205,11 @@ pub trait Acts<V> {
         self.act_inplace(&mut object);
         object
     }
+    /// Returns whether this action can be applied on the object.
+    /// The default implementation always returns `true`.
+    fn can_act(&self, _object: &V) -> bool {
+        true
+    }
 }
 
 /// This trait is implemented by Values,
@@ -186,3 +218,77 @@ pub trait ToSummary<S> {
     /// Creates the summary of a single value.
     fn to_summary(&self) -> S;
 }
+
+/// Values that represent several consecutive elements each,
+/// such as runs of equal elements, or intervals of an implicit array.
+/// The number of elements in a value is the [`SizedSummary::size`] of its summary.
+///
+/// Index locators already work with such values, but a value can only be accepted or rejected
+/// as a whole. [`SomeTree::slice_exact`](crate::SomeTree::slice_exact) uses this trait to split
+/// the values at the edges of an index range first, so that the segment contains exactly
+/// the elements in the range.
+///```
+/// use grove::*;
+/// use grove::example_data::Unit;
+///
+/// #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+/// struct Run { value: char, len: usize }
+///
+/// #[derive(Clone, Copy, Default)]
+/// struct RunSize { size: usize }
+///
+/// impl std::ops::Add for RunSize {
+///     type Output = RunSize;
+///     fn add(self, other: RunSize) -> RunSize {
+///         RunSize { size: self.size + other.size }
+///     }
+/// }
+///
+/// impl SizedSummary for RunSize {
+///     fn size(&self) -> usize {
+///         self.size
+///     }
+/// }
+///
+/// impl ToSummary<RunSize> for Run {
+///     fn to_summary(&self) -> RunSize {
+///         RunSize { size: self.len }
+///     }
+/// }
+///
+/// impl SplittableValue for Run {
+///     fn split_at(&self, offset: usize) -> (Run, Run) {
+///         (Run { len: offset, ..*self }, Run { len: self.len - offset, ..*self })
+///     }
+///
+///     fn try_merge(&self, other: &Run) -> Option<Run> {
+///         if self.value == other.value {
+///             Some(Run { len: self.len + other.len, ..*self })
+///         } else {
+///             None
+///         }
+///     }
+/// }
+///
+/// // "aaaaabbbbbbbbbb"
+/// let runs = [Run { value: 'a', len: 5 }, Run { value: 'b', len: 10 }];
+/// let mut tree: treap::Treap<(Run, RunSize, Unit)> = runs.iter().cloned().collect();
+/// let mid: Vec<Run> = tree.slice_exact(3..8).iter().cloned().collect();
+/// assert_eq!(mid, vec![Run { value: 'a', len: 2 }, Run { value: 'b', len: 3 }]);
+///
+/// // the split values can be merged back
+/// assert!(tree.merge_at(3));
+/// assert!(!tree.merge_at(5));
+/// assert_eq!(tree.iter().count(), 3);
+///```
+pub trait SplittableValue: Sized {
+    /// Splits the value into its first `offset` elements and the rest of its elements.
+    /// Is only called with `0 < offset < size`.
+    fn split_at(&self, offset: usize) -> (Self, Self);
+
+    /// Merges the value with the value right after it into a single value, if possible.
+    /// The default implementation never merges values.
+    fn try_merge(&self, _other: &Self) -> Option<Self> {
+        None
+    }
+}
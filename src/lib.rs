@@ -28,7 +28,7 @@
 //! ## Goals
 //!
 //! Grove aims to be the most generic segment tree library possible. Grove should be able to represent as many kinds of segment tree / augmented tree as possible (and it certainly can much more than any other implementation known to the author). Grove is generic in:
-//! * Balanced tree algorithm (currently only implements Splay tree, AVL tree, and Treap).
+//! * Balanced tree algorithm (currently only implements Splay tree, AVL tree, Red-black tree, Scapegoat tree, Weight-balanced tree, and Treap).
 //! * Value type.
 //! * The way in which you can search for elements in the structure.
 //! * Segment summaries - the augmentation data about the subtree stored in each node.
@@ -90,7 +90,19 @@
 //! In order to use a certain kind of tree, i.e., red-black, AVL, splay tree, treaps,
 //! scapegoat trees, regular unbalanced trees, or any other, the user has to specify
 //! a tree type that implements the trait in the [`trees`] module. (currently
-//! splay/AVL/treaps/unbalanced trees are implemented)
+//! splay/AVL/red-black/scapegoat/weight-balanced/treaps/unbalanced trees are implemented)
+//!
+//! Persistent treaps, whose clones are cheap snapshots that share their nodes, are in the [`persistent`] module.
+//!
+//! Link-cut trees, for forests with dynamic edges and path queries, are in the [`link_cut`] module.
+//!
+//! Euler-tour trees, for forests with dynamic edges and subtree queries, are in the [`euler_tour`] module.
+//!
+//! Collections with a familiar interface that are built on the trees, such as the [`Seq`](collections::Seq)
+//! sequence type, the [`OrderedSet`](collections::OrderedSet) and [`OrderedMap`](collections::OrderedMap)
+//! types, the [`IntervalMap`](collections::IntervalMap), [`RangeMap`](collections::RangeMap)
+//! and [`RangeSet`](collections::RangeSet) types and the [`Rope`](collections::Rope) text buffer,
+//! are in the [`collections`] module.
 //!
 //! ```rust
 //! use grove::*;
@@ -139,10 +151,11 @@
 #[macro_use]
 extern crate derive_destructure;
 
+pub mod collections;
 pub mod data;
 pub mod locators;
 pub mod trees;
 
 pub use data::*;
-pub use locators::Locator;
+pub use locators::{Locator, SubtreeFilter};
 pub use trees::*;
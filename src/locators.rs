@@ -9,6 +9,7 @@
 //! but leads the locator into a space between nodes, where the node will be inserted.
 
 use crate::*;
+use std::ops::Bound;
 
 /// This is the result type that a `locator` returns when queried about a specific node.
 /// See [`Locator`].
@@ -45,18 +46,76 @@ use LocResult::*;
 pub trait Locator<D: Data>: Clone {
     /// Looks at a specific node's value, and its context (the summaries to the right and left),
     /// and decides whether to go left, right, or accept the node.
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult;
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult;
+
+    /// Returns `false` if the locator only looks at the sizes of the values, which actions
+    /// don't change, and not at the values themselves. Then, queries that only have immutable
+    /// access to the tree can call the locator on the values as they are stored, without
+    /// applying the pending actions to them first.
+    ///
+    /// The default implementation returns `true`.
+    fn uses_values(&self) -> bool {
+        true
+    }
 }
 
 impl<D: Data, F> Locator<D> for F
 where
-    F: Fn(D::Summary, &D::Value, D::Summary) -> LocResult + Clone,
+    F: Fn(&D::Summary, &D::Value, &D::Summary) -> LocResult + Clone,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult {
         self(left, node, right)
     }
 }
 
+/// This is the result type that a [`SubtreeFilter`] returns when queried about a subtree.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum FilterResult {
+    /// None of the values in the subtree match, so the subtree should be skipped
+    Skip,
+    /// All of the values in the subtree match
+    TakeAll,
+    /// Some of the values in the subtree might match, so we should look inside the subtree
+    Descend,
+}
+
+/// Subtree filters select the values of the tree that satisfy some condition.
+/// Unlike locators, the selected values don't have to form a contiguous segment.
+///
+/// When the filter is used, the filter is queried about the summaries of subtrees,
+/// and it has to reply:
+/// * If none of the values in the subtree match, return `Skip`.
+/// * If all of the values in the subtree match, return `TakeAll`.
+/// * Otherwise, return `Descend`, and the filter will be queried about the node's value
+///   and about its subtrees.
+///
+/// For example, with a summary of the maximum and the minimum of a segment, the values that are
+/// bigger than some bound can be found by skipping the subtrees whose maximum is smaller
+/// than the bound, and taking the subtrees whose minimum is bigger than the bound.
+/// This takes `O((k + 1)*log n)` time, where `k` is the number of maximal matching segments.
+///
+/// Anonymous functions of the type `Fn(&D::Summary) -> FilterResult` can be used as filters.
+pub trait SubtreeFilter<D: Data> {
+    /// Looks at the summary of a non-empty subtree, and decides whether to skip it,
+    /// take all of it, or look inside it.
+    fn filter(&self, summary: &D::Summary) -> FilterResult;
+
+    /// Decides whether a single value matches, when the filter descended into its subtree.
+    /// By default, a value matches unless the filter skips its summary.
+    fn matches(&self, value: &D::Value) -> bool {
+        self.filter(&value.to_summary()) != FilterResult::Skip
+    }
+}
+
+impl<D: Data, F> SubtreeFilter<D> for F
+where
+    F: Fn(&D::Summary) -> FilterResult,
+{
+    fn filter(&self, summary: &D::Summary) -> FilterResult {
+        self(summary)
+    }
+}
+
 /// Returns the result of the locator at the walker
 /// Returns None if the walker is at an empty position
 pub fn query_locator<W, D: Data, L>(walker: &mut W, locator: &L) -> Option<LocResult>
@@ -67,7 +126,7 @@ where
     if let Some(value) = walker.value() {
         let left = walker.left_summary();
         let right = walker.right_summary();
-        Some(locator.locate(left, value, right))
+        Some(locator.locate(&left, value, &right))
     } else {
         None
     }
@@ -87,9 +146,9 @@ where
     D::Value: Clone,
 {
     if !current_action.to_reverse() {
-        locator.locate(left, &current_action.act(value.clone()), right)
+        locator.locate(&left, &current_action.act(value.clone()), &right)
     } else {
-        locator.locate(right, &current_action.act(value.clone()), left)
+        locator.locate(&right, &current_action.act(value.clone()), &left)
     }
 }
 
@@ -98,7 +157,7 @@ impl<D: Data> Locator<D> for usize
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -110,20 +169,32 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for [`std::ops::RangeFull`].
 impl<D: Data> Locator<D> for std::ops::RangeFull {
-    fn locate(&self, _left: D::Summary, _node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, _node: &D::Value, _right: &D::Summary) -> LocResult {
         Accept
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for a reference to [`std::ops::RangeFull`].
 impl<D: Data> Locator<D> for &std::ops::RangeFull {
-    fn locate(&self, _left: D::Summary, _node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, _node: &D::Value, _right: &D::Summary) -> LocResult {
         Accept
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for [`std::ops::Range<usize>`] representing an index range.
@@ -131,7 +202,7 @@ impl<D: Data> Locator<D> for std::ops::Range<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -143,6 +214,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for a reference to [`std::ops::Range<usize>`] representing an index range.
@@ -150,7 +225,7 @@ impl<D: Data> Locator<D> for &std::ops::Range<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -162,6 +237,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for [`std::ops::RangeInclusive<usize>`] representing an index range.
@@ -170,7 +249,7 @@ impl<D: Data> Locator<D> for std::ops::RangeInclusive<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -182,6 +261,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance fora reference to [`std::ops::RangeInclusive<usize>`] representing an index range.
@@ -190,7 +273,7 @@ impl<D: Data> Locator<D> for &std::ops::RangeInclusive<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -202,6 +285,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for [`std::ops::RangeFrom<usize>`] representing an index range.
@@ -209,7 +296,7 @@ impl<D: Data> Locator<D> for std::ops::RangeFrom<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -219,6 +306,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for a reference to [`std::ops::RangeFrom<usize>`] representing an index range.
@@ -226,7 +317,7 @@ impl<D: Data> Locator<D> for &std::ops::RangeFrom<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -236,6 +327,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for [`std::ops::RangeTo<usize>`] representing an index range.
@@ -243,7 +338,7 @@ impl<D: Data> Locator<D> for std::ops::RangeTo<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, _node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, _node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -253,6 +348,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for a referencfe to [`std::ops::RangeTo<usize>`] representing an index range.
@@ -260,7 +359,7 @@ impl<D: Data> Locator<D> for &std::ops::RangeTo<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, _node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, _node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -270,6 +369,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for [`std::ops::RangeToInclusive<usize>`] representing an index range.
@@ -278,7 +381,7 @@ impl<D: Data> Locator<D> for std::ops::RangeToInclusive<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, _node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, _node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -288,6 +391,10 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for a reference to [`std::ops::RangeToInclusive<usize>`] representing an index range.
@@ -296,7 +403,7 @@ impl<D: Data> Locator<D> for &std::ops::RangeToInclusive<usize>
 where
     D::Summary: SizedSummary,
 {
-    fn locate(&self, left: D::Summary, _node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, _node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let s = left.size();
 
@@ -306,6 +413,77 @@ where
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
+}
+
+/// Locates an index in the segment between the bounds, given the number of elements
+/// to the left of the node and the number of elements in the node.
+fn locate_index_between(
+    left: usize,
+    size: usize,
+    start: Bound<&usize>,
+    end: Bound<&usize>,
+) -> LocResult {
+    let after_end = match end {
+        Bound::Included(&end) => left > end,
+        Bound::Excluded(&end) => left >= end,
+        Bound::Unbounded => false,
+    };
+    let before_start = match start {
+        Bound::Included(&start) => left + size <= start,
+        Bound::Excluded(&start) => left + size <= start + 1,
+        Bound::Unbounded => false,
+    };
+    if after_end {
+        GoLeft
+    } else if before_start {
+        GoRight
+    } else {
+        Accept
+    }
+}
+
+/// Locator instance for a pair of [`Bound`]s representing an index range,
+/// in the same way as [`std::ops::RangeBounds`]. This allows passing the bounds of any
+/// range straight through, e.g., `(range.start_bound().cloned(), range.end_bound().cloned())`.
+impl<D: Data> Locator<D> for (Bound<usize>, Bound<usize>)
+where
+    D::Summary: SizedSummary,
+{
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
+        locate_index_between(
+            left.size(),
+            node.to_summary().size(),
+            self.0.as_ref(),
+            self.1.as_ref(),
+        )
+    }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
+}
+
+/// Locator instance for a reference to a pair of [`Bound`]s representing an index range.
+impl<D: Data> Locator<D> for &(Bound<usize>, Bound<usize>)
+where
+    D::Summary: SizedSummary,
+{
+    fn locate(&self, left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
+        locate_index_between(
+            left.size(),
+            node.to_summary().size(),
+            self.0.as_ref(),
+            self.1.as_ref(),
+        )
+    }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// This struct says you want your locator to be based on your values' keys, through the
@@ -313,6 +491,9 @@ where
 /// For example, a `ByKey(3..9)` locator will accept
 /// elements with keys in the range `3..9`. Of course, this is a legal locator only if
 /// the elements are sorted by their keys.
+///
+/// The keys inside a `ByKey` locator are borrowed, e.g., `ByKey(&3..&9)` or `ByKey((&5,))`.
+/// Ranges of owned keys can be used through a reference, e.g., `&ByKey(3..9)`.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
 pub struct ByKey<T>(pub T);
 
@@ -322,7 +503,7 @@ impl<'a, D: Data, Key: Ord> Locator<D> for ByKey<(&Key,)>
 where
     D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         match node.get_key().cmp(self.0 .0) {
             std::cmp::Ordering::Less => GoRight,
             std::cmp::Ordering::Equal => Accept,
@@ -333,9 +514,13 @@ where
 
 /// Locator instance for [`ByKey`]`<`[`std::ops::RangeFull`]`>`.
 impl<D: Data> Locator<D> for ByKey<std::ops::RangeFull> {
-    fn locate(&self, _left: D::Summary, _node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, _node: &D::Value, _right: &D::Summary) -> LocResult {
         Accept
     }
+
+    fn uses_values(&self) -> bool {
+        false
+    }
 }
 
 /// Locator instance for [`ByKey`]`<std::ops::Range<D::Value::Key>>` representing searching by a key.
@@ -343,7 +528,7 @@ impl<D: Data, Key: Ord> Locator<D> for ByKey<std::ops::Range<&Key>>
 where
     D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let key = node.get_key();
         if key < self.0.start {
@@ -356,25 +541,23 @@ where
     }
 }
 
-/*
-/// Locator instance for a reference to [`ByKey`]`<std::ops::Range<D::Value::Key>>` representing searching by a key.
-impl<D: Data> Locator<D> for &ByKey<std::ops::Range<<D::Value as Keyed>::Key>> where
-    D::Value: Keyed,
-    <D::Value as Keyed>::Key: Copy,
+/// Locator instance for a reference to [`ByKey`]`<std::ops::Range<D::Value::Key>>`,
+/// with an owned range of keys.
+impl<D: Data, Key: Ord> Locator<D> for &ByKey<std::ops::Range<Key>>
+where
+    D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
-        // find the index of the current node
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         let key = node.get_key();
-        if key < self.0.start {
-            GoLeft
-        } else if self.0.end <= key {
+        if *key < self.0.start {
             GoRight
+        } else if self.0.end <= *key {
+            GoLeft
         } else {
             Accept
         }
     }
 }
-*/
 
 /// Locator instance for [`ByKey`]`<std::ops::RangeInclusive<D::Value::Key>>` representing searching by a key.
 /// Do not use with ranges that have been iterated on to exhaustion.
@@ -382,7 +565,7 @@ impl<D: Data, Key: Ord> Locator<D> for ByKey<std::ops::RangeInclusive<&Key>>
 where
     D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let key = &node.get_key();
         if key < self.0.start() {
@@ -395,33 +578,31 @@ where
     }
 }
 
-/*
-/// Locator instance fora reference to [`ByKey`]`<std::ops::RangeInclusive<D::Value::Key>>` representing searching by a key.
+/// Locator instance for a reference to [`ByKey`]`<std::ops::RangeInclusive<D::Value::Key>>`,
+/// with an owned range of keys.
 /// Do not use with ranges that have been iterated on to exhaustion.
-impl<D: Data> Locator<D> for &ByKey<std::ops::RangeInclusive<<D::Value as Keyed>::Key>> where
-    D::Value: Keyed,
-    <D::Value as Keyed>::Key: Copy,
+impl<D: Data, Key: Ord> Locator<D> for &ByKey<std::ops::RangeInclusive<Key>>
+where
+    D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
-        // find the index of the current node
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         let key = node.get_key();
-        if key < *self.0.start() {
-            GoLeft
-        } else if *self.0.end() < key {
+        if key < self.0.start() {
             GoRight
+        } else if self.0.end() < key {
+            GoLeft
         } else {
             Accept
         }
     }
 }
-*/
 
 /// Locator instance for [`ByKey`]`<`[`std::ops::RangeFrom`]`<D::Value::Key>>` representing an index range.
 impl<D: Data, Key: Ord> Locator<D> for ByKey<std::ops::RangeFrom<&Key>>
 where
     D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let key = node.get_key();
         if key < self.0.start {
@@ -437,7 +618,7 @@ impl<D: Data, Key: Ord> Locator<D> for ByKey<std::ops::RangeTo<&Key>>
 where
     D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let key = node.get_key();
         if self.0.end <= key {
@@ -453,7 +634,7 @@ impl<D: Data, Key: Ord> Locator<D> for ByKey<std::ops::RangeToInclusive<&Key>>
 where
     D::Value: Keyed<Key>,
 {
-    fn locate(&self, _left: D::Summary, node: &D::Value, _right: D::Summary) -> LocResult {
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
         // find the index of the current node
         let key = node.get_key();
         if self.0.end < key {
@@ -464,6 +645,51 @@ where
     }
 }
 
+/// Locates a key in the segment of keys between the bounds.
+fn locate_key_between<Key: Ord>(key: &Key, start: Bound<&Key>, end: Bound<&Key>) -> LocResult {
+    let before_start = match start {
+        Bound::Included(start) => key < start,
+        Bound::Excluded(start) => key <= start,
+        Bound::Unbounded => false,
+    };
+    let after_end = match end {
+        Bound::Included(end) => end < key,
+        Bound::Excluded(end) => end <= key,
+        Bound::Unbounded => false,
+    };
+    if before_start {
+        GoRight
+    } else if after_end {
+        GoLeft
+    } else {
+        Accept
+    }
+}
+
+/// Locator instance for [`ByKey`]`<(Bound<&D::Value::Key>, Bound<&D::Value::Key>)>`
+/// representing searching by a key, in the same way as [`std::ops::RangeBounds`].
+/// This allows passing the bounds of any range of keys straight through,
+/// e.g., `ByKey((range.start_bound(), range.end_bound()))`.
+impl<D: Data, Key: Ord> Locator<D> for ByKey<(Bound<&Key>, Bound<&Key>)>
+where
+    D::Value: Keyed<Key>,
+{
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
+        locate_key_between(node.get_key(), self.0 .0, self.0 .1)
+    }
+}
+
+/// Locator instance for a reference to [`ByKey`]`<(Bound<D::Value::Key>, Bound<D::Value::Key>)>`,
+/// with owned bounds.
+impl<D: Data, Key: Ord> Locator<D> for &ByKey<(Bound<Key>, Bound<Key>)>
+where
+    D::Value: Keyed<Key>,
+{
+    fn locate(&self, _left: &D::Summary, node: &D::Value, _right: &D::Summary) -> LocResult {
+        locate_key_between(node.get_key(), self.0 .0.as_ref(), self.0 .1.as_ref())
+    }
+}
+
 /// A Wrapper for other locators what will find exactly the left edge
 /// of the previous locator. So, this is always a splitting locator.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -474,21 +700,29 @@ pub struct LeftEdgeOf<L>(pub L);
 pub struct RightEdgeOf<L>(pub L);
 
 impl<D: Data, L: Locator<D>> Locator<D> for LeftEdgeOf<L> {
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult {
         match self.0.locate(left, node, right) {
             Accept => GoLeft,
             res => res,
         }
     }
+
+    fn uses_values(&self) -> bool {
+        self.0.uses_values()
+    }
 }
 
 impl<D: Data, L: Locator<D>> Locator<D> for RightEdgeOf<L> {
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult {
         match self.0.locate(left, node, right) {
             Accept => GoRight,
             res => res,
         }
     }
+
+    fn uses_values(&self) -> bool {
+        self.0.uses_values()
+    }
 }
 
 /// A Wrapper for other locators what will find the segment to the left
@@ -502,21 +736,29 @@ pub struct LeftOf<L>(pub L);
 pub struct RightOf<L>(pub L);
 
 impl<D: Data, L: Locator<D>> Locator<D> for LeftOf<L> {
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult {
         match self.0.locate(left, node, right) {
             GoRight => Accept,
             _ => GoLeft,
         }
     }
+
+    fn uses_values(&self) -> bool {
+        self.0.uses_values()
+    }
 }
 
 impl<D: Data, L: Locator<D>> Locator<D> for RightOf<L> {
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult {
         match self.0.locate(left, node, right) {
             GoLeft => Accept,
             _ => GoRight,
         }
     }
+
+    fn uses_values(&self) -> bool {
+        self.0.uses_values()
+    }
 }
 
 /// A Wrapper for two other locators, that finds the smallest segment containing both of them.
@@ -525,7 +767,7 @@ impl<D: Data, L: Locator<D>> Locator<D> for RightOf<L> {
 pub struct UnionLocator<L1, L2>(pub L1, pub L2);
 
 impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for UnionLocator<L1, L2> {
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult {
         let a = self.0.locate(left, node, right);
         let b = self.1.locate(left, node, right);
         if a == b {
@@ -534,6 +776,10 @@ impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for UnionLocator<L1, L2
             Accept
         }
     }
+
+    fn uses_values(&self) -> bool {
+        self.0.uses_values() || self.1.uses_values()
+    }
 }
 
 /// A Wrapper for two other locators, that finds the segment between them.
@@ -545,7 +791,7 @@ impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for UnionLocator<L1, L2
 pub struct BetweenLocator<L1, L2>(pub L1, pub L2);
 
 impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for BetweenLocator<L1, L2> {
-    fn locate(&self, left: D::Summary, node: &D::Value, right: D::Summary) -> LocResult {
+    fn locate(&self, left: &D::Summary, node: &D::Value, right: &D::Summary) -> LocResult {
         let a = self.0.locate(left, node, right);
         let b = self.1.locate(left, node, right);
         match (a, b) {
@@ -555,4 +801,8 @@ impl<D: Data, L1: Locator<D>, L2: Locator<D>> Locator<D> for BetweenLocator<L1,
             (x, _) => x,
         }
     }
+
+    fn uses_values(&self) -> bool {
+        self.0.uses_values() || self.1.uses_values()
+    }
 }
@@ -0,0 +1,473 @@
+//! The arena module.
+//! This module implements storage for tree nodes inside a single vector, instead of
+//! allocating every node separately in its own [`Box`].
+//!
+//! Nodes are referred to by [`NodeIdx`] indices into the arena. Freed slots are kept in a free list
+//! and reused by later allocations, so a tree that has as many insertions as deletions doesn't grow.
+//! Since all of the nodes live in one vector:
+//! * Inserting a node usually doesn't allocate.
+//! * Nodes that were allocated together are close together in memory.
+//! * The whole tree can be cleared at once with [`Arena::clear`], without walking the tree.
+//! * Dropping an arena never recurses, even if its trees are very deep, unlike boxed trees
+//!   which need [`deallocate_iteratively`](super::basic_tree::deallocate_iteratively).
+//!
+//! Nodes also store the index of their parent. Since nodes never move inside the arena,
+//! a [`NodeIdx`] stays valid as long as its node is in the tree, no matter how the tree is
+//! restructured, and can be used as a stable handle to the node's value.
+//!
+//! Indices are generational: every slot counts how many times it was freed, and every index
+//! remembers the count of its slot when it was allocated. Using the index of a freed node
+//! panics, even if its slot was reused by a different node since.
+//! Every arena also has its own id, and every index remembers the id of the arena that allocated it,
+//! so using an index with a different arena (e.g., of another tree) panics too.
+//!
+//! The arena is a standalone node storage, used by [`treap::ArenaTreap`],
+//! [`link_cut`] and [`euler_tour`].
+//! It is not a storage backend for the other trees: [`BasicTree`](basic_tree::BasicTree)
+//! and [`BasicWalker`](basic_tree::BasicWalker) aren't generic over the node storage,
+//! so the walker-based trees, such as [`AVLTree`](avl::AVLTree)
+//! and [`Treap`](crate::treap::Treap), still store their nodes in boxes.
+
+pub mod treap;
+
+use crate::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// An index of a node inside an [`Arena`].
+/// After a node is freed, its slot might be reused for a different node, but the old index
+/// won't refer to the new node: accessing a freed node through its index panics.
+/// Accessing a node through an index that was allocated by a different arena also panics.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct NodeIdx {
+    arena: u32,
+    index: u32,
+    generation: u32,
+}
+
+impl NodeIdx {
+    fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+/// A node stored in an [`Arena`]. The sons are indices into the same arena.
+/// The `T` parameter is for algorithm-specific bookeeping data.
+pub struct ArenaNode<D: ?Sized + Data, T = ()> {
+    action: D::Action,
+    subtree_summary: D::Summary,
+    pub(crate) node_value: D::Value,
+    pub(crate) left: Option<NodeIdx>,
+    pub(crate) right: Option<NodeIdx>,
+    pub(crate) parent: Option<NodeIdx>,
+    pub(crate) alg_data: T,
+}
+
+impl<D: Data, T> ArenaNode<D, T> {
+    /// Creates a node with a single value, and the algorithm specific data.
+    pub fn new_alg(value: D::Value, alg_data: T) -> ArenaNode<D, T> {
+        let subtree_summary = value.to_summary();
+        ArenaNode {
+            action: Default::default(),
+            node_value: value,
+            subtree_summary,
+            left: None,
+            right: None,
+            parent: None,
+            alg_data,
+        }
+    }
+
+    /// Returns the algorithm-specific data
+    pub fn alg_data(&self) -> &T {
+        &self.alg_data
+    }
+
+    /// Returns the left son's index.
+    pub fn left(&self) -> Option<NodeIdx> {
+        self.left
+    }
+
+    /// Returns the right son's index.
+    pub fn right(&self) -> Option<NodeIdx> {
+        self.right
+    }
+
+    /// Returns the parent's index.
+    /// Returns [`None`] for the root.
+    pub fn parent(&self) -> Option<NodeIdx> {
+        self.parent
+    }
+
+    /// Returns a summary for the value in this node specifically,
+    /// and not the subtree.
+    pub fn node_summary(&self) -> D::Summary {
+        let summary = self.node_value.to_summary();
+        self.action.act(summary)
+    }
+}
+
+/// A slot in the arena, together with its generation.
+/// The generation goes up whenever the slot is freed. Free slots form a linked list.
+enum Slot<D: ?Sized + Data, T> {
+    Occupied(u32, ArenaNode<D, T>),
+    Free(u32, Option<u32>),
+}
+
+/// An arena that stores tree nodes.
+/// The `T` parameter is for algorithm-specific bookeeping data.
+pub struct Arena<D: ?Sized + Data, T = ()> {
+    // distinguishes the indices of this arena from indices of other arenas
+    id: u32,
+    slots: Vec<Slot<D, T>>,
+    // the head of the free list
+    free: Option<u32>,
+    len: usize,
+}
+
+const FREED_NODE_ERROR: &str = "Invalid node index: the node was freed";
+const FOREIGN_NODE_ERROR: &str = "Invalid node index: the node belongs to a different arena";
+
+/// The id of the next arena to be created.
+static NEXT_ARENA_ID: AtomicU32 = AtomicU32::new(0);
+
+fn new_arena_id() -> u32 {
+    NEXT_ARENA_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+impl<D: Data, T> Default for Arena<D, T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<D: Data, T> Arena<D, T> {
+    /// Creates an empty arena.
+    pub fn new() -> Self {
+        Arena {
+            id: new_arena_id(),
+            slots: vec![],
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Creates an empty arena with space for `capacity` nodes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            id: new_arena_id(),
+            slots: Vec::with_capacity(capacity),
+            free: None,
+            len: 0,
+        }
+    }
+
+    /// Returns the number of nodes in the arena.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if there are no nodes in the arena.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of nodes the arena can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Removes all of the nodes, in `O(n)` time, without walking any tree.
+    /// Keeps the allocated memory. Invalidates all indices.
+    pub fn clear(&mut self) {
+        self.free = None;
+        // going in reverse, so that the lower slots are reused first
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            let generation = match slot {
+                Slot::Occupied(generation, _) => generation.wrapping_add(1),
+                Slot::Free(generation, _) => *generation,
+            };
+            *slot = Slot::Free(generation, self.free);
+            self.free = Some(index as u32);
+        }
+        self.len = 0;
+    }
+
+    /// Iterates over the indices of all of the nodes in the arena.
+    pub fn indices(&self) -> impl Iterator<Item = NodeIdx> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| match slot {
+                Slot::Occupied(generation, _) => Some(NodeIdx {
+                    arena: self.id,
+                    index: index as u32,
+                    generation: *generation,
+                }),
+                Slot::Free(..) => None,
+            })
+    }
+
+    /// Stores the node in the arena, and returns its index.
+    /// Reuses a freed slot if there is one.
+    pub fn alloc(&mut self, node: ArenaNode<D, T>) -> NodeIdx {
+        self.len += 1;
+        match self.free {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                let generation = match *slot {
+                    Slot::Free(generation, next) => {
+                        self.free = next;
+                        generation
+                    }
+                    Slot::Occupied(..) => panic!("Occupied slot in the free list"),
+                };
+                *slot = Slot::Occupied(generation, node);
+                NodeIdx {
+                    arena: self.id,
+                    index,
+                    generation,
+                }
+            }
+            None => {
+                let index =
+                    u32::try_from(self.slots.len()).expect("Too many nodes for a single arena");
+                self.slots.push(Slot::Occupied(0, node));
+                NodeIdx {
+                    arena: self.id,
+                    index,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Removes the node from the arena and returns it.
+    /// Its slot will be reused by later allocations.
+    /// Panics if the node was already freed, or if the index belongs to a different arena.
+    pub fn free(&mut self, idx: NodeIdx) -> ArenaNode<D, T> {
+        // check before changing the slot
+        self.node(idx);
+        let slot = std::mem::replace(
+            &mut self.slots[idx.index()],
+            Slot::Free(idx.generation.wrapping_add(1), self.free),
+        );
+        self.free = Some(idx.index);
+        self.len -= 1;
+        match slot {
+            Slot::Occupied(_, node) => node,
+            Slot::Free(..) => unreachable!(),
+        }
+    }
+
+    /// Returns the node at the given index.
+    /// Panics if the node was freed, or if the index belongs to a different arena.
+    pub fn node(&self, idx: NodeIdx) -> &ArenaNode<D, T> {
+        assert!(idx.arena == self.id, "{}", FOREIGN_NODE_ERROR);
+        match &self.slots[idx.index()] {
+            Slot::Occupied(generation, node) if *generation == idx.generation => node,
+            _ => panic!("{}", FREED_NODE_ERROR),
+        }
+    }
+
+    /// Returns the node at the given index.
+    /// Panics if the node was freed, or if the index belongs to a different arena.
+    pub fn node_mut(&mut self, idx: NodeIdx) -> &mut ArenaNode<D, T> {
+        assert!(idx.arena == self.id, "{}", FOREIGN_NODE_ERROR);
+        match &mut self.slots[idx.index()] {
+            Slot::Occupied(generation, node) if *generation == idx.generation => node,
+            _ => panic!("{}", FREED_NODE_ERROR),
+        }
+    }
+
+    /// Sets the left son of the node, and updates the son's parent.
+    pub fn set_left(&mut self, idx: NodeIdx, son: Option<NodeIdx>) {
+        self.node_mut(idx).left = son;
+        if let Some(son) = son {
+            self.node_mut(son).parent = Some(idx);
+        }
+    }
+
+    /// Sets the right son of the node, and updates the son's parent.
+    pub fn set_right(&mut self, idx: NodeIdx, son: Option<NodeIdx>) {
+        self.node_mut(idx).right = son;
+        if let Some(son) = son {
+            self.node_mut(son).parent = Some(idx);
+        }
+    }
+
+    /// Marks the node as a root, i.e., as having no parent.
+    pub fn make_root(&mut self, tree: Option<NodeIdx>) {
+        if let Some(idx) = tree {
+            self.node_mut(idx).parent = None;
+        }
+    }
+
+    /// Returns the indices of the nodes on the path from the root of the node's tree to the node,
+    /// and pushes down all of the actions on the path.
+    /// After this, the node's value and the structure above it are clean.
+    pub fn access_path(&mut self, idx: NodeIdx) -> Vec<NodeIdx> {
+        let mut path = vec![idx];
+        let mut current = idx;
+        while let Some(parent) = self.node(current).parent {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        for &idx in path.iter() {
+            self.access(idx);
+        }
+        path
+    }
+
+    /// Returns the summary of all values in the subtree rooted at `tree`.
+    /// Returns `default()` for the empty tree.
+    pub fn subtree_summary(&self, tree: Option<NodeIdx>) -> D::Summary {
+        match tree {
+            Some(idx) => {
+                let node = self.node(idx);
+                node.action.act(node.subtree_summary.clone())
+            }
+            None => Default::default(),
+        }
+    }
+
+    /// Applies the given action to the whole subtree rooted at `tree`.
+    ///
+    /// If the action can't act on the subtree's summary (see [`Acts::can_act`]),
+    /// it is applied on the root's value and on its sons separately instead.
+    pub fn act_subtree(&mut self, tree: Option<NodeIdx>, action: D::Action) {
+        if let Some(idx) = tree {
+            if action.can_act(&self.subtree_summary(tree)) {
+                let node = self.node_mut(idx);
+                node.action = action + std::mem::take(&mut node.action);
+            } else {
+                self.access(idx);
+                let node = self.node_mut(idx);
+                if action.to_reverse() {
+                    std::mem::swap(&mut node.left, &mut node.right);
+                }
+                action.act_inplace(&mut node.node_value);
+                let (left, right) = (node.left, node.right);
+                self.act_subtree(left, action.clone());
+                self.act_subtree(right, action);
+                self.rebuild(idx);
+            }
+        }
+    }
+
+    /// Pushes any actions stored in this node to its sons.
+    /// Actions stored in nodes are supposed to be eventually applied to its
+    /// whole subtree. Therefore, in order to access a node cleanly, without
+    /// the still-unapplied-function complicating things, you must `access()` the node.
+    pub fn access(&mut self, idx: NodeIdx) {
+        let node = self.node_mut(idx);
+        if node.action.is_identity() {
+            return;
+        }
+        let action = std::mem::take(&mut node.action);
+        // reversing
+        // for data that doesn't implement reversing, this becomes a no-op
+        // and hopefully optimized away
+        if action.to_reverse() {
+            std::mem::swap(&mut node.left, &mut node.right);
+        }
+        action.act_inplace(&mut node.subtree_summary);
+        action.act_inplace(&mut node.node_value);
+        let (left, right) = (node.left, node.right);
+        self.act_subtree(left, action.clone());
+        self.act_subtree(right, action);
+    }
+
+    /// Remakes the data that is stored in this node, based on its sons.
+    /// This is necessary when the data in the sons might have changed.
+    pub fn rebuild(&mut self, idx: NodeIdx) {
+        let node = self.node(idx);
+        assert!(node.action.is_identity());
+        let summary = self.subtree_summary(node.left)
+            + node.node_value.to_summary()
+            + self.subtree_summary(node.right);
+        self.node_mut(idx).subtree_summary = summary;
+    }
+
+    #[cfg(debug_assertions)]
+    /// Used for debugging. Prints a representation of the subtree rooted at `tree`,
+    /// in the same format as [`SomeEntry::representation`], using `alg_print`
+    /// to print the algorithm-specific data.
+    pub fn representation<F>(
+        &self,
+        tree: Option<NodeIdx>,
+        alg_print: &F,
+        to_reverse: bool,
+    ) -> String
+    where
+        F: Fn(&ArenaNode<D, T>) -> String,
+    {
+        let idx = match tree {
+            None => return String::from("*"),
+            Some(idx) => idx,
+        };
+        let node = self.node(idx);
+        let xor = node.action.to_reverse() ^ to_reverse;
+        let shebang = if node.action.to_reverse() { "!" } else { "" };
+        let mut left = self.representation(node.left, alg_print, xor);
+        let mut right = self.representation(node.right, alg_print, xor);
+        if xor {
+            std::mem::swap(&mut left, &mut right);
+        }
+        format!("<{} {} {} {} >", shebang, alg_print(node), left, right)
+    }
+
+    /// Returns the value stored in this node.
+    /// Requires mutable access because it calls [`Arena::access`], to ensure
+    /// that the action applies.
+    pub fn node_value(&mut self, idx: NodeIdx) -> &D::Value {
+        self.access(idx);
+        &self.node(idx).node_value
+    }
+
+    /// Pushes down all of the actions in the subtree rooted at `tree`, and returns
+    /// the indices of its nodes in order.
+    pub fn access_in_order(&mut self, tree: Option<NodeIdx>) -> Vec<NodeIdx> {
+        let mut res = vec![];
+        let mut stack = vec![];
+        let mut current = tree;
+        loop {
+            while let Some(idx) = current {
+                self.access(idx);
+                stack.push(idx);
+                current = self.node(idx).left;
+            }
+            match stack.pop() {
+                None => return res,
+                Some(idx) => {
+                    res.push(idx);
+                    current = self.node(idx).right;
+                }
+            }
+        }
+    }
+
+    /// Checks that the summaries and parent indices in the subtree rooted at `tree`
+    /// were calculated correctly, and calls `func` on every node.
+    /// Otherwise, panics.
+    pub fn assert_correctness_with<F>(&self, tree: Option<NodeIdx>, func: F)
+    where
+        D::Summary: Eq,
+        F: Fn(&Self, &ArenaNode<D, T>),
+    {
+        let mut stack: Vec<NodeIdx> = tree.into_iter().collect();
+        while let Some(idx) = stack.pop() {
+            let node = self.node(idx);
+            let os: D::Summary = self.subtree_summary(node.left)
+                + node.node_value.to_summary()
+                + self.subtree_summary(node.right);
+            assert!(node.subtree_summary == os, "Incorrect summaries found.");
+            for son in node.left.into_iter().chain(node.right) {
+                assert_eq!(self.node(son).parent, Some(idx), "Incorrect parent found.");
+            }
+            func(self, node);
+            stack.extend(node.left);
+            stack.extend(node.right);
+        }
+    }
+}
@@ -0,0 +1,740 @@
+//! A treap that stores its nodes in an [`Arena`].
+//!
+//! This has the same structure as the [`Treap`](crate::treap::Treap), but all of the
+//! operations are implemented by splitting and concatenating the tree.
+//! The priorities are drawn from a [`PrioritySource`], like in regular treaps.
+//!
+//! Since nodes never move inside the arena, the [`NodeIdx`] returned by [`ArenaTreap::insert`]
+//! is a stable handle to the inserted value: it stays valid across any other operations,
+//! until the value itself is deleted. Using a handle after its value was deleted panics,
+//! even if the value's slot in the arena was reused by another value.
+//! Using the parent links stored in the nodes, the handle can be used to find
+//! the value's position ([`ArenaTreap::index_of`], [`ArenaTreap::summary_left_of`]),
+//! to remove the value ([`ArenaTreap::remove`]) or to create a walker at the value ([`ArenaTreap::walker_at`]).
+//!
+//! [`ArenaTreap`] doesn't implement [`SomeTree`], since the iterators of [`SomeTree`] are
+//! iterators over [`BasicTree`](basic_tree::BasicTree)s. Instead, `&mut ArenaTreap` implements
+//! [`SomeTreeRef`], and the walker-based algorithms of [`SomeTree`], such as
+//! [`ArenaTreap::max_right`] and [`ArenaTreap::for_each_matching`], are available as methods.
+
+use super::*;
+use crate::treap::{PrioritySource, RngPriorities, ThreadRngPriorities};
+use crate::trees::segment_algorithms;
+use locators::{LeftEdgeOf, LocResult, Locator, RightEdgeOf};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+// The type that is used for bookkeeping.
+// convention: a bigger number should go higher up the tree.
+type T = u64;
+
+/// A treap that stores its nodes in an [`Arena`].
+/// The `P` parameter is the source of the priorities of the nodes.
+///```
+/// use grove::arena::treap::ArenaTreap;
+/// use grove::example_data::{StdNum, RevAffineAction};
+///
+/// let mut tree: ArenaTreap<StdNum> = (0..10).collect();
+/// tree.act_segment(RevAffineAction {to_reverse: true, mul: 1, add: 10}, 2..8);
+/// assert_eq!(tree.delete(0), Some(0));
+/// assert_eq!(tree.segment_summary(1..7).sum, (12..18).sum());
+/// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), vec![1, 17, 16, 15, 14, 13, 12, 8, 9]);
+///
+/// tree.clear();
+/// assert!(tree.is_empty());
+/// # tree.assert_correctness();
+///```
+pub struct ArenaTreap<D: Data, P = ThreadRngPriorities> {
+    arena: Arena<D, T>,
+    root: Option<NodeIdx>,
+    priorities: P,
+}
+
+impl<D: Data> ArenaTreap<D> {
+    /// Creates an empty treap, that draws its priorities from [`rand::thread_rng`].
+    pub fn new() -> ArenaTreap<D> {
+        ArenaTreap::with_priority_source(ThreadRngPriorities)
+    }
+}
+
+impl<D: Data> ArenaTreap<D, RngPriorities<StdRng>> {
+    /// Creates an empty treap, that draws its priorities from a random number generator
+    /// seeded by `seed`. See [`Treap::with_seed`](crate::treap::Treap::with_seed).
+    pub fn with_seed(seed: u64) -> Self {
+        ArenaTreap::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<D: Data, R: RngCore + SeedableRng> ArenaTreap<D, RngPriorities<R>> {
+    /// Creates an empty treap, that draws its priorities from the given random number generator.
+    pub fn with_rng(rng: R) -> Self {
+        ArenaTreap::with_priority_source(RngPriorities(rng))
+    }
+}
+
+impl<D: Data, P> ArenaTreap<D, P> {
+    /// Creates an empty treap, that draws its priorities from the given source.
+    pub fn with_priority_source(priorities: P) -> Self {
+        ArenaTreap {
+            arena: Arena::new(),
+            root: None,
+            priorities,
+        }
+    }
+
+    /// Returns the number of values in the tree.
+    pub fn len(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Returns true if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Removes all of the values, without walking the tree.
+    /// Keeps the allocated memory, so that it can be reused by later insertions.
+    pub fn clear(&mut self) {
+        self.arena.clear();
+        self.root = None;
+    }
+
+    /// Reserves space for at least `additional` more values.
+    pub fn reserve(&mut self, additional: usize) {
+        self.arena.slots.reserve(additional);
+    }
+
+    /// Returns the arena the nodes are stored in, and the index of the root.
+    pub fn arena(&self) -> (&Arena<D, T>, Option<NodeIdx>) {
+        (&self.arena, self.root)
+    }
+
+    /// Sets the root of the tree, and marks it as a root.
+    fn set_root(&mut self, root: Option<NodeIdx>) {
+        self.arena.make_root(root);
+        self.root = root;
+    }
+
+    /// Returns the root's priority.
+    /// Returns [`None`] if the tree is empty.
+    pub fn priority(&self) -> Option<T> {
+        Some(*self.arena.node(self.root?).alg_data())
+    }
+
+    /// Returns the summary of all values in this tree.
+    pub fn subtree_summary(&self) -> D::Summary {
+        self.arena.subtree_summary(self.root)
+    }
+
+    /// Compute the summary of a subsegment.
+    /// This doesn't restructure the tree.
+    pub fn segment_summary<L: Locator<D>>(&mut self, locator: L) -> D::Summary {
+        segment_algorithms::segment_summary(self, locator)
+    }
+
+    /// Apply an action on a subsegment.
+    /// Supports reversals.
+    pub fn act_segment<L: Locator<D>>(&mut self, action: D::Action, locator: L) {
+        let (left, mid, right) = split_segment(&mut self.arena, self.root, locator);
+        self.arena.act_subtree(mid, action);
+        let root = concatenate3(&mut self.arena, left, mid, right);
+        self.set_root(root);
+    }
+
+    /// Returns the largest `end` such that `pred` holds on the summary of the segment `start..end`.
+    /// See [`SomeTree::max_right`].
+    pub fn max_right<F>(&mut self, start: usize, pred: F) -> usize
+    where
+        F: FnMut(&D::Summary) -> bool,
+        D::Summary: SizedSummary,
+    {
+        segment_algorithms::max_right(self, start, pred)
+    }
+
+    /// Returns the smallest `start` such that `pred` holds on the summary of the segment `start..end`.
+    /// See [`SomeTree::min_left`].
+    pub fn min_left<F>(&mut self, end: usize, pred: F) -> usize
+    where
+        F: FnMut(&D::Summary) -> bool,
+        D::Summary: SizedSummary,
+    {
+        segment_algorithms::min_left(self, end, pred)
+    }
+
+    /// Calls `f` on every value that matches the filter, in order.
+    /// See [`SomeTree::for_each_matching`].
+    pub fn for_each_matching<F, C>(&mut self, filter: F, f: C)
+    where
+        F: SubtreeFilter<D>,
+        C: FnMut(&D::Value),
+    {
+        segment_algorithms::for_each_matching(self, filter, f)
+    }
+
+    /// Applies an action on every value that matches the filter.
+    /// See [`SomeTree::act_matching`].
+    pub fn act_matching<F: SubtreeFilter<D>>(&mut self, action: D::Action, filter: F) {
+        segment_algorithms::act_matching(self, action, filter)
+    }
+
+    /// Calls `f` on every value in the segment, in order.
+    /// See [`SomeTree::for_each_mut`].
+    pub fn for_each_mut<L, F>(&mut self, locator: L, f: F)
+    where
+        L: Locator<D>,
+        F: FnMut(&mut D::Value),
+    {
+        segment_algorithms::for_each_mut(self, locator, f)
+    }
+
+    /// Deletes a value that the locator accepts, and returns it.
+    /// Returns [`None`] if the locator doesn't accept any value in the tree.
+    pub fn delete<L: Locator<D>>(&mut self, locator: L) -> Option<D::Value> {
+        let (left, mid, right) = split_segment(&mut self.arena, self.root, locator);
+        let (mid, res) = match mid {
+            None => (None, None),
+            Some(idx) => {
+                self.arena.access(idx);
+                let node = self.arena.free(idx);
+                let mid = concatenate(&mut self.arena, node.left, node.right);
+                (mid, Some(node.node_value))
+            }
+        };
+        let root = concatenate3(&mut self.arena, left, mid, right);
+        self.set_root(root);
+        res
+    }
+
+    /// Returns the value that the handle refers to.
+    /// This pushes down the actions above the value, so it requires mutable access.
+    pub fn get(&mut self, handle: NodeIdx) -> &D::Value {
+        self.arena.access_path(handle);
+        &self.arena.node(handle).node_value
+    }
+
+    /// Returns the summary of all the values to the left of the value that the handle refers to.
+    /// Takes `O(depth)` time, i.e., logarithmic time.
+    pub fn summary_left_of(&mut self, handle: NodeIdx) -> D::Summary {
+        let path = self.arena.access_path(handle);
+        let mut res = self.arena.subtree_summary(self.arena.node(handle).left);
+        for pair in path.windows(2).rev() {
+            let (parent, son) = (self.arena.node(pair[0]), pair[1]);
+            if parent.right == Some(son) {
+                res = self.arena.subtree_summary(parent.left) + parent.node_summary() + res;
+            }
+        }
+        res
+    }
+
+    /// Returns the index of the value that the handle refers to.
+    /// Takes `O(depth)` time, i.e., logarithmic time.
+    ///```
+    /// use grove::arena::treap::ArenaTreap;
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: ArenaTreap<StdNum> = (0..10).collect();
+    /// let handle = tree.insert(5..5, 100).unwrap();
+    /// tree.act_segment(RevAffineAction {to_reverse: true, mul: 1, add: 0}, ..);
+    /// tree.delete(0);
+    /// assert_eq!(tree.index_of(handle), 4);
+    /// assert_eq!(tree.remove(handle), 100);
+    /// # tree.assert_correctness();
+    ///```
+    pub fn index_of(&mut self, handle: NodeIdx) -> usize
+    where
+        D::Summary: SizedSummary,
+    {
+        self.summary_left_of(handle).size()
+    }
+
+    /// Removes the value that the handle refers to, and returns it.
+    /// This invalidates the handle.
+    pub fn remove(&mut self, handle: NodeIdx) -> D::Value {
+        let path = self.arena.access_path(handle);
+        let node = self.arena.free(handle);
+        let merged = concatenate(&mut self.arena, node.left, node.right);
+        match path.len().checked_sub(2).map(|i| path[i]) {
+            None => self.set_root(merged),
+            Some(parent) => {
+                if self.arena.node(parent).left == Some(handle) {
+                    self.arena.set_left(parent, merged);
+                } else {
+                    self.arena.set_right(parent, merged);
+                }
+                for &idx in path[..path.len() - 1].iter().rev() {
+                    self.arena.rebuild(idx);
+                }
+            }
+        }
+        node.node_value
+    }
+
+    /// Returns a walker at the root of the tree.
+    pub fn walker(&mut self) -> ArenaWalker<'_, D, P> {
+        if let Some(root) = self.root {
+            self.arena.access(root);
+        }
+        ArenaWalker {
+            path: self.root.into_iter().collect(),
+            sides: vec![],
+            frames: vec![(Default::default(), Default::default())],
+            tree: self,
+        }
+    }
+
+    /// Returns a walker at the value that the handle refers to.
+    pub fn walker_at(&mut self, handle: NodeIdx) -> ArenaWalker<'_, D, P> {
+        let path = self.arena.access_path(handle);
+        let mut walker = self.walker();
+        for pair in path.windows(2) {
+            if walker.tree.arena.node(pair[0]).left == Some(pair[1]) {
+                walker.go_left().unwrap();
+            } else {
+                walker.go_right().unwrap();
+            }
+        }
+        walker
+    }
+
+    /// Iterates over the values of the tree.
+    /// This pushes down all of the actions in the tree, so it requires mutable access.
+    pub fn iter(&mut self) -> Iter<'_, D, P> {
+        let order = self.arena.access_in_order(self.root);
+        Iter {
+            tree: self,
+            order: order.into_iter(),
+        }
+    }
+
+    /// Asserts that the priorities maintain the priority invariant,
+    /// and that the summaries and the parent links are correct.
+    /// Panics otherwise.
+    pub fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        if let Some(root) = self.root {
+            assert_eq!(self.arena.node(root).parent, None, "The root has a parent");
+        }
+        self.arena
+            .assert_correctness_with(self.root, |arena, node| {
+                for son in node.left.into_iter().chain(node.right) {
+                    assert!(node.alg_data() > arena.node(son).alg_data());
+                }
+            });
+    }
+}
+
+impl<D: Data, P: PrioritySource> ArenaTreap<D, P> {
+    /// Inserts a value at the empty position that the locator leads to.
+    /// Returns [`None`] if the locator accepts some value in the tree,
+    /// i.e., doesn't lead to an empty position.
+    /// Otherwise, returns a handle to the inserted value.
+    pub fn insert<L: Locator<D>>(&mut self, locator: L, value: D::Value) -> Option<NodeIdx> {
+        let (left, mid, right) = split_segment(&mut self.arena, self.root, locator);
+        if mid.is_some() {
+            let root = concatenate3(&mut self.arena, left, mid, right);
+            self.set_root(root);
+            return None;
+        }
+        let node = ArenaNode::new_alg(value, self.priorities.next_priority());
+        let idx = self.arena.alloc(node);
+        let root = concatenate3(&mut self.arena, left, Some(idx), right);
+        self.set_root(root);
+        Some(idx)
+    }
+}
+
+impl<D: Data, P: PrioritySource> Default for ArenaTreap<D, P> {
+    fn default() -> Self {
+        ArenaTreap::with_priority_source(P::default())
+    }
+}
+
+impl<D: Data, P: PrioritySource> std::iter::FromIterator<D::Value> for ArenaTreap<D, P> {
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut tree: ArenaTreap<D, P> = Default::default();
+        tree.reserve(iter.size_hint().0);
+        for value in iter {
+            let node = ArenaNode::new_alg(value, tree.priorities.next_priority());
+            let idx = tree.arena.alloc(node);
+            let root = concatenate(&mut tree.arena, tree.root, Some(idx));
+            tree.set_root(root);
+        }
+        tree
+    }
+}
+
+impl<D: Data, P> IntoIterator for ArenaTreap<D, P> {
+    type Item = D::Value;
+    type IntoIter = std::vec::IntoIter<D::Value>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let order = self.arena.access_in_order(self.root);
+        let values: Vec<D::Value> = order
+            .into_iter()
+            .map(|idx| self.arena.free(idx).node_value)
+            .collect();
+        values.into_iter()
+    }
+}
+
+impl<'a, D: Data, P> SomeTreeRef<D> for &'a mut ArenaTreap<D, P> {
+    type Walker = ArenaWalker<'a, D, P>;
+
+    fn walker(self) -> Self::Walker {
+        ArenaTreap::walker(self)
+    }
+}
+
+/// An iterator over the values of an [`ArenaTreap`].
+pub struct Iter<'a, D: Data, P> {
+    tree: &'a ArenaTreap<D, P>,
+    order: std::vec::IntoIter<NodeIdx>,
+}
+
+impl<'a, D: Data, P> Iterator for Iter<'a, D, P> {
+    type Item = &'a D::Value;
+
+    fn next(&mut self) -> Option<&'a D::Value> {
+        let idx = self.order.next()?;
+        // all of the nodes were already accessed
+        Some(&self.tree.arena.node(idx).node_value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+/// A walker for an [`ArenaTreap`].
+/// Walking down pushes down the actions, and walking up rebuilds the nodes.
+/// The walker doesn't restructure the tree, so handles stay valid while it exists.
+/// When the walker is dropped, it goes back up to the root.
+pub struct ArenaWalker<'a, D: Data, P> {
+    tree: &'a mut ArenaTreap<D, P>,
+    // the nodes from the root to the current position.
+    // if the walker is at an empty position, the last node is its parent.
+    path: Vec<NodeIdx>,
+    // the sides taken from the root.
+    sides: Vec<Side>,
+    // the summaries of the values to the left and right of the current subtree.
+    frames: Vec<(D::Summary, D::Summary)>,
+}
+
+impl<'a, D: Data, P> ArenaWalker<'a, D, P> {
+    /// Returns the handle of the current node.
+    /// Returns [`None`] if the walker is at an empty position.
+    pub fn handle(&self) -> Option<NodeIdx> {
+        if self.path.len() > self.sides.len() {
+            self.path.last().copied()
+        } else {
+            None
+        }
+    }
+
+    fn go_to(&mut self, side: Side) -> Result<(), ()> {
+        let idx = self.handle().ok_or(())?;
+        let arena = &self.tree.arena;
+        let node = arena.node(idx);
+        let (far_left, far_right) = self.frames.last().unwrap().clone();
+        let (son, frame) = match side {
+            Side::Left => {
+                let far_right = node.node_summary() + arena.subtree_summary(node.right) + far_right;
+                (node.left, (far_left, far_right))
+            }
+            Side::Right => {
+                let far_left = far_left + arena.subtree_summary(node.left) + node.node_summary();
+                (node.right, (far_left, far_right))
+            }
+        };
+        self.frames.push(frame);
+        self.sides.push(side);
+        if let Some(son) = son {
+            self.tree.arena.access(son);
+            self.path.push(son);
+        }
+        Ok(())
+    }
+}
+
+impl<'a, D: Data, P> SomeWalker<D> for ArenaWalker<'a, D, P> {
+    fn depth(&self) -> usize {
+        self.sides.len()
+    }
+
+    fn value(&self) -> Option<&D::Value> {
+        Some(&self.tree.arena.node(self.handle()?).node_value)
+    }
+
+    fn go_left(&mut self) -> Result<(), ()> {
+        self.go_to(Side::Left)
+    }
+
+    fn go_right(&mut self) -> Result<(), ()> {
+        self.go_to(Side::Right)
+    }
+
+    fn go_up(&mut self) -> Result<Side, ()> {
+        let side = self.sides.pop().ok_or(())?;
+        if self.path.len() > self.sides.len() + 1 {
+            self.path.pop();
+        }
+        self.frames.pop();
+        self.tree.arena.rebuild(*self.path.last().unwrap());
+        Ok(side)
+    }
+
+    fn far_left_summary(&self) -> D::Summary {
+        self.frames.last().unwrap().0.clone()
+    }
+
+    fn far_right_summary(&self) -> D::Summary {
+        self.frames.last().unwrap().1.clone()
+    }
+}
+
+impl<'a, D: Data, P> SomeEntry<D> for ArenaWalker<'a, D, P> {
+    fn with_value<F, R>(&mut self, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut D::Value) -> R,
+    {
+        let idx = self.handle()?;
+        let res = f(&mut self.tree.arena.node_mut(idx).node_value);
+        self.tree.arena.rebuild(idx);
+        Some(res)
+    }
+
+    fn node_summary(&self) -> D::Summary {
+        match self.handle() {
+            Some(idx) => self.tree.arena.node(idx).node_summary(),
+            None => Default::default(),
+        }
+    }
+
+    fn subtree_summary(&self) -> D::Summary {
+        self.tree.arena.subtree_summary(self.handle())
+    }
+
+    fn left_subtree_summary(&self) -> Option<D::Summary> {
+        let node = self.tree.arena.node(self.handle()?);
+        Some(self.tree.arena.subtree_summary(node.left))
+    }
+
+    fn right_subtree_summary(&self) -> Option<D::Summary> {
+        let node = self.tree.arena.node(self.handle()?);
+        Some(self.tree.arena.subtree_summary(node.right))
+    }
+
+    fn act_node(&mut self, action: D::Action) -> Option<()> {
+        let idx = self.handle()?;
+        action.act_inplace(&mut self.tree.arena.node_mut(idx).node_value);
+        self.tree.arena.rebuild(idx);
+        Some(())
+    }
+
+    fn act_subtree(&mut self, action: D::Action) {
+        if let Some(idx) = self.handle() {
+            self.tree.arena.act_subtree(Some(idx), action);
+            self.tree.arena.access(idx);
+        }
+    }
+
+    fn act_left_subtree(&mut self, action: D::Action) -> Option<()> {
+        let idx = self.handle()?;
+        let left = self.tree.arena.node(idx).left;
+        self.tree.arena.act_subtree(left, action);
+        self.tree.arena.rebuild(idx);
+        Some(())
+    }
+
+    fn act_right_subtree(&mut self, action: D::Action) -> Option<()> {
+        let idx = self.handle()?;
+        let right = self.tree.arena.node(idx).right;
+        self.tree.arena.act_subtree(right, action);
+        self.tree.arena.rebuild(idx);
+        Some(())
+    }
+
+    fn assert_correctness_locally(&self)
+    where
+        D::Summary: Eq,
+    {
+        if let Some(idx) = self.handle() {
+            let arena = &self.tree.arena;
+            let node = arena.node(idx);
+            let summary = arena.subtree_summary(node.left)
+                + node.node_summary()
+                + arena.subtree_summary(node.right);
+            assert!(
+                arena.subtree_summary(Some(idx)) == summary,
+                "Incorrect summaries found."
+            );
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    type EntryTreeData = T;
+
+    /// Arena nodes aren't [`BasicNode`](basic_tree::BasicNode)s, so `alg_print` is ignored,
+    /// and the priorities are printed instead.
+    #[cfg(debug_assertions)]
+    fn representation<F>(&self, _alg_print: &F, to_reverse: bool) -> String
+    where
+        F: Fn(&basic_tree::BasicNode<D, T>) -> String,
+    {
+        self.tree.arena.representation(
+            self.handle(),
+            &|node: &ArenaNode<D, T>| node.alg_data().to_string(),
+            to_reverse,
+        )
+    }
+}
+
+impl<'a, D: Data, P> Drop for ArenaWalker<'a, D, P> {
+    fn drop(&mut self) {
+        self.go_to_root();
+    }
+}
+
+/// Splits the tree at the position that the locator leads to.
+/// The locator should never accept a node, e.g., [`LeftEdgeOf`] and [`RightEdgeOf`].
+/// `far_left` and `far_right` are the summaries of the values to the left and right of the tree.
+fn split<D: Data, L: Locator<D>>(
+    arena: &mut Arena<D, T>,
+    tree: Option<NodeIdx>,
+    locator: &L,
+    far_left: D::Summary,
+    far_right: D::Summary,
+) -> (Option<NodeIdx>, Option<NodeIdx>) {
+    let idx = match tree {
+        None => return (None, None),
+        Some(idx) => idx,
+    };
+    arena.access(idx);
+    let node = arena.node(idx);
+    let (node_left, node_right) = (node.left, node.right);
+    let left_summary = far_left.clone() + arena.subtree_summary(node_left);
+    let right_summary = arena.subtree_summary(node_right) + far_right.clone();
+    let node_summary = node.node_summary();
+    match locator.locate(&left_summary, &node.node_value, &right_summary) {
+        LocResult::GoRight => {
+            let far_left = left_summary + node_summary;
+            let (left, right) = split(arena, node_right, locator, far_left, far_right);
+            arena.set_right(idx, left);
+            arena.rebuild(idx);
+            (Some(idx), right)
+        }
+        LocResult::GoLeft => {
+            let far_right = node_summary + right_summary;
+            let (left, right) = split(arena, node_left, locator, far_left, far_right);
+            arena.set_left(idx, right);
+            arena.rebuild(idx);
+            (left, Some(idx))
+        }
+        LocResult::Accept => panic!("splitting locators shouldn't accept nodes"),
+    }
+}
+
+/// Splits the tree into the part before the locator's segment, the segment,
+/// and the part after the segment.
+fn split_segment<D: Data, L: Locator<D>>(
+    arena: &mut Arena<D, T>,
+    tree: Option<NodeIdx>,
+    locator: L,
+) -> (Option<NodeIdx>, Option<NodeIdx>, Option<NodeIdx>) {
+    let default = Default::default;
+    let (left, rest) = split(
+        arena,
+        tree,
+        &LeftEdgeOf(locator.clone()),
+        default(),
+        default(),
+    );
+    let far_left = arena.subtree_summary(left);
+    let (mid, right) = split(arena, rest, &RightEdgeOf(locator), far_left, default());
+    (left, mid, right)
+}
+
+/// Splits the tree that contains the node, into the part before the node and the part after it.
+/// The node goes to the left part if `to_left` is true, and to the right part otherwise.
+/// Walks up from the node using the parent links, so no locator is needed.
+pub(crate) fn split_at_node<D: Data>(
+    arena: &mut Arena<D, T>,
+    idx: NodeIdx,
+    to_left: bool,
+) -> (Option<NodeIdx>, Option<NodeIdx>) {
+    let path = arena.access_path(idx);
+    let (mut left, mut right) = if to_left {
+        let right = arena.node(idx).right;
+        arena.node_mut(idx).right = None;
+        (Some(idx), right)
+    } else {
+        let left = arena.node(idx).left;
+        arena.node_mut(idx).left = None;
+        (left, Some(idx))
+    };
+    arena.rebuild(idx);
+    for pair in path.windows(2).rev() {
+        let (parent, son) = (pair[0], pair[1]);
+        if arena.node(parent).right == Some(son) {
+            arena.set_right(parent, left);
+            arena.rebuild(parent);
+            left = Some(parent);
+        } else {
+            arena.set_left(parent, right);
+            arena.rebuild(parent);
+            right = Some(parent);
+        }
+    }
+    arena.make_root(left);
+    arena.make_root(right);
+    (left, right)
+}
+
+/// Returns the root of the tree that contains the node.
+pub(crate) fn root_of<D: Data>(arena: &Arena<D, T>, mut idx: NodeIdx) -> NodeIdx {
+    while let Some(parent) = arena.node(idx).parent {
+        idx = parent;
+    }
+    idx
+}
+
+/// Concatenates two trees, by merging their right and left spines according to the priorities.
+/// The result isn't marked as a root.
+pub(crate) fn concatenate<D: Data>(
+    arena: &mut Arena<D, T>,
+    left: Option<NodeIdx>,
+    right: Option<NodeIdx>,
+) -> Option<NodeIdx> {
+    let (left_idx, right_idx) = match (left, right) {
+        (None, _) => return right,
+        (_, None) => return left,
+        (Some(left_idx), Some(right_idx)) => (left_idx, right_idx),
+    };
+    if arena.node(left_idx).alg_data() > arena.node(right_idx).alg_data() {
+        arena.access(left_idx);
+        let son = arena.node(left_idx).right;
+        let son = concatenate(arena, son, right);
+        arena.set_right(left_idx, son);
+        arena.rebuild(left_idx);
+        left
+    } else {
+        arena.access(right_idx);
+        let son = arena.node(right_idx).left;
+        let son = concatenate(arena, left, son);
+        arena.set_left(right_idx, son);
+        arena.rebuild(right_idx);
+        right
+    }
+}
+
+fn concatenate3<D: Data>(
+    arena: &mut Arena<D, T>,
+    left: Option<NodeIdx>,
+    mid: Option<NodeIdx>,
+    right: Option<NodeIdx>,
+) -> Option<NodeIdx> {
+    let left = concatenate(arena, left, mid);
+    concatenate(arena, left, right)
+}
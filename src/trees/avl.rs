@@ -108,6 +108,101 @@ impl<D: Data> AVLTree<D> {
         self.tree
             .assert_correctness_with(Self::assert_ranks_locally_internal);
     }
+
+    /// Finds any node that the locator `Accept`s, with only immutable access to the tree.
+    /// Same as [`BasicTree::search_imm`].
+    pub fn search_imm<L: locators::Locator<D>>(&self, locator: L) -> ImmDownBasicWalker<'_, D, T> {
+        self.tree.search_imm(locator)
+    }
+
+    /// Returns any value that the locator `Accept`s, together with its pending actions,
+    /// with only immutable access to the tree. Same as [`BasicTree::get`].
+    pub fn get<L: locators::Locator<D>>(&self, locator: L) -> Option<ValueRef<'_, D>> {
+        self.tree.get(locator)
+    }
+
+    /// Iterates over all of the values, with only immutable access to the tree.
+    /// Same as [`BasicTree::iter_imm`].
+    pub fn iter_imm(&self) -> iterators::IterImm<'_, D, T> {
+        self.tree.iter_imm()
+    }
+
+    /// Computes the union of two trees, ordered by keys.
+    /// We order the resulting tree based on the `D::Value: Ord` instance, assuming that
+    /// the values in the existing trees are also in the correct order.
+    /// This is different from concatenate, because concatenate puts first all elements of the first tree,
+    /// and then all of the elements of the second tree.
+    ///
+    /// If elements with equal keys are found, all of them are kept, in an arbitrary order.
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn union(&mut self, other: AVLTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let tree = std::mem::take(self);
+        *self = union(tree, other);
+    }
+
+    /// Keeps only the values that are equal to some value of `other`, ordered by keys.
+    /// We assume that the values in both trees are in sorted order, without duplicates.
+    /// See [`intersection`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn intersection(&mut self, other: AVLTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let tree = std::mem::take(self);
+        *self = intersection(tree, other);
+    }
+
+    /// Removes all the values that are equal to some value of `other`, ordered by keys.
+    /// We assume that the values in both trees are in sorted order.
+    /// See [`difference`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn difference(&mut self, other: AVLTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let tree = std::mem::take(self);
+        *self = difference(tree, other);
+    }
+
+    /// Keeps the values that are in exactly one of the trees, ordered by keys.
+    /// We assume that the values in both trees are in sorted order, without duplicates.
+    /// See [`symmetric_difference`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn symmetric_difference(&mut self, other: AVLTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let tree = std::mem::take(self);
+        *self = symmetric_difference(tree, other);
+    }
+
+    /// Returns true if every value of this tree is equal to some value of `other`.
+    /// See [`is_subset`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn is_subset(&mut self, other: &mut AVLTree<D>) -> bool
+    where
+        D::Value: Ord,
+    {
+        is_subset(self, other)
+    }
 }
 
 impl<D: Data> Rankable for AVLTree<D> {
@@ -131,6 +226,20 @@ impl<D: Data> Default for AVLTree<D> {
     }
 }
 
+impl<D: Data> AsBasicTree<D> for AVLTree<D> {
+    fn as_basic_tree(&self) -> &BasicTree<D, T> {
+        &self.tree
+    }
+}
+
+impl<D: Data> sealed::AsBasicTreeMut<D> for AVLTree<D> {
+    type AlgData = T;
+
+    fn as_basic_tree_mut(&mut self, _: sealed::Token) -> &mut BasicTree<D, T> {
+        &mut self.tree
+    }
+}
+
 impl<D: Data> SomeTree<D> for AVLTree<D> {
     fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
     where
@@ -707,3 +816,218 @@ pub fn concatenate_with_middle<D: Data>(
     left.concatenate_middle_right(mid, right);
     left
 }
+
+/// Computes the union of two trees, ordered by keys.
+/// We order the resulting tree based on the `D::Value: Ord` instance, assuming that
+/// the values in the existing trees are also in the correct order.
+/// This is different from concatenate, because concatenate puts first all elements of the first tree,
+/// and then all of the elements of the second tree.
+///
+/// If elements with equal keys are found, all of them are kept, in an arbitrary order.
+///
+///```rust
+///use grove::{SomeTree, avl, avl::AVLTree};
+///use grove::example_data::{PlainData};
+///
+///type T = AVLTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = avl::union(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(),
+///    [0,1,2,3,4,4,5,5,6,6,7,8].iter().cloned().collect::<Vec<_>>());
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn union<D: Data>(tree1: AVLTree<D>, tree2: AVLTree<D>) -> AVLTree<D>
+where
+    D::Value: Ord,
+{
+    set_algorithms::union(tree1, tree2)
+}
+
+/// Computes the union of two trees, ordered by keys.
+/// Unlike [`union`], values with equal keys are combined into a single value using the policy.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, avl, avl::AVLTree};
+///use grove::example_data::{PlainData};
+///
+///type T = AVLTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = avl::union_with(tree1, tree2, |a, _| a);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), (0..9).collect::<Vec<_>>());
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn union_with<D: Data, DP>(tree1: AVLTree<D>, tree2: AVLTree<D>, mut policy: DP) -> AVLTree<D>
+where
+    D::Value: Ord,
+    DP: DuplicatePolicy<D::Value>,
+{
+    set_algorithms::union_with(tree1, tree2, &mut policy)
+}
+
+/// Computes the intersection of two trees, ordered by keys:
+/// Keeps the values of `tree1` that are equal to some value of `tree2`.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, avl, avl::AVLTree};
+///use grove::example_data::{PlainData};
+///
+///type T = AVLTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = avl::intersection(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![4,5,6]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn intersection<D: Data>(tree1: AVLTree<D>, tree2: AVLTree<D>) -> AVLTree<D>
+where
+    D::Value: Ord,
+{
+    intersection_with(tree1, tree2, KeepLeft)
+}
+
+/// Computes the intersection of two trees, ordered by keys.
+/// Values with equal keys are combined into a single value using the policy.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, KeepRight, avl, avl::AVLTree};
+///use grove::example_data::{PlainData};
+///
+///type T = AVLTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = avl::intersection_with(tree1, tree2, KeepRight);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![4,5,6]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn intersection_with<D: Data, DP>(
+    tree1: AVLTree<D>,
+    tree2: AVLTree<D>,
+    mut policy: DP,
+) -> AVLTree<D>
+where
+    D::Value: Ord,
+    DP: DuplicatePolicy<D::Value>,
+{
+    set_algorithms::intersection_with(tree1, tree2, &mut policy)
+}
+
+/// Computes the difference of two trees, ordered by keys:
+/// Keeps the values of `tree1` that are not equal to any value of `tree2`.
+/// We assume that the values in both trees are in sorted order.
+///
+///```rust
+///use grove::{SomeTree, avl, avl::AVLTree};
+///use grove::example_data::{PlainData};
+///
+///type T = AVLTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = avl::difference(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![0,1,2,3]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn difference<D: Data>(tree1: AVLTree<D>, tree2: AVLTree<D>) -> AVLTree<D>
+where
+    D::Value: Ord,
+{
+    set_algorithms::difference(tree1, tree2)
+}
+
+/// Computes the symmetric difference of two trees, ordered by keys:
+/// Keeps the values that are in exactly one of the trees.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, avl, avl::AVLTree};
+///use grove::example_data::{PlainData};
+///
+///type T = AVLTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = avl::symmetric_difference(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![0,1,2,3,7,8]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn symmetric_difference<D: Data>(tree1: AVLTree<D>, tree2: AVLTree<D>) -> AVLTree<D>
+where
+    D::Value: Ord,
+{
+    set_algorithms::symmetric_difference(tree1, tree2)
+}
+
+/// Returns true if every value of `tree1` is equal to some value of `tree2`.
+/// We assume that the values in both trees are in sorted order.
+///
+/// The trees are split apart during the check, and joined back afterwards,
+/// so they keep their values, but not necessarily their shapes.
+///
+///```rust
+///use grove::{SomeTree, avl, avl::AVLTree};
+///use grove::example_data::{PlainData};
+///
+///type T = AVLTree<PlainData<i32>>;
+///let mut tree1: T = (3..7).collect();
+///let mut tree2: T = (0..9).collect();
+///assert!(avl::is_subset(&mut tree1, &mut tree2));
+///assert!(!avl::is_subset(&mut tree2, &mut tree1));
+/// # tree1.assert_correctness();
+/// # tree2.assert_correctness();
+///assert_eq!(tree1.into_iter().collect::<Vec<_>>(), (3..7).collect::<Vec<_>>());
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn is_subset<D: Data>(tree1: &mut AVLTree<D>, tree2: &mut AVLTree<D>) -> bool
+where
+    D::Value: Ord,
+{
+    set_algorithms::is_subset(tree1, tree2)
+}
+
+impl<D: Data> set_algorithms::JoinableTree<D> for AVLTree<D> {
+    fn expose(self) -> Option<(Self, D::Value, Self)> {
+        let mut node = self.tree.into_node()?;
+        node.access();
+        let left = AVLTree {
+            tree: std::mem::replace(&mut node.left, BasicTree::Empty),
+        };
+        let right = AVLTree {
+            tree: std::mem::replace(&mut node.right, BasicTree::Empty),
+        };
+        Some((left, node.node_value, right))
+    }
+
+    fn join(left: Self, mid: D::Value, right: Self) -> Self {
+        concatenate_with_middle(left, mid, right)
+    }
+}
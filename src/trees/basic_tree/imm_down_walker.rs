@@ -2,8 +2,10 @@ use crate::*;
 use trees::basic_tree::BasicTree;
 
 /// A BasicWalker version that is immutable, and can only go down.
-#[derive(Copy)]
-pub(crate) struct ImmDownBasicWalker<'a, D: Data, T = ()> {
+///
+/// Since it can't apply the pending actions to the nodes it passes through, it keeps
+/// their composition, and applies it to the summaries it computes.
+pub struct ImmDownBasicWalker<'a, D: Data, T = ()> {
     tree: &'a BasicTree<D, T>,
 
     // to be applied to everything in `tree`.
@@ -22,7 +24,12 @@ pub(crate) struct ImmDownBasicWalker<'a, D: Data, T = ()> {
 /// `D: Clone` and `T: Clone`.
 impl<'a, D: Data, T> Clone for ImmDownBasicWalker<'a, D, T> {
     fn clone(&self) -> Self {
-        ImmDownBasicWalker { ..*self }
+        ImmDownBasicWalker {
+            tree: self.tree,
+            current_action: self.current_action.clone(),
+            far_left_summary: self.far_left_summary.clone(),
+            far_right_summary: self.far_right_summary.clone(),
+        }
     }
 }
 
@@ -74,9 +81,8 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
 
         let extra = self.current_action.act(node.node_value.to_summary())
             + self.current_action.act(right.subtree_summary());
-        self.far_right_summary = extra + self.far_right_summary;
-        self.tree = left;
-        self.current_action = self.current_action + left.action();
+        self.far_right_summary = extra.clone() + std::mem::take(&mut self.far_right_summary);
+        self.enter(left);
         Some(extra)
     }
 
@@ -103,13 +109,38 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
 
         let extra = self.current_action.act(left.subtree_summary())
             + self.current_action.act(node.node_value.to_summary());
-        self.far_left_summary = self.far_left_summary + extra;
-        self.tree = right;
-        self.current_action = self.current_action + right.action();
+        self.far_left_summary = std::mem::take(&mut self.far_left_summary) + extra.clone();
+        self.enter(right);
         Some(extra)
     }
 
-    /// Returns the value at the current node.
+    /// Goes to the son, and composes its action onto the current action.
+    /// Most nodes have no pending action, so the composition is skipped for them.
+    fn enter(&mut self, son: &'a BasicTree<D, T>) {
+        if let Some(node) = son.node() {
+            if !node.action().is_identity() {
+                self.current_action =
+                    std::mem::take(&mut self.current_action) + node.action().clone();
+            }
+        }
+        self.tree = son;
+    }
+
+    /// Returns true if the walker is at an empty position.
+    pub fn is_empty(&self) -> bool {
+        self.tree.node().is_none()
+    }
+
+    /// Returns the value at the current node, together with the actions that are pending on it.
+    /// Unlike [`ImmDownBasicWalker::value`], this doesn't require `D::Value: Clone`.
+    pub fn value_ref(&self) -> Option<ValueRef<'a, D>> {
+        Some(ValueRef::new(
+            &self.tree.node()?.node_value,
+            self.current_action.clone(),
+        ))
+    }
+
+    /// Returns a copy of the value at the current node, with the pending actions applied.
     pub fn value(&self) -> Option<D::Value>
     where
         D::Value: Clone,
@@ -128,6 +159,7 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
         )
     }
 
+    /// Returns the summary of all the values to the left of the current node.
     pub fn left_summary(&self) -> D::Summary {
         if let Some(node) = self.tree.node() {
             let left = if self.current_action.to_reverse() {
@@ -135,12 +167,13 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
             } else {
                 &node.left
             };
-            self.far_left_summary + self.current_action.act(left.subtree_summary())
+            self.far_left_summary.clone() + self.current_action.act(left.subtree_summary())
         } else {
-            self.far_left_summary
+            self.far_left_summary.clone()
         }
     }
 
+    /// Returns the summary of all the values to the right of the current node.
     pub fn right_summary(&self) -> D::Summary {
         if let Some(node) = self.tree.node() {
             let right = if self.current_action.to_reverse() {
@@ -148,35 +181,122 @@ impl<'a, D: Data, T> ImmDownBasicWalker<'a, D, T> {
             } else {
                 &node.right
             };
-            self.current_action.act(right.subtree_summary()) + self.far_right_summary
+            self.current_action.act(right.subtree_summary()) + self.far_right_summary.clone()
         } else {
-            self.far_right_summary
+            self.far_right_summary.clone()
         }
     }
 
+    /// Returns the algorithm-specific data of the current node.
     pub fn alg_data(&self) -> Option<&T> {
         self.tree.alg_data()
     }
 
+    /// Returns true if the locator can be called on the value as it is stored,
+    /// because the value has no pending actions, or the locator doesn't look at it.
+    fn can_locate_stored<L: Locator<D>>(&self, locator: &L) -> bool {
+        self.current_action.is_identity() || !locator.uses_values()
+    }
+
+    /// Calls the locator on the current node.
+    /// If at an empty position, returns [`None`].
+    ///
+    /// If the value has pending actions, they are applied to a copy of it first,
+    /// unless the locator doesn't look at values (see [`Locator::uses_values`]).
     pub fn query_locator<L: Locator<D>>(&self, locator: &L) -> Option<locators::LocResult>
     where
         D::Value: Clone,
     {
         let node = self.tree.node()?;
+        let direction = if self.can_locate_stored(locator) {
+            locator.locate(
+                &self.left_summary(),
+                &node.node_value,
+                &self.right_summary(),
+            )
+        } else {
+            let value = self.current_action.act(node.node_value.clone());
+            locator.locate(&self.left_summary(), &value, &self.right_summary())
+        };
+        Some(direction)
+    }
 
-        // deal with reversals
-        let mut right = &node.right;
-        let mut left = &node.left;
-        if self.current_action.to_reverse() {
-            std::mem::swap(&mut left, &mut right);
+    /// Calls the locator on the current node, without copying its value.
+    /// If at an empty position, returns [`None`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value has pending actions, and the locator looks at values
+    /// (see [`Locator::uses_values`]). In that case, use [`ImmDownBasicWalker::query_locator`].
+    pub fn query_locator_imm<L: Locator<D>>(&self, locator: &L) -> Option<locators::LocResult> {
+        let node = self.tree.node()?;
+        assert!(
+            self.can_locate_stored(locator),
+            "The locator looks at a value with pending actions, which requires cloning it"
+        );
+        Some(locator.locate(
+            &self.left_summary(),
+            &node.node_value,
+            &self.right_summary(),
+        ))
+    }
+}
+
+/// A reference to a value in a tree, together with the composition of the actions
+/// that are pending on it.
+///
+/// Queries that only have immutable access to a tree can't apply the pending actions
+/// to the stored values, so they return this instead. Values of trees that were never
+/// acted upon have no pending actions, and can be read directly using [`ValueRef::get`].
+pub struct ValueRef<'a, D: Data> {
+    value: &'a D::Value,
+    action: D::Action,
+}
+
+// the default clone implementation requires that D: Clone, which is uneccessary
+impl<'a, D: Data> Clone for ValueRef<'a, D> {
+    fn clone(&self) -> Self {
+        ValueRef {
+            value: self.value,
+            action: self.action.clone(),
         }
+    }
+}
 
-        let direction = locator.locate(
-            self.left_summary(),
-            &self.value().expect("suddenly empty error"),
-            self.right_summary(),
-        );
+impl<'a, D: Data> ValueRef<'a, D> {
+    pub(crate) fn new(value: &'a D::Value, action: D::Action) -> Self {
+        ValueRef { value, action }
+    }
 
-        Some(direction)
+    /// Returns the value as it is stored in the tree, without the pending actions.
+    pub fn stored(&self) -> &'a D::Value {
+        self.value
+    }
+
+    /// Returns the composition of the actions that are pending on the value.
+    pub fn action(&self) -> &D::Action {
+        &self.action
+    }
+
+    /// Returns the value, if it has no pending actions.
+    pub fn get(&self) -> Option<&'a D::Value> {
+        if self.action.is_identity() {
+            Some(self.value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the summary of the value, with the pending actions applied.
+    pub fn summary(&self) -> D::Summary {
+        self.action.act(self.value.to_summary())
+    }
+
+    /// Returns a copy of the value, with the pending actions applied.
+    pub fn to_value(&self) -> D::Value
+    where
+        D::Value: Clone,
+    {
+        self.action.act(self.value.clone())
     }
 }
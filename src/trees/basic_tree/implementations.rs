@@ -8,6 +8,20 @@ use recursive_reference::RecRef;
 
 const NO_VALUE_ERROR: &str = "invariant violated: RecRef can't be empty";
 
+impl<D: Data, T> AsBasicTree<D> for BasicTree<D, T> {
+    fn as_basic_tree(&self) -> &BasicTree<D, T> {
+        self
+    }
+}
+
+impl<D: Data, T> sealed::AsBasicTreeMut<D> for BasicTree<D, T> {
+    type AlgData = T;
+
+    fn as_basic_tree_mut(&mut self, _: sealed::Token) -> &mut BasicTree<D, T> {
+        self
+    }
+}
+
 impl<D: Data> SomeTree<D> for BasicTree<D> {
     fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
     where
@@ -119,7 +133,9 @@ impl<'a, D: Data, T> SomeWalker<D> for BasicWalker<'a, D, T> {
         let res = RecRef::extend_result(&mut self.rec_ref, |tree| {
             if let Some(node) = tree.node_mut() {
                 // update values
-                frame.right = node.node_summary() + node.right.subtree_summary() + frame.right;
+                frame.right = node.node_summary()
+                    + node.right.subtree_summary()
+                    + std::mem::take(&mut frame.right);
                 node.left.access();
                 Ok(&mut node.left)
             } else {
@@ -139,7 +155,9 @@ impl<'a, D: Data, T> SomeWalker<D> for BasicWalker<'a, D, T> {
         let res = RecRef::extend_result(&mut self.rec_ref, |tree| {
             if let Some(node) = tree.node_mut() {
                 // update values
-                frame.left = frame.left + node.left.subtree_summary() + node.node_summary();
+                frame.left = std::mem::take(&mut frame.left)
+                    + node.left.subtree_summary()
+                    + node.node_summary();
 
                 node.right.access();
                 Ok(&mut node.right)
@@ -172,10 +190,10 @@ impl<'a, D: Data, T> SomeWalker<D> for BasicWalker<'a, D, T> {
     }
 
     fn far_left_summary(&self) -> D::Summary {
-        self.vals.last().expect(NO_VALUE_ERROR).left
+        self.vals.last().expect(NO_VALUE_ERROR).left.clone()
     }
     fn far_right_summary(&self) -> D::Summary {
-        self.vals.last().expect(NO_VALUE_ERROR).right
+        self.vals.last().expect(NO_VALUE_ERROR).right.clone()
     }
 
     // fn inner(&self) -> &BasicTree<A> {
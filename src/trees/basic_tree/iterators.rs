@@ -1,12 +1,20 @@
 use crate::*;
 use basic_tree::*;
-use locators::LocResult;
+use locators::{FilterResult, LocResult, SubtreeFilter};
+use std::collections::VecDeque;
 
 enum Fragment<'a, D: Data, T = ()> {
     Value(&'a mut D::Value),
     Node(&'a mut BasicNode<D, T>),
 }
 
+/// A fragment, together with the summaries of everything to its left and to its right.
+type FragmentWithContext<'a, D, T> = (
+    Fragment<'a, D, T>,
+    <D as Data>::Summary,
+    <D as Data>::Summary,
+);
+
 /// Mutable iterator iterating over a segment of the tree. Since it is a mutable
 /// iterator, the tree will probably not be in a legal state if the values are modified.
 /// please use walkers instead.
@@ -17,9 +25,9 @@ enum Fragment<'a, D: Data, T = ()> {
 /// and could rebuild the nodes while iterating.
 ///
 /// There are also two technical problems:
-/// * Since the values could have changed, when we go back to rebuild the node,
-///   the locator might select a different subsegment, and therefore we might not rebuild some of the nodes
-///   we should have rebuilt.
+/// * We need to go back to rebuild the nodes that were accessed by the iterator. Since the values
+///   could have changed, the locator might select a different subsegment, and therefore we might
+///   not rebuild some of the nodes we should have rebuilt.
 /// * We need to convince the compiler that we can safely walk down the tree mutably a second time
 ///   after iteration finished. This can be sidestepped by a guard. Conveniently, the `Slice` struct can
 ///   be used as a guard, but this makes the implementation of `Slice` weird: after iterating mutably,
@@ -35,97 +43,228 @@ enum Fragment<'a, D: Data, T = ()> {
 ///
 /// Therefore, this type isn't exposed - it can't be used productively.
 /// Instead, this type is wrapped inside the `IterLocator` type, which is exported.
-struct IterLocatorMut<'a, D: Data, L, T = ()> {
-    left: D::Summary,
-    // a stack of the fragments, and for every fragment,
-    // the summary of everything to its right
-    stack: Vec<(Fragment<'a, D, T>, D::Summary)>,
-    locator: L,
+///
+/// The locator is only used when the iterator is created: the edges of the segment are found
+/// right away, so that the segment can be consumed from both ends.
+struct IterLocatorMut<'a, D: Data, T = ()> {
+    // the fragments of the segment that weren't returned yet, from left to right.
+    // every fragment is entirely inside the segment.
+    fragments: VecDeque<FragmentWithContext<'a, D, T>>,
 }
 
-impl<'a, D: Data, L, T> IterLocatorMut<'a, D, L, T> {
-    pub fn new(tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
+impl<'a, D: Data, T> IterLocatorMut<'a, D, T> {
+    pub fn new<L: Locator<D>>(mut tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
         let mut res = IterLocatorMut {
-            left: Default::default(),
-            stack: vec![],
-            locator,
+            fragments: VecDeque::new(),
+        };
+        let mut left: D::Summary = Default::default();
+        let mut right: D::Summary = Default::default();
+        // find the highest node in the segment
+        let node = loop {
+            let node = match tree.node_mut() {
+                None => return res,
+                Some(node) => node,
+            };
+            node.access();
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            match locator.locate(&near_left_summary, &node.node_value, &near_right_summary) {
+                LocResult::GoLeft => {
+                    right = value_summary + near_right_summary;
+                    tree = &mut node.left;
+                }
+                LocResult::GoRight => {
+                    left = near_left_summary + value_summary;
+                    tree = &mut node.right;
+                }
+                LocResult::Accept => break node,
+            }
         };
-        res.push(tree, Default::default());
+
+        let value_summary = node.node_value.to_summary();
+        let near_left_summary = left.clone() + node.left.subtree_summary();
+        let near_right_summary = node.right.subtree_summary() + right.clone();
+        res.fragments.push_back((
+            Fragment::Value(&mut node.node_value),
+            near_left_summary.clone(),
+            near_right_summary.clone(),
+        ));
+        res.push_left_edge(
+            &mut node.left,
+            &locator,
+            left,
+            value_summary.clone() + near_right_summary,
+        );
+        res.push_right_edge(
+            &mut node.right,
+            &locator,
+            near_left_summary + value_summary,
+            right,
+        );
         res
     }
 
-    /// Internal method: same as stack.push(...), but deals with the [`Empty`] case.
+    /// Internal method: pushes the fragments of the segment inside the given subtree,
+    /// assuming the segment continues to the right of the subtree.
+    fn push_left_edge<L: Locator<D>>(
+        &mut self,
+        mut tree: &'a mut BasicTree<D, T>,
+        locator: &L,
+        mut left: D::Summary,
+        mut right: D::Summary,
+    ) {
+        while let Some(node) = tree.node_mut() {
+            node.access();
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            match locator.locate(&near_left_summary, &node.node_value, &near_right_summary) {
+                LocResult::GoLeft => panic!("GoLeft received in the middle of a segment"),
+                LocResult::GoRight => {
+                    left = near_left_summary + value_summary;
+                    tree = &mut node.right;
+                }
+                LocResult::Accept => {
+                    self.push_front(
+                        &mut node.right,
+                        near_left_summary.clone() + value_summary.clone(),
+                        right,
+                    );
+                    self.fragments.push_front((
+                        Fragment::Value(&mut node.node_value),
+                        near_left_summary,
+                        near_right_summary.clone(),
+                    ));
+                    right = value_summary + near_right_summary;
+                    tree = &mut node.left;
+                }
+            }
+        }
+    }
+
+    /// Internal method: pushes the fragments of the segment inside the given subtree,
+    /// assuming the segment continues to the left of the subtree.
+    fn push_right_edge<L: Locator<D>>(
+        &mut self,
+        mut tree: &'a mut BasicTree<D, T>,
+        locator: &L,
+        mut left: D::Summary,
+        mut right: D::Summary,
+    ) {
+        while let Some(node) = tree.node_mut() {
+            node.access();
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            match locator.locate(&near_left_summary, &node.node_value, &near_right_summary) {
+                LocResult::GoRight => panic!("GoRight received in the middle of a segment"),
+                LocResult::GoLeft => {
+                    right = value_summary + near_right_summary;
+                    tree = &mut node.left;
+                }
+                LocResult::Accept => {
+                    self.push_back(
+                        &mut node.left,
+                        left,
+                        value_summary.clone() + near_right_summary.clone(),
+                    );
+                    self.fragments.push_back((
+                        Fragment::Value(&mut node.node_value),
+                        near_left_summary.clone(),
+                        near_right_summary,
+                    ));
+                    left = near_left_summary + value_summary;
+                    tree = &mut node.right;
+                }
+            }
+        }
+    }
+
+    /// Internal method: same as fragments.push_front(...), but deals with the [`Empty`] case.
+    /// If empty, do nothing.
+    fn push_front(&mut self, tree: &'a mut BasicTree<D, T>, left: D::Summary, right: D::Summary) {
+        if let Some(node) = tree.node_mut() {
+            self.fragments
+                .push_front((Fragment::Node(node), left, right));
+        }
+    }
+
+    /// Internal method: same as fragments.push_back(...), but deals with the [`Empty`] case.
     /// If empty, do nothing.
-    fn push(&mut self, tree: &'a mut BasicTree<D, T>, summary: D::Summary) {
+    fn push_back(&mut self, tree: &'a mut BasicTree<D, T>, left: D::Summary, right: D::Summary) {
         if let Some(node) = tree.node_mut() {
-            self.stack.push((Fragment::Node(node), summary));
+            self.fragments
+                .push_back((Fragment::Node(node), left, right));
         }
     }
 }
 
-impl<'a, D: Data, L: Locator<D>, T> Iterator for IterLocatorMut<'a, D, L, T> {
-    type Item = &'a mut D::Value;
+/// Yields every value together with the summaries of everything to its left and to its right.
+impl<'a, D: Data, T> Iterator for IterLocatorMut<'a, D, T> {
+    type Item = (D::Summary, &'a mut D::Value, D::Summary);
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         // Iterator is empty
-        if self.stack.is_empty() {
+        if self.fragments.is_empty() {
             (0, Some(0))
         } else {
-            // We know that every stack fragment contains at least one element.
+            // We know that every fragment contains at least one element.
             // We don't know any upper bound.
             // If we could specialize for `D: SizedData`, we could know the exact size,
             // but we can't.
-            (self.stack.len(), None)
+            (self.fragments.len(), None)
         }
     }
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let (frag, summary) = match self.stack.pop() {
-                None => return None,
-                Some(x) => x,
+            // the fragments are inside the segment, so the locator isn't needed anymore
+            let (node, left, right) = match self.fragments.pop_front()? {
+                (Fragment::Value(val), left, right) => return Some((left, val, right)),
+                (Fragment::Node(node), left, right) => (node, left, right),
             };
+            node.access();
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            self.push_front(
+                &mut node.right,
+                near_left_summary.clone() + value_summary.clone(),
+                right,
+            );
+            self.fragments.push_front((
+                Fragment::Value(&mut node.node_value),
+                near_left_summary,
+                near_right_summary.clone(),
+            ));
+            self.push_front(&mut node.left, left, value_summary + near_right_summary);
+        }
+    }
+}
 
-            let node = match frag {
-                // if value has been inserted to the stack, the locator has already been called
-                // on it and returned `Accept`.
-                Fragment::Value(val) => {
-                    self.left = self.left + (*val).to_summary();
-                    return Some(val);
-                }
-                Fragment::Node(node) => node,
+impl<'a, D: Data, T> DoubleEndedIterator for IterLocatorMut<'a, D, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, left, right) = match self.fragments.pop_back()? {
+                (Fragment::Value(val), left, right) => return Some((left, val, right)),
+                (Fragment::Node(node), left, right) => (node, left, right),
             };
-
             node.access();
-            let value = &mut node.node_value;
-            let right_node = &mut node.right;
-            let left_node = &mut node.left;
-
-            let value_summary = (*value).to_summary();
-            let near_left_summary: D::Summary = self.left + left_node.subtree_summary();
-            let near_right_summary: D::Summary = right_node.subtree_summary() + summary;
-
-            let dir = self
-                .locator
-                .locate(near_left_summary, value, near_right_summary);
-            match dir {
-                LocResult::GoLeft => {
-                    if !self.stack.is_empty() {
-                        panic!("GoLeft received in the middle of a segment");
-                    }
-                    self.push(left_node, value_summary + near_right_summary);
-                }
-                LocResult::GoRight => {
-                    self.push(right_node, summary);
-                    self.left = near_left_summary + value_summary;
-                }
-                LocResult::Accept => {
-                    self.push(right_node, summary);
-                    self.stack
-                        .push((Fragment::Value(value), near_right_summary));
-                    self.push(left_node, value_summary + near_right_summary);
-                }
-            }
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            self.push_back(
+                &mut node.left,
+                left,
+                value_summary.clone() + near_right_summary.clone(),
+            );
+            self.fragments.push_back((
+                Fragment::Value(&mut node.node_value),
+                near_left_summary.clone(),
+                near_right_summary,
+            ));
+            self.push_back(&mut node.right, near_left_summary + value_summary, right);
         }
     }
 }
@@ -140,8 +279,30 @@ impl<'a, D: Data, L: Locator<D>, T> Iterator for IterLocatorMut<'a, D, L, T> {
 ///
 /// If you use interior mutability to update the values inside the tree, and these changes affect the summaries,
 /// the tree may behave incorrectly.
+///
+/// For an iterator that only needs a `&self` argument, see [`IterImm`].
+///
+/// The iterator is double-ended, so the segment can be consumed from both ends, or in reverse
+/// using [`Iterator::rev`]. Pending reversals are applied while iterating, so the values are always
+/// returned in their order in the tree.
+/// For an [`ExactSizeIterator`], see [`SomeTree::iter_locator_sized`].
+///```
+/// use grove::*;
+/// use grove::treap::Treap;
+/// use grove::example_data::{RevAffineAction, StdNum};
+///
+/// let mut tree: Treap<StdNum> = (0..10).collect();
+/// tree.act_segment(RevAffineAction { to_reverse: true, mul: 1, add: 0 }, 2..6);
+///
+/// let mut iter = tree.iter_locator(1..8);
+/// assert_eq!(iter.next_back(), Some(&7));
+/// assert_eq!(iter.next(), Some(&1));
+/// assert_eq!(iter.rev().cloned().collect::<Vec<_>>(), vec![6, 2, 3, 4, 5]);
+/// # tree.assert_correctness();
+///```
 pub struct IterLocator<'a, D: Data, L, T = ()> {
-    mut_iter: IterLocatorMut<'a, D, L, T>,
+    mut_iter: IterLocatorMut<'a, D, T>,
+    phantom: std::marker::PhantomData<L>,
 }
 
 impl<'a, D: Data, L: Locator<D>, T> IterLocator<'a, D, L, T> {
@@ -149,6 +310,16 @@ impl<'a, D: Data, L: Locator<D>, T> IterLocator<'a, D, L, T> {
     pub fn new(tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
         IterLocator {
             mut_iter: IterLocatorMut::new(tree, locator),
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the rest of the values, that also returns
+    /// the summaries to the left and to the right of every value. See [`IterWithContext`].
+    pub fn with_context(self) -> IterWithContext<'a, D, L, T> {
+        IterWithContext {
+            mut_iter: self.mut_iter,
+            phantom: std::marker::PhantomData,
         }
     }
 }
@@ -162,7 +333,221 @@ impl<'a, D: Data, L: Locator<D>, T> Iterator for IterLocator<'a, D, L, T> {
 
     /// Creates a new immutable iterator for a segment of the given tree.
     fn next(&mut self) -> Option<Self::Item> {
-        Some(&*self.mut_iter.next()?)
+        let (_, value, _) = self.mut_iter.next()?;
+        Some(&*value)
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> DoubleEndedIterator for IterLocator<'a, D, L, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (_, value, _) = self.mut_iter.next_back()?;
+        Some(&*value)
+    }
+}
+
+/// Immutable iterator over a segment of the tree, that returns every value together with
+/// the summaries of all of the values to its left and to its right, in the whole tree.
+/// This is the same as calling [`SomeTree::segment_summary`] for the prefix and the suffix
+/// of every value, but the summaries are kept while walking the tree, so every step only takes
+/// `O(1)` amortized time and summary additions.
+///
+/// Returned by [`SomeTree::iter_with_context`] and [`IterLocator::with_context`].
+/// Like [`IterLocator`], it is double-ended.
+pub struct IterWithContext<'a, D: Data, L, T = ()> {
+    mut_iter: IterLocatorMut<'a, D, T>,
+    phantom: std::marker::PhantomData<L>,
+}
+
+impl<'a, D: Data, L: Locator<D>, T> IterWithContext<'a, D, L, T> {
+    /// Creates a new iterator for a segment of the given tree.
+    pub fn new(tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
+        IterLocator::new(tree, locator).with_context()
+    }
+
+    /// Returns an iterator over the rest of the values, that returns the indices
+    /// of the values instead of the summaries. See [`IterIndexed`].
+    pub fn indexed(self) -> IterIndexed<'a, D, L, T>
+    where
+        D::Summary: SizedSummary,
+    {
+        IterIndexed { iter: self }
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> Iterator for IterWithContext<'a, D, L, T> {
+    type Item = (D::Summary, &'a D::Value, D::Summary);
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.mut_iter.size_hint()
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (left, value, right) = self.mut_iter.next()?;
+        Some((left, &*value, right))
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> DoubleEndedIterator for IterWithContext<'a, D, L, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (left, value, right) = self.mut_iter.next_back()?;
+        Some((left, &*value, right))
+    }
+}
+
+/// Immutable iterator over a segment of the tree, that returns every value together with its
+/// index in the whole tree. The indices are the sizes of the summaries to the left of the values,
+/// as computed by [`IterWithContext`].
+///
+/// Returned by [`SomeTree::iter_indexed`]. Like [`IterLocator`], it is double-ended.
+pub struct IterIndexed<'a, D: Data, L, T = ()> {
+    iter: IterWithContext<'a, D, L, T>,
+}
+
+impl<'a, D: Data, L: Locator<D>, T> IterIndexed<'a, D, L, T>
+where
+    D::Summary: SizedSummary,
+{
+    /// Creates a new iterator for a segment of the given tree.
+    pub fn new(tree: &'a mut BasicTree<D, T>, locator: L) -> Self {
+        IterIndexed {
+            iter: IterWithContext::new(tree, locator),
+        }
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> Iterator for IterIndexed<'a, D, L, T>
+where
+    D::Summary: SizedSummary,
+{
+    type Item = (usize, &'a D::Value);
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (left, value, _) = self.iter.next()?;
+        Some((left.size(), value))
+    }
+}
+
+impl<'a, D: Data, L: Locator<D>, T> DoubleEndedIterator for IterIndexed<'a, D, L, T>
+where
+    D::Summary: SizedSummary,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (left, value, _) = self.iter.next_back()?;
+        Some((left.size(), value))
+    }
+}
+
+/// Immutable iterator over the values of the tree that match a [`SubtreeFilter`], in order.
+/// Subtrees that the filter skips aren't visited at all, and subtrees that it takes entirely
+/// aren't queried again. See [`SubtreeFilter`].
+///
+/// Returned by [`SomeTree::iter_matching`]. Like [`IterLocator`], it requires mutable access
+/// to the tree in order to apply the pending actions.
+pub struct IterMatching<'a, D: Data, F, T = ()> {
+    filter: F,
+    // the fragments that weren't visited yet, where the next one is at the top of the stack,
+    // together with whether all of their values match.
+    stack: Vec<(Fragment<'a, D, T>, bool)>,
+}
+
+impl<'a, D: Data, F: SubtreeFilter<D>, T> IterMatching<'a, D, F, T> {
+    /// Creates a new iterator over the values of the given tree that match the filter.
+    pub fn new(tree: &'a mut BasicTree<D, T>, filter: F) -> Self {
+        let mut res = IterMatching {
+            filter,
+            stack: vec![],
+        };
+        if let Some(node) = tree.node_mut() {
+            res.stack.push((Fragment::Node(node), false));
+        }
+        res
+    }
+}
+
+impl<'a, D: Data, F: SubtreeFilter<D>, T> Iterator for IterMatching<'a, D, F, T> {
+    type Item = &'a D::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, taken) = match self.stack.pop()? {
+                (Fragment::Value(value), _) => return Some(&*value),
+                (Fragment::Node(node), taken) => (node, taken),
+            };
+            let taken = taken
+                || match self.filter.filter(&node.subtree_summary()) {
+                    FilterResult::Skip => continue,
+                    FilterResult::TakeAll => true,
+                    FilterResult::Descend => false,
+                };
+            node.access();
+            let BasicNode {
+                left,
+                node_value,
+                right,
+                ..
+            } = node;
+            if let Some(right) = right.node_mut() {
+                self.stack.push((Fragment::Node(right), taken));
+            }
+            if taken || self.filter.matches(node_value) {
+                self.stack.push((Fragment::Value(node_value), taken));
+            }
+            if let Some(left) = left.node_mut() {
+                self.stack.push((Fragment::Node(left), taken));
+            }
+        }
+    }
+}
+
+/// Iterator over all of the values of a tree, that only requires immutable access to it.
+/// Since it can't apply the pending actions to the values, it returns [`ValueRef`]s,
+/// which hold the values together with their pending actions.
+pub struct IterImm<'a, D: Data, T = ()> {
+    // the nodes whose values are yet to be returned, along with all the actions
+    // that apply to them, including their own.
+    stack: Vec<(&'a BasicNode<D, T>, D::Action)>,
+}
+
+impl<'a, D: Data, T> IterImm<'a, D, T> {
+    /// Creates a new iterator over all of the values of the given tree.
+    pub fn new(tree: &'a BasicTree<D, T>) -> Self {
+        let mut res = IterImm { stack: vec![] };
+        res.push_left_spine(tree, Default::default());
+        res
+    }
+
+    fn push_left_spine(&mut self, mut tree: &'a BasicTree<D, T>, mut action: D::Action) {
+        while let Some(node) = tree.node() {
+            // most nodes have no pending action, so composing it can be skipped
+            if !node.action().is_identity() {
+                action = action + node.action().clone();
+            }
+            tree = if action.to_reverse() {
+                &node.right
+            } else {
+                &node.left
+            };
+            self.stack.push((node, action.clone()));
+        }
+    }
+}
+
+impl<'a, D: Data, T> Iterator for IterImm<'a, D, T> {
+    type Item = ValueRef<'a, D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, action) = self.stack.pop()?;
+        let right = if action.to_reverse() {
+            &node.left
+        } else {
+            &node.right
+        };
+        self.push_left_spine(right, action.clone());
+        Some(ValueRef::new(&node.node_value, action))
     }
 }
 
@@ -172,31 +557,142 @@ enum OFragment<D: Data, T = ()> {
     Node(Box<BasicNode<D, T>>),
 }
 /// Owning iterator iterating over a segment of the tree.
+///
+/// Like [`IterLocator`], the iterator is double-ended.
+/// For an [`ExactSizeIterator`], see [`SomeTree::into_iter_sized`].
 pub struct IntoIter<D: Data, L, T = ()> {
-    left: D::Summary,
-    // a stack of the fragments, and for every fragment,
-    // the summary of everything to its right
-    stack: Vec<(OFragment<D, T>, D::Summary)>,
-    locator: L,
+    // the fragments of the segment that weren't returned yet, from left to right.
+    // every fragment is entirely inside the segment.
+    fragments: VecDeque<OFragment<D, T>>,
+    phantom: std::marker::PhantomData<L>,
 }
 
-impl<D: Data, L, T> IntoIter<D, L, T> {
+impl<D: Data, L: Locator<D>, T> IntoIter<D, L, T> {
     /// Creates a new owning iterator for a segment of the given tree.
-    pub fn new(tree: BasicTree<D, T>, locator: L) -> Self {
+    pub fn new(mut tree: BasicTree<D, T>, locator: L) -> Self {
         let mut res = IntoIter {
-            left: Default::default(),
-            stack: vec![],
-            locator,
+            fragments: VecDeque::new(),
+            phantom: std::marker::PhantomData,
         };
-        res.push(tree, Default::default());
+        let mut left: D::Summary = Default::default();
+        let mut right: D::Summary = Default::default();
+        // find the highest node in the segment
+        let node = loop {
+            let mut node = match tree.into_node_boxed() {
+                None => return res,
+                Some(node) => node,
+            };
+            node.access();
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            match locator.locate(&near_left_summary, &node.node_value, &near_right_summary) {
+                LocResult::GoLeft => {
+                    right = value_summary + near_right_summary;
+                    tree = node.left;
+                }
+                LocResult::GoRight => {
+                    left = near_left_summary + value_summary;
+                    tree = node.right;
+                }
+                LocResult::Accept => break node,
+            }
+        };
+
+        let value_summary = node.node_value.to_summary();
+        let left_summary = node.left.subtree_summary();
+        let right_summary = node.right.subtree_summary();
+        res.fragments.push_back(OFragment::Value(node.node_value));
+        res.push_left_edge(
+            node.left,
+            &locator,
+            left.clone(),
+            value_summary.clone() + right_summary + right.clone(),
+        );
+        res.push_right_edge(
+            node.right,
+            &locator,
+            left + left_summary + value_summary,
+            right,
+        );
         res
     }
 
-    /// Internal method: same as stack.push(...), but deals with the [`Empty`] case.
+    /// Internal method: pushes the fragments of the segment inside the given subtree,
+    /// assuming the segment continues to the right of the subtree.
+    fn push_left_edge(
+        &mut self,
+        mut tree: BasicTree<D, T>,
+        locator: &L,
+        mut left: D::Summary,
+        mut right: D::Summary,
+    ) {
+        while let Some(mut node) = tree.into_node_boxed() {
+            node.access();
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            match locator.locate(&near_left_summary, &node.node_value, &near_right_summary) {
+                LocResult::GoLeft => panic!("GoLeft received in the middle of a segment"),
+                LocResult::GoRight => {
+                    left = near_left_summary + value_summary;
+                    tree = node.right;
+                }
+                LocResult::Accept => {
+                    self.push_front(node.right);
+                    self.fragments.push_front(OFragment::Value(node.node_value));
+                    right = value_summary + near_right_summary;
+                    tree = node.left;
+                }
+            }
+        }
+    }
+
+    /// Internal method: pushes the fragments of the segment inside the given subtree,
+    /// assuming the segment continues to the left of the subtree.
+    fn push_right_edge(
+        &mut self,
+        mut tree: BasicTree<D, T>,
+        locator: &L,
+        mut left: D::Summary,
+        mut right: D::Summary,
+    ) {
+        while let Some(mut node) = tree.into_node_boxed() {
+            node.access();
+            let value_summary = node.node_value.to_summary();
+            let near_left_summary = left.clone() + node.left.subtree_summary();
+            let near_right_summary = node.right.subtree_summary() + right.clone();
+            match locator.locate(&near_left_summary, &node.node_value, &near_right_summary) {
+                LocResult::GoRight => panic!("GoRight received in the middle of a segment"),
+                LocResult::GoLeft => {
+                    right = value_summary + near_right_summary;
+                    tree = node.left;
+                }
+                LocResult::Accept => {
+                    self.push_back(node.left);
+                    self.fragments.push_back(OFragment::Value(node.node_value));
+                    left = near_left_summary + value_summary;
+                    tree = node.right;
+                }
+            }
+        }
+    }
+}
+
+impl<D: Data, L, T> IntoIter<D, L, T> {
+    /// Internal method: same as fragments.push_front(...), but deals with the [`Empty`] case.
     /// If empty, do nothing.
-    fn push(&mut self, tree: BasicTree<D, T>, summary: D::Summary) {
+    fn push_front(&mut self, tree: BasicTree<D, T>) {
         if let Some(boxed_node) = tree.into_node_boxed() {
-            self.stack.push((OFragment::Node(boxed_node), summary));
+            self.fragments.push_front(OFragment::Node(boxed_node));
+        }
+    }
+
+    /// Internal method: same as fragments.push_back(...), but deals with the [`Empty`] case.
+    /// If empty, do nothing.
+    fn push_back(&mut self, tree: BasicTree<D, T>) {
+        if let Some(boxed_node) = tree.into_node_boxed() {
+            self.fragments.push_back(OFragment::Node(boxed_node));
         }
     }
 }
@@ -206,64 +702,104 @@ impl<D: Data, L: Locator<D>, T> Iterator for IntoIter<D, L, T> {
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         // Iterator is empty
-        if self.stack.is_empty() {
+        if self.fragments.is_empty() {
             (0, Some(0))
         } else {
-            // We know that every stack fragment contains at least one element.
+            // We know that every fragment contains at least one element.
             // We don't know any upper bound.
             // If we could specialize for `D: SizedData`, we could know the exact size,
             // but we can't.
-            (self.stack.len(), None)
+            (self.fragments.len(), None)
         }
     }
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let (frag, summary) = match self.stack.pop() {
-                None => return None,
-                Some(x) => x,
+            // the fragments are inside the segment, so the locator isn't needed anymore
+            let mut node = match self.fragments.pop_front()? {
+                OFragment::Value(val) => return Some(val),
+                OFragment::Node(node) => node,
             };
+            node.access();
+            self.push_front(node.right);
+            self.fragments.push_front(OFragment::Value(node.node_value));
+            self.push_front(node.left);
+        }
+    }
+}
 
-            let mut node = match frag {
-                // if value has been inserted to the stack, the locator has already been called
-                // on it and returned `Accept`.
-                OFragment::Value(val) => {
-                    self.left = self.left + val.to_summary();
-                    return Some(val);
-                }
+impl<D: Data, L: Locator<D>, T> DoubleEndedIterator for IntoIter<D, L, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut node = match self.fragments.pop_back()? {
+                OFragment::Value(val) => return Some(val),
                 OFragment::Node(node) => node,
             };
-
             node.access();
-            let value = node.node_value;
-            let right_node = node.right;
-            let left_node = node.left;
-
-            let value_summary = value.to_summary();
-            let near_left_summary: D::Summary = self.left + left_node.subtree_summary();
-            let near_right_summary: D::Summary = right_node.subtree_summary() + summary;
-
-            let dir = self
-                .locator
-                .locate(near_left_summary, &value, near_right_summary);
-            match dir {
-                LocResult::GoLeft => {
-                    if !self.stack.is_empty() {
-                        panic!("GoLeft received in the middle of a segment");
-                    }
-                    self.push(left_node, value_summary + near_right_summary);
-                }
-                LocResult::GoRight => {
-                    self.push(right_node, summary);
-                    self.left = near_left_summary + value_summary;
-                }
-                LocResult::Accept => {
-                    self.push(right_node, summary);
-                    self.stack
-                        .push((OFragment::Value(value), near_right_summary));
-                    self.push(left_node, value_summary + near_right_summary);
-                }
+            self.push_back(node.left);
+            self.fragments.push_back(OFragment::Value(node.node_value));
+            self.push_back(node.right);
+        }
+    }
+}
+
+/// An iterator over a segment, together with the number of values it has left to return.
+/// Returned by [`SomeTree::iter_locator_sized`] and [`SomeTree::into_iter_sized`].
+///
+/// The segment iterators only know a lower bound on their length, since without
+/// specialization they can't use [`SizedSummary`]. This wrapper counts the values in the segment
+/// when it is built, using the size of the segment's summary, so it implements
+/// [`ExactSizeIterator`] with an exact [`Iterator::size_hint`].
+/// The count is only correct if every value has size `1`.
+pub struct ExactSize<I> {
+    iter: I,
+    len: usize,
+}
+
+impl<I> ExactSize<I> {
+    /// Wraps the iterator, which must return exactly `len` more values.
+    pub fn new(iter: I, len: usize) -> Self {
+        ExactSize { iter, len }
+    }
+
+    /// Returns the wrapped iterator.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+
+    // updates the count after a value was returned
+    fn count<V>(&mut self, res: Option<V>) -> Option<V> {
+        match res {
+            Some(_) => {
+                self.len = self
+                    .len
+                    .checked_sub(1)
+                    .expect("The segment has more values than its summary's size");
             }
+            None => self.len = 0,
         }
+        res
     }
 }
+
+impl<I: Iterator> Iterator for ExactSize<I> {
+    type Item = I::Item;
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let res = self.iter.next();
+        self.count(res)
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for ExactSize<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let res = self.iter.next_back();
+        self.count(res)
+    }
+}
+
+impl<I: Iterator> ExactSizeIterator for ExactSize<I> {}
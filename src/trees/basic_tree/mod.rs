@@ -13,7 +13,7 @@
 // for some of the functions of this module
 
 mod imm_down_walker;
-pub(crate) use imm_down_walker::ImmDownBasicWalker;
+pub use imm_down_walker::{ImmDownBasicWalker, ValueRef};
 
 mod walker;
 pub use walker::*;
@@ -51,7 +51,7 @@ impl<D: Data, T> BasicTree<D, T> {
     /// Returns `default()` if the tree is empty, and `self.node().action` otherwise
     pub fn action(&self) -> D::Action {
         match self.node() {
-            Some(node) => node.action,
+            Some(node) => node.action.clone(),
             None => Default::default(),
         }
     }
@@ -136,6 +136,66 @@ impl<D: Data, T> BasicTree<D, T> {
         }
     }
 
+    /// Finds any node that the locator `Accept`s, like [`SomeTreeRef::search`], but only
+    /// requires immutable access to the tree.
+    /// If there isn't any, it finds the empty location where that node would be instead.
+    /// Returns an immutable walker at the wanted position.
+    ///
+    /// # Panics
+    ///
+    /// Since the pending actions can't be applied to the values, this panics if the locator
+    /// is called on a value with pending actions, and the locator looks at values
+    /// (see [`Locator::uses_values`]). Index locators never panic.
+    pub fn search_imm<L: Locator<D>>(&self, locator: L) -> ImmDownBasicWalker<'_, D, T> {
+        use locators::LocResult::*;
+
+        let mut walker = ImmDownBasicWalker::new(self);
+        while let Some(direction) = walker.query_locator_imm(&locator) {
+            match direction {
+                GoLeft => walker.go_left().unwrap(),
+                GoRight => walker.go_right().unwrap(),
+                Accept => break,
+            }
+        }
+        walker
+    }
+
+    /// Returns any value that the locator `Accept`s, together with its pending actions.
+    /// Only requires immutable access to the tree. Panics in the same cases as
+    /// [`BasicTree::search_imm`].
+    ///```
+    /// use grove::{*, basic_tree::*};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: BasicTree<StdNum> = (1..=8).collect();
+    /// assert_eq!(tree.get(3).unwrap().get(), Some(&4));
+    ///
+    /// tree.act_segment(RevAffineAction {to_reverse: false, mul: 1, add: 10}, 2..6);
+    /// let value = tree.get(3).unwrap();
+    /// assert_eq!(value.to_value(), 14);
+    /// assert_eq!(value.summary().sum, 14);
+    /// # tree.assert_correctness();
+    ///```
+    pub fn get<L: Locator<D>>(&self, locator: L) -> Option<ValueRef<'_, D>> {
+        self.search_imm(locator).value_ref()
+    }
+
+    /// Iterates over all of the values, with only immutable access to the tree.
+    /// The values are returned together with their pending actions.
+    ///```
+    /// use grove::{*, basic_tree::*};
+    /// use grove::example_data::{StdNum, RevAffineAction};
+    ///
+    /// let mut tree: BasicTree<StdNum> = (1..=8).collect();
+    /// tree.act_segment(RevAffineAction {to_reverse: false, mul: -1, add: 0}, 4..);
+    /// let values: Vec<i32> = tree.iter_imm().map(|value| value.to_value()).collect();
+    /// assert_eq!(values, vec![1, 2, 3, 4, -5, -6, -7, -8]);
+    /// # tree.assert_correctness();
+    ///```
+    pub fn iter_imm(&self) -> iterators::IterImm<'_, D, T> {
+        iterators::IterImm::new(self)
+    }
+
     /// Checks that invariants remain correct. i.e., that every node's summary
     /// is the sum of the summaries of its children.
     /// If it is not, panics.
@@ -205,7 +265,7 @@ impl<D: Data, T> BasicNode<D, T> {
     /// Returns the summary of all values in this node's subtree.
     /// Same as [`BasicTree::subtree_summary`].
     pub fn subtree_summary(&self) -> D::Summary {
-        self.action.act(self.subtree_summary)
+        self.action.act(self.subtree_summary.clone())
     }
 
     /// Returns a summary for the value in this node specifically,
@@ -248,11 +308,11 @@ impl<D: Data, T> BasicNode<D, T> {
             std::mem::swap(&mut self.left, &mut self.right);
         }
 
-        self.left.act_subtree(self.action);
-        self.right.act_subtree(self.action);
-        self.action.act_inplace(&mut self.subtree_summary);
-        self.action.act_inplace(&mut self.node_value);
-        self.action = Default::default();
+        let action = std::mem::take(&mut self.action);
+        action.act_inplace(&mut self.subtree_summary);
+        action.act_inplace(&mut self.node_value);
+        self.left.act_subtree(action.clone());
+        self.right.act_subtree(action);
     }
 
     /// Remakes the data that is stored in this node, based on its sons.
@@ -283,8 +343,22 @@ impl<D: Data, T> BasicNode<D, T> {
     /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (-3..=4).rev().collect::<Vec<_>>());
     /// # tree.assert_correctness();
     ///```
+    ///
+    /// If the action can't act on the subtree's summary (see [`Acts::can_act`]),
+    /// it is applied on the node's value and on its sons separately instead.
     pub fn act(&mut self, action: D::Action) {
-        self.action = action + self.action;
+        if action.can_act(&self.subtree_summary()) {
+            self.action = action + std::mem::take(&mut self.action);
+        } else {
+            self.access();
+            if action.to_reverse() {
+                std::mem::swap(&mut self.left, &mut self.right);
+            }
+            action.act_inplace(&mut self.node_value);
+            self.left.act_subtree(action.clone());
+            self.right.act_subtree(action);
+            self.rebuild();
+        }
     }
 
     /// This function applies the given action only to the current value in this node.
@@ -324,7 +398,7 @@ impl<D: Data, T> BasicNode<D, T> {
     where
         D::Summary: Eq,
     {
-        let ns = self.subtree_summary;
+        let ns = self.subtree_summary.clone();
         let os: D::Summary = self.left.subtree_summary()
             + self.node_value.to_summary()
             + self.right.subtree_summary();
@@ -198,7 +198,7 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
         bn2.access();
 
         bn1.right = bn2.left;
-        bn2.subtree_summary = bn1.subtree_summary; // this is insetad of bn2.rebuild(), since we already know the result
+        bn2.subtree_summary = std::mem::take(&mut bn1.subtree_summary); // this is insetad of bn2.rebuild(), since we already know the result
         bn1.rebuild();
         rebuilder(&mut *bn1);
         bn2.left = BasicTree::from_boxed_node(bn1);
@@ -232,7 +232,7 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
         bn2.access();
 
         bn1.left = bn2.right;
-        bn2.subtree_summary = bn1.subtree_summary; // this is insetad of bn2.rebuild(), since we already know the result
+        bn2.subtree_summary = std::mem::take(&mut bn1.subtree_summary); // this is insetad of bn2.rebuild(), since we already know the result
         bn1.rebuild();
         rebuilder(&mut *bn1);
         bn2.right = BasicTree::from_boxed_node(bn1);
@@ -331,9 +331,13 @@ impl<'a, D: Data, T> BasicWalker<'a, D, T> {
 
     /// If the current position is empty, puts the given value there instead.
     /// Intended to help writing tree algorithms.
+    ///
+    /// The new subtree is accessed, in order to keep the invariant that the
+    /// current node is always accessed (the subtree might be a son with a pending action).
     pub(in super::super) fn put_subtree(&mut self, new: BasicTree<D, T>) -> Option<()> {
         if self.rec_ref.is_empty() {
             *self.rec_ref = new;
+            self.rec_ref.access();
             Some(())
         } else {
             None
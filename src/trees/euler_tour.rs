@@ -0,0 +1,425 @@
+//! An implementation of Euler-tour trees.
+//!
+//! An [`EulerTourForest`] maintains a forest of rooted trees, whose vertices hold values.
+//! Edges can be added and removed, and the values of a whole tree, or of a subtree,
+//! can be summarized or acted upon, all in `O(log n)` expected time.
+//! It is the complement of the [`link_cut`] module, which
+//! handles path queries instead of subtree queries.
+//!
+//! Every tree is stored as its Euler tour: the sequence of edges walked by a depth-first
+//! search from the root, where every edge appears twice, once going down and once going up.
+//! In addition, every vertex appears once, at some point in which the walk visits it.
+//! The tour always starts with the root. The subtree of a vertex is then the segment between
+//! the two appearances of the edge to its parent.
+//!
+//! The tours are stored in treaps whose nodes are kept in an [`Arena`],
+//! so that a vertex can find its position in its tour using the parent links.
+//! Linking, cutting and rerooting are done by splitting and concatenating the tours.
+//!
+//!```
+//! use grove::euler_tour::EulerTourForest;
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let mut forest: EulerTourForest<StdNum> = EulerTourForest::new();
+//! let v: Vec<_> = (0..5).map(|i| forest.add_vertex(i)).collect();
+//! forest.link(v[1], v[0]).unwrap();
+//! forest.link(v[2], v[1]).unwrap();
+//! forest.link(v[3], v[1]).unwrap();
+//! assert!(forest.connected(v[2], v[3]));
+//! assert!(!forest.connected(v[2], v[4]));
+//! assert_eq!(forest.root(v[3]), v[0]);
+//!
+//! assert_eq!(forest.component_summary(v[2]).sum, 0 + 1 + 2 + 3);
+//! assert_eq!(forest.subtree_summary(v[1], v[0]).unwrap().sum, 1 + 2 + 3);
+//! forest.subtree_act(v[1], v[0], RevAffineAction {to_reverse: false, mul: 1, add: 10}).unwrap();
+//! assert_eq!(forest.component_summary(v[0]).sum, 0 + 11 + 12 + 13);
+//!
+//! forest.reroot(v[2]);
+//! assert_eq!(forest.subtree_summary(v[0], v[1]).unwrap().sum, 0);
+//! forest.cut(v[1], v[2]).unwrap();
+//! assert_eq!(forest.root(v[3]), v[1]);
+//! assert_eq!(forest.component_summary(v[2]).sum, 12);
+//! # forest.assert_correctness();
+//!```
+
+use super::arena::{treap::*, *};
+use crate::treap::{PrioritySource, RngPriorities, ThreadRngPriorities};
+use crate::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use std::collections::HashMap;
+use std::ops::Add;
+
+/// A vertex in an [`EulerTourForest`].
+/// Vertices stay valid as long as the forest exists.
+pub type Vertex = NodeIdx;
+
+/// An entry of an Euler tour.
+enum TourEntry<V> {
+    Vertex(V),
+    Edge,
+}
+
+/// The summaries of Euler tours. Edges have empty summaries.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+struct TourSummary<S>(S);
+
+impl<S: Add<Output = S>> Add for TourSummary<S> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        TourSummary(self.0 + other.0)
+    }
+}
+
+/// The actions on Euler tours. Edges aren't affected.
+#[derive(Clone, Copy, Default)]
+struct TourAction<A>(A);
+
+impl<A: Action> Add for TourAction<A> {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        TourAction(self.0 + other.0)
+    }
+}
+
+impl<A: Action> Action for TourAction<A> {
+    fn is_identity(&self) -> bool {
+        self.0.is_identity()
+    }
+
+    fn to_reverse(&self) -> bool {
+        self.0.to_reverse()
+    }
+}
+
+impl<V, A: Acts<V>> Acts<TourEntry<V>> for TourAction<A> {
+    fn act_inplace(&self, entry: &mut TourEntry<V>) {
+        if let TourEntry::Vertex(value) = entry {
+            self.0.act_inplace(value);
+        }
+    }
+}
+
+impl<S, A: Acts<S>> Acts<TourSummary<S>> for TourAction<A> {
+    fn act_inplace(&self, summary: &mut TourSummary<S>) {
+        self.0.act_inplace(&mut summary.0);
+    }
+
+    fn can_act(&self, summary: &TourSummary<S>) -> bool {
+        self.0.can_act(&summary.0)
+    }
+}
+
+impl<S: Default, V: ToSummary<S>> ToSummary<TourSummary<S>> for TourEntry<V> {
+    fn to_summary(&self) -> TourSummary<S> {
+        match self {
+            TourEntry::Vertex(value) => TourSummary(value.to_summary()),
+            TourEntry::Edge => TourSummary(Default::default()),
+        }
+    }
+}
+
+type TourData<D> = (
+    TourEntry<<D as Data>::Value>,
+    TourSummary<<D as Data>::Summary>,
+    TourAction<<D as Data>::Action>,
+);
+
+/// A forest of rooted trees, that supports linking and cutting trees,
+/// and summarizing and acting on whole trees and subtrees.
+/// The `P` parameter is the source of the priorities of the treap nodes.
+/// See the module documentation.
+pub struct EulerTourForest<D: Data, P = ThreadRngPriorities> {
+    arena: Arena<TourData<D>, u64>,
+    // the two entries of every edge, in both orders of its endpoints
+    edges: HashMap<(Vertex, Vertex), (NodeIdx, NodeIdx)>,
+    priorities: P,
+}
+
+impl<D: Data> EulerTourForest<D> {
+    /// Creates an empty forest, that draws its priorities from [`rand::thread_rng`].
+    pub fn new() -> Self {
+        EulerTourForest::with_priority_source(ThreadRngPriorities)
+    }
+}
+
+impl<D: Data> EulerTourForest<D, RngPriorities<StdRng>> {
+    /// Creates an empty forest, that draws its priorities from a random number generator
+    /// seeded by `seed`. See [`Treap::with_seed`](crate::treap::Treap::with_seed).
+    pub fn with_seed(seed: u64) -> Self {
+        EulerTourForest::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<D: Data, R: RngCore + SeedableRng> EulerTourForest<D, RngPriorities<R>> {
+    /// Creates an empty forest, that draws its priorities from the given random number generator.
+    pub fn with_rng(rng: R) -> Self {
+        EulerTourForest::with_priority_source(RngPriorities(rng))
+    }
+}
+
+impl<D: Data, P: PrioritySource> Default for EulerTourForest<D, P> {
+    fn default() -> Self {
+        EulerTourForest::with_priority_source(P::default())
+    }
+}
+
+impl<D: Data, P> EulerTourForest<D, P> {
+    /// Creates an empty forest, that draws its priorities from the given source.
+    pub fn with_priority_source(priorities: P) -> Self {
+        EulerTourForest {
+            arena: Arena::new(),
+            edges: HashMap::new(),
+            priorities,
+        }
+    }
+
+    /// Returns the number of vertices in the forest.
+    pub fn len(&self) -> usize {
+        // every edge has two entries, and two keys in `self.edges`
+        self.arena.len() - self.edges.len()
+    }
+
+    /// Returns true if there are no vertices in the forest.
+    pub fn is_empty(&self) -> bool {
+        self.arena.is_empty()
+    }
+
+    /// Returns whether there is an edge between the two vertices.
+    pub fn has_edge(&self, u: Vertex, v: Vertex) -> bool {
+        self.edges.contains_key(&(u, v))
+    }
+
+    /// Returns the value of the vertex.
+    pub fn get(&mut self, v: Vertex) -> &D::Value {
+        self.arena.access_path(v);
+        match &self.arena.node(v).node_value {
+            TourEntry::Vertex(value) => value,
+            TourEntry::Edge => panic!("{}", NOT_A_VERTEX_ERROR),
+        }
+    }
+
+    /// Lets you modify the value of the vertex.
+    pub fn with_value<F, R>(&mut self, v: Vertex, f: F) -> R
+    where
+        F: FnOnce(&mut D::Value) -> R,
+    {
+        let path = self.arena.access_path(v);
+        let res = match &mut self.arena.node_mut(v).node_value {
+            TourEntry::Vertex(value) => f(value),
+            TourEntry::Edge => panic!("{}", NOT_A_VERTEX_ERROR),
+        };
+        for &idx in path.iter().rev() {
+            self.arena.rebuild(idx);
+        }
+        res
+    }
+
+    /// Returns whether the two vertices are in the same tree.
+    pub fn connected(&self, u: Vertex, v: Vertex) -> bool {
+        root_of(&self.arena, u) == root_of(&self.arena, v)
+    }
+
+    /// Returns the root of the tree that contains the vertex.
+    pub fn root(&mut self, v: Vertex) -> Vertex {
+        // the root is the first entry in the tour
+        let mut first = root_of(&self.arena, v);
+        self.arena.access(first);
+        while let Some(left) = self.arena.node(first).left() {
+            self.arena.access(left);
+            first = left;
+        }
+        first
+    }
+
+    /// Makes the vertex the root of its tree.
+    pub fn reroot(&mut self, v: Vertex) {
+        // rotate the tour, so that it starts at the vertex
+        let (left, right) = split_at_node(&mut self.arena, v, false);
+        let root = concatenate(&mut self.arena, right, left);
+        self.arena.make_root(root);
+    }
+
+    /// Returns the summary of all of the values in the tree that contains the vertex.
+    pub fn component_summary(&self, v: Vertex) -> D::Summary {
+        let root = root_of(&self.arena, v);
+        self.arena.subtree_summary(Some(root)).0
+    }
+
+    /// Applies the action on all of the values in the tree that contains the vertex.
+    pub fn component_act(&mut self, v: Vertex, action: D::Action) {
+        let first = self.root(v);
+        let root = root_of(&self.arena, v);
+        let to_reverse = action.to_reverse();
+        self.arena.act_subtree(Some(root), TourAction(action));
+        if to_reverse {
+            // the reversed tour is still a tour, but it doesn't start with the root anymore
+            self.reroot(first);
+        }
+    }
+
+    /// Returns the summary of all of the values in the subtree of `v`,
+    /// where `parent` is the parent of `v`.
+    /// Returns [`None`] if `parent` isn't the parent of `v`.
+    pub fn subtree_summary(&mut self, v: Vertex, parent: Vertex) -> Option<D::Summary> {
+        let [left, down, mid, up, right] = self.isolate_subtree(v, parent)?;
+        let res = self.arena.subtree_summary(mid).0;
+        self.join([left, down, mid, up, right]);
+        Some(res)
+    }
+
+    /// Applies the action on all of the values in the subtree of `v`,
+    /// where `parent` is the parent of `v`.
+    /// Returns [`None`] if `parent` isn't the parent of `v`.
+    pub fn subtree_act(&mut self, v: Vertex, parent: Vertex, action: D::Action) -> Option<()> {
+        let [left, down, mid, up, right] = self.isolate_subtree(v, parent)?;
+        self.arena.act_subtree(mid, TourAction(action));
+        self.join([left, down, mid, up, right]);
+        Some(())
+    }
+
+    /// Removes the edge between the two vertices.
+    /// The tree that contains the old root keeps it, and the other tree is rooted
+    /// at the vertex of the edge that it contains.
+    /// Returns [`None`] if there is no such edge.
+    pub fn cut(&mut self, u: Vertex, v: Vertex) -> Option<()> {
+        let (first, second) = self.edges.remove(&(u, v))?;
+        self.edges.remove(&(v, u));
+        let [left, down, mid, up, right] = self.isolate_edge(first, second);
+        self.arena.free(down.unwrap());
+        self.arena.free(up.unwrap());
+        let root = concatenate(&mut self.arena, left, right);
+        self.arena.make_root(root);
+        // the subtree is a tour of the other tree, starting at its root,
+        // but the root's vertex entry might not be the first entry
+        let new_root = if root_of(&self.arena, u) == mid.unwrap() {
+            u
+        } else {
+            v
+        };
+        self.reroot(new_root);
+        Some(())
+    }
+
+    /// Asserts that the treaps and the tours are correct.
+    /// Panics otherwise.
+    /// This pushes down actions in order to find the start of every tour,
+    /// so it requires mutable access.
+    pub fn assert_correctness(&mut self)
+    where
+        D::Summary: Eq,
+    {
+        let roots: Vec<_> = self
+            .arena
+            .indices()
+            .filter(|&idx| self.arena.node(idx).parent().is_none())
+            .collect();
+        for root in roots {
+            self.arena
+                .assert_correctness_with(Some(root), |arena, node| {
+                    for son in node.left().into_iter().chain(node.right()) {
+                        assert!(node.alg_data() > arena.node(son).alg_data());
+                    }
+                });
+            let first = self.root(root);
+            assert!(
+                matches!(self.arena.node(first).node_value, TourEntry::Vertex(_)),
+                "The tour doesn't start with a vertex"
+            );
+        }
+        for (&(u, v), &(down, up)) in self.edges.iter() {
+            assert!(self.connected(u, v));
+            assert!(self.connected(down, up));
+            assert!(self.connected(u, down));
+        }
+    }
+
+    /// Splits the tour into the part before the edge, the edge's entry going down,
+    /// the subtree below the edge, the edge's entry going up, and the part after the edge.
+    /// `v` must be below `parent`.
+    fn isolate_subtree(&mut self, v: Vertex, parent: Vertex) -> Option<[Option<NodeIdx>; 5]> {
+        let (first, second) = *self.edges.get(&(v, parent))?;
+        let parts = self.isolate_edge(first, second);
+        if root_of(&self.arena, v) == parts[2].unwrap() {
+            Some(parts)
+        } else {
+            // `parent` is below `v`
+            self.join(parts);
+            None
+        }
+    }
+
+    /// Splits the tour around the two entries of an edge, as in
+    /// [`EulerTourForest::isolate_subtree`].
+    fn isolate_edge(&mut self, first: NodeIdx, second: NodeIdx) -> [Option<NodeIdx>; 5] {
+        let (first, second) = if self.precedes(first, second) {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        let (left, _) = split_at_node(&mut self.arena, first, false);
+        let (_, right) = split_at_node(&mut self.arena, second, true);
+        split_at_node(&mut self.arena, first, true);
+        let (mid, _) = split_at_node(&mut self.arena, second, false);
+        [left, Some(first), mid, Some(second), right]
+    }
+
+    /// Concatenates the parts of a tour.
+    fn join(&mut self, parts: [Option<NodeIdx>; 5]) {
+        let root = parts
+            .into_iter()
+            .fold(None, |tree, part| concatenate(&mut self.arena, tree, part));
+        self.arena.make_root(root);
+    }
+
+    /// Returns whether the first entry comes before the second entry in their tour.
+    fn precedes(&mut self, first: NodeIdx, second: NodeIdx) -> bool {
+        let first_path = self.arena.access_path(first);
+        let second_path = self.arena.access_path(second);
+        let common = first_path
+            .iter()
+            .zip(second_path.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let lca = self.arena.node(first_path[common - 1]);
+        // the position of each entry relative to the lowest common ancestor
+        let side = |path: &[NodeIdx]| match path.get(common) {
+            None => 0,
+            Some(&son) if lca.left() == Some(son) => -1,
+            Some(_) => 1,
+        };
+        side(&first_path) < side(&second_path)
+    }
+}
+
+impl<D: Data, P: PrioritySource> EulerTourForest<D, P> {
+    /// Adds a new vertex, in a tree of its own, and returns it.
+    pub fn add_vertex(&mut self, value: D::Value) -> Vertex {
+        self.alloc(TourEntry::Vertex(value))
+    }
+
+    /// Adds an edge between two vertices in different trees.
+    /// The tree of `u` is rerooted so that `u` becomes a son of `v`.
+    /// Returns [`None`] if the two vertices are already connected.
+    pub fn link(&mut self, u: Vertex, v: Vertex) -> Option<()> {
+        if self.connected(u, v) {
+            return None;
+        }
+        self.reroot(u);
+        let subtree = Some(root_of(&self.arena, u));
+        // insert the tour of `u` just after a visit of `v`
+        let (left, right) = split_at_node(&mut self.arena, v, true);
+        let down = self.alloc(TourEntry::Edge);
+        let up = self.alloc(TourEntry::Edge);
+        self.join([left, Some(down), subtree, Some(up), right]);
+        self.edges.insert((u, v), (down, up));
+        self.edges.insert((v, u), (down, up));
+        Some(())
+    }
+
+    fn alloc(&mut self, entry: TourEntry<D::Value>) -> NodeIdx {
+        let node = ArenaNode::new_alg(entry, self.priorities.next_priority());
+        self.arena.alloc(node)
+    }
+}
+
+const NOT_A_VERTEX_ERROR: &str = "Invalid vertex: the node is an edge entry";
@@ -0,0 +1,385 @@
+//! An implementation of link-cut trees.
+//!
+//! A [`LinkCutForest`] maintains a forest of rooted trees, whose vertices hold values.
+//! Edges can be added and removed, and the values on the path between two vertices
+//! can be summarized or acted upon, all in `O(log n)` amortized time.
+//!
+//! The forest is decomposed into paths, each of which is stored in a splay tree,
+//! ordered from the top of the path to its bottom. The splay trees use the same splay steps
+//! as the [`splay`] module, but the splaying starts at a given vertex
+//! instead of at a walker's position. Since boxed trees can't find a node from below,
+//! the nodes are stored in an [`Arena`], which keeps parent links. A parent link of the root of
+//! a splay tree points to the vertex just above its path, if there is one.
+//!
+//! The summaries and actions are the user's [`Data`] summaries and actions. Rerooting a tree
+//! reverses a path, so [`LinkCutForest::make_root`] and everything that uses it require
+//! the action to be a [`ReversibleAction`].
+//!
+//!```
+//! use grove::link_cut::LinkCutForest;
+//! use grove::example_data::{StdNum, RevAffineAction};
+//!
+//! let mut forest: LinkCutForest<StdNum> = LinkCutForest::new();
+//! let v: Vec<_> = (0..5).map(|i| forest.add_vertex(i)).collect();
+//! forest.link(v[1], v[0]).unwrap();
+//! forest.link(v[2], v[1]).unwrap();
+//! forest.link(v[3], v[1]).unwrap();
+//! assert!(forest.connected(v[2], v[3]));
+//! assert!(!forest.connected(v[2], v[4]));
+//! assert_eq!(forest.find_root(v[3]), v[0]);
+//! assert_eq!(forest.lca(v[2], v[3]), Some(v[1]));
+//!
+//! // path queries reroot the tree at the path's first vertex
+//! assert_eq!(forest.path_summary(v[2], v[3]).unwrap().sum, 2 + 1 + 3);
+//! forest.path_act(v[0], v[2], RevAffineAction {to_reverse: false, mul: 1, add: 10}).unwrap();
+//! assert_eq!(forest.path_summary(v[3], v[0]).unwrap().sum, 3 + 11 + 10);
+//! assert_eq!(forest.find_root(v[0]), v[3]);
+//!
+//! forest.cut_any(v[1], v[0]).unwrap();
+//! assert!(!forest.connected(v[0], v[3]));
+//! # forest.assert_correctness();
+//!```
+
+use super::arena::*;
+use super::splay::{splay_step, SplayPosition};
+use crate::*;
+
+/// A vertex in a [`LinkCutForest`].
+/// Vertices stay valid as long as the forest exists.
+pub type Vertex = NodeIdx;
+
+/// A forest of rooted trees, that supports linking and cutting trees,
+/// and summarizing and acting on paths.
+/// See the module documentation.
+pub struct LinkCutForest<D: Data> {
+    arena: Arena<D>,
+}
+
+impl<D: Data> Default for LinkCutForest<D> {
+    fn default() -> Self {
+        LinkCutForest::new()
+    }
+}
+
+impl<D: Data> LinkCutForest<D> {
+    /// Creates an empty forest.
+    pub fn new() -> Self {
+        LinkCutForest {
+            arena: Arena::new(),
+        }
+    }
+
+    /// Returns the number of vertices in the forest.
+    pub fn len(&self) -> usize {
+        self.arena.len()
+    }
+
+    /// Returns true if there are no vertices in the forest.
+    pub fn is_empty(&self) -> bool {
+        self.arena.is_empty()
+    }
+
+    /// Adds a new vertex, in a tree of its own, and returns it.
+    pub fn add_vertex(&mut self, value: D::Value) -> Vertex {
+        self.arena.alloc(ArenaNode::new_alg(value, ()))
+    }
+
+    /// Returns the value of the vertex.
+    pub fn get(&mut self, v: Vertex) -> &D::Value {
+        self.splay(v);
+        &self.arena.node(v).node_value
+    }
+
+    /// Lets you modify the value of the vertex.
+    pub fn with_value<F, R>(&mut self, v: Vertex, f: F) -> R
+    where
+        F: FnOnce(&mut D::Value) -> R,
+    {
+        self.splay(v);
+        let res = f(&mut self.arena.node_mut(v).node_value);
+        self.arena.rebuild(v);
+        res
+    }
+
+    /// Returns the root of the tree that contains the vertex.
+    pub fn find_root(&mut self, v: Vertex) -> Vertex {
+        self.expose(v);
+        let mut root = v;
+        while let Some(left) = self.arena.node(root).left() {
+            self.arena.access(left);
+            root = left;
+        }
+        // splay to keep the amortized complexity
+        self.splay(root);
+        root
+    }
+
+    /// Returns whether the two vertices are in the same tree.
+    pub fn connected(&mut self, u: Vertex, v: Vertex) -> bool {
+        self.find_root(u) == self.find_root(v)
+    }
+
+    /// Returns the parent of the vertex in its rooted tree.
+    /// Returns [`None`] if the vertex is a root.
+    pub fn parent(&mut self, v: Vertex) -> Option<Vertex> {
+        self.expose(v);
+        let mut parent = self.arena.node(v).left()?;
+        self.arena.access(parent);
+        while let Some(right) = self.arena.node(parent).right() {
+            self.arena.access(right);
+            parent = right;
+        }
+        self.splay(parent);
+        Some(parent)
+    }
+
+    /// Returns the lowest common ancestor of the two vertices, in their rooted tree.
+    /// Returns [`None`] if they are not in the same tree.
+    pub fn lca(&mut self, u: Vertex, v: Vertex) -> Option<Vertex> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.expose(u);
+        Some(self.expose(v))
+    }
+
+    /// Returns the summary of the values on the path from the root of the vertex's tree
+    /// to the vertex, in that order.
+    pub fn root_path_summary(&mut self, v: Vertex) -> D::Summary {
+        self.expose(v);
+        self.arena.subtree_summary(Some(v))
+    }
+
+    /// Applies the action on the values on the path from the root of the vertex's tree
+    /// to the vertex. Unlike [`LinkCutForest::path_act`], this doesn't reroot the tree,
+    /// so the action doesn't have to be a [`ReversibleAction`].
+    /// If the action reverses, the path changes its direction, i.e., `v` becomes the root.
+    pub fn root_path_act(&mut self, v: Vertex, action: D::Action) {
+        self.expose(v);
+        self.arena.act_subtree(Some(v), action);
+    }
+
+    /// Makes `child`, which has to be the root of its tree, a son of `parent`.
+    /// Returns [`None`] if `child` isn't a root, or if the two vertices are already connected.
+    pub fn link(&mut self, child: Vertex, parent: Vertex) -> Option<()> {
+        if self.find_root(child) != child || self.connected(child, parent) {
+            return None;
+        }
+        // `child` is the root of its tree, and it was splayed by `find_root`,
+        // so it is the top of its path. Attach the path below `parent`.
+        self.expose(child);
+        self.arena.node_mut(child).parent = Some(parent);
+        Some(())
+    }
+
+    /// Removes the edge between `child` and its parent, `parent`.
+    /// Returns [`None`] if `parent` isn't the parent of `child`.
+    pub fn cut(&mut self, child: Vertex, parent: Vertex) -> Option<()> {
+        if self.parent(child) != Some(parent) {
+            return None;
+        }
+        self.expose(child);
+        // the path is the path from the root to `child`, so everything above `child`
+        // is in its left subtree.
+        let left = self.arena.node(child).left();
+        self.arena.node_mut(child).left = None;
+        self.arena.make_root(left);
+        self.arena.rebuild(child);
+        Some(())
+    }
+
+    /// Asserts that the summaries and the parent links are correct.
+    /// Panics otherwise.
+    pub fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        for idx in self.arena.indices() {
+            if self.is_splay_root(idx) {
+                self.arena.assert_correctness_with(Some(idx), |_, _| ());
+            }
+        }
+    }
+
+    /// Makes the path from the root of the vertex's tree to the vertex a preferred path,
+    /// with nothing below the vertex, and splays the vertex to the root of its splay tree.
+    /// Returns the last vertex where the path was switched, i.e., the lowest vertex on
+    /// the path that was on the previous preferred path from the root.
+    fn expose(&mut self, v: Vertex) -> Vertex {
+        let mut last = None;
+        let mut current = Some(v);
+        let mut res = v;
+        while let Some(idx) = current {
+            self.splay(idx);
+            self.arena.set_right(idx, last);
+            self.arena.rebuild(idx);
+            res = idx;
+            last = Some(idx);
+            current = self.arena.node(idx).parent();
+        }
+        self.splay(v);
+        res
+    }
+
+    fn is_splay_root(&self, idx: NodeIdx) -> bool {
+        self.side(idx).is_none()
+    }
+
+    /// Splays the node to the root of its splay tree.
+    fn splay(&mut self, idx: NodeIdx) {
+        // push down the actions from the root of the splay tree
+        let mut path = vec![idx];
+        let mut current = idx;
+        while !self.is_splay_root(current) {
+            current = self.arena.node(current).parent().unwrap();
+            path.push(current);
+        }
+        for &idx in path.iter().rev() {
+            self.arena.access(idx);
+        }
+
+        let mut pos = SplayNode { forest: self, idx };
+        while pos.is_left_son().is_some() {
+            splay_step(&mut pos);
+        }
+    }
+
+    /// Returns which son of its parent the node is, or [`None`] for the root of a splay tree.
+    fn side(&self, idx: NodeIdx) -> Option<Side> {
+        let parent = self.arena.node(self.arena.node(idx).parent()?);
+        if parent.left() == Some(idx) {
+            Some(Side::Left)
+        } else if parent.right() == Some(idx) {
+            Some(Side::Right)
+        } else {
+            None
+        }
+    }
+
+    /// Rotates the node above its parent.
+    /// The node and its parent have to be accessed.
+    fn rotate_up(&mut self, idx: NodeIdx) {
+        let parent = self.arena.node(idx).parent().unwrap();
+        let parent_was_root = self.is_splay_root(parent);
+        let grandparent = self.arena.node(parent).parent();
+        if self.arena.node(parent).left() == Some(idx) {
+            let son = self.arena.node(idx).right();
+            self.arena.set_left(parent, son);
+            self.arena.set_right(idx, Some(parent));
+        } else {
+            let son = self.arena.node(idx).left();
+            self.arena.set_right(parent, son);
+            self.arena.set_left(idx, Some(parent));
+        }
+        // if the parent was the root of the splay tree, this is the link to the path above
+        self.arena.node_mut(idx).parent = grandparent;
+        if !parent_was_root {
+            let grandparent = self.arena.node_mut(grandparent.unwrap());
+            if grandparent.left == Some(parent) {
+                grandparent.left = Some(idx);
+            } else {
+                grandparent.right = Some(idx);
+            }
+        }
+        self.arena.rebuild(parent);
+        self.arena.rebuild(idx);
+    }
+}
+
+/// A position at a node of one of the splay trees of a [`LinkCutForest`],
+/// for the splay steps of the [`splay`](super::splay) module.
+/// The nodes on the way to the root of the splay tree have to be accessed.
+struct SplayNode<'a, D: Data> {
+    forest: &'a mut LinkCutForest<D>,
+    idx: NodeIdx,
+}
+
+impl<'a, D: Data> SplayPosition for SplayNode<'a, D> {
+    fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn go_to_parent(&mut self) -> Result<Side, ()> {
+        let side = self.forest.side(self.idx).ok_or(())?;
+        self.idx = self.forest.arena.node(self.idx).parent().unwrap();
+        Ok(side)
+    }
+
+    fn is_left_son(&self) -> Option<Side> {
+        self.forest.side(self.idx)
+    }
+
+    fn rot_side(&mut self, side: Side) -> Option<()> {
+        let node = self.forest.arena.node(self.idx);
+        let son = match side {
+            Side::Left => node.right(),
+            Side::Right => node.left(),
+        }?;
+        self.forest.rotate_up(son);
+        self.idx = son;
+        Some(())
+    }
+}
+
+impl<D: Data> LinkCutForest<D>
+where
+    D::Action: ReversibleAction,
+{
+    /// Makes the vertex the root of its tree.
+    pub fn make_root(&mut self, v: Vertex) {
+        self.expose(v);
+        self.arena.act_subtree(Some(v), D::Action::reversal());
+    }
+
+    /// Adds an edge between two vertices in different trees.
+    /// The tree of `u` is rerooted so that `u` becomes a son of `v`.
+    /// Returns [`None`] if the two vertices are already connected.
+    pub fn link_any(&mut self, u: Vertex, v: Vertex) -> Option<()> {
+        if self.connected(u, v) {
+            return None;
+        }
+        self.make_root(u);
+        self.link(u, v)
+    }
+
+    /// Removes the edge between two vertices, regardless of which of them is the parent.
+    /// Returns [`None`] if there is no such edge.
+    pub fn cut_any(&mut self, u: Vertex, v: Vertex) -> Option<()> {
+        if self.parent(u) == Some(v) {
+            self.cut(u, v)
+        } else {
+            self.cut(v, u)
+        }
+    }
+
+    /// Returns the summary of the values on the path from `u` to `v`, in that order.
+    /// Returns [`None`] if the vertices aren't connected.
+    ///
+    /// This reroots the tree at `u`.
+    pub fn path_summary(&mut self, u: Vertex, v: Vertex) -> Option<D::Summary> {
+        self.expose_path(u, v)?;
+        Some(self.arena.subtree_summary(Some(v)))
+    }
+
+    /// Applies the action on the values on the path from `u` to `v`.
+    /// If the action reverses, the path changes its direction, i.e., `v` becomes the root.
+    /// The values stay at their vertices.
+    /// Returns [`None`] if the vertices aren't connected.
+    ///
+    /// This reroots the tree at `u`.
+    pub fn path_act(&mut self, u: Vertex, v: Vertex, action: D::Action) -> Option<()> {
+        self.expose_path(u, v)?;
+        self.arena.act_subtree(Some(v), action);
+        Some(())
+    }
+
+    /// Makes the path from `u` to `v` a single splay tree, rooted at `v`.
+    fn expose_path(&mut self, u: Vertex, v: Vertex) -> Option<()> {
+        if !self.connected(u, v) {
+            return None;
+        }
+        self.make_root(u);
+        self.expose(v);
+        Some(())
+    }
+}
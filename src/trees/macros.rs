@@ -9,12 +9,13 @@
 ///     }
 /// }
 ///```
-/// expects the `go_up` method to be implemented
+/// expects the `go_up` method to be implemented.
+/// Extra generic parameters can follow `D: Data`, e.g., `impl<'a, D: Data, P> ...`
 macro_rules! derive_SomeWalker {
-    ($accessor:ident, impl<$lifetime:lifetime, $data:ident: Data> SomeWalker<D> for $self:ty
+    ($accessor:ident, impl<$lifetime:lifetime, $data:ident: Data $(, $param:ident $(: $bound:path)?)*> SomeWalker<D> for $self:ty
         { $($token:tt)* }
     ) => {
-        impl<$lifetime, $data: Data> SomeWalker<$data> for $self {
+        impl<$lifetime, $data: Data $(, $param $(: $bound)?)*> SomeWalker<$data> for $self {
             fn go_left(&mut self) -> Result<(), ()> {
                 self.$accessor.go_left()
             }
@@ -32,7 +33,7 @@ macro_rules! derive_SomeWalker {
             }
 
             fn far_right_summary(&self) -> $data::Summary {
-                self.$accessor.far_left_summary()
+                self.$accessor.far_right_summary()
             }
 
             fn value(&self) -> Option<& $data::Value> {
@@ -57,12 +58,13 @@ macro_rules! derive_SomeWalker {
 ///     }
 /// }
 ///```
-/// expects the `assert_correctness_locally` method to be implemented
+/// expects the `assert_correctness_locally` method to be implemented.
+/// Extra generic parameters can follow `D: Data`, e.g., `impl<'a, D: Data, P> ...`
 macro_rules! derive_SomeEntry {
-    ($accessor:ident, $alg_data:ty, impl <$($lifetime:lifetime,)? $data:ident : Data> SomeEntry<D> for $self:ty
+    ($accessor:ident, $alg_data:ty, impl <$($lifetime:lifetime,)? $data:ident : Data $(, $param:ident $(: $bound:path)?)*> SomeEntry<D> for $self:ty
         { $($token:tt)* }
     ) => {
-        impl<$($lifetime,)? $data : Data> SomeEntry<$data> for $self {
+        impl<$($lifetime,)? $data : Data $(, $param $(: $bound)?)*> SomeEntry<$data> for $self {
             fn with_value<F, R>(&mut self, f: F) -> Option<R>
             where
                 F: FnOnce(&mut D::Value) -> R,
@@ -10,12 +10,20 @@
 #[macro_use]
 mod macros;
 mod segment_algorithms;
+mod set_algorithms;
 
+pub mod arena;
 pub mod avl;
 pub mod basic_tree;
+pub mod euler_tour;
+pub mod link_cut;
+pub mod persistent;
+pub mod red_black;
+pub mod scapegoat;
 pub mod slice;
 pub mod splay;
 pub mod treap;
+pub mod weight_balanced;
 
 use crate::data::*;
 use crate::locators;
@@ -76,6 +84,269 @@ where
         slice::Slice::new(self, locator)
     }
 
+    /// Like [`SomeTree::slice`], for an index range in a tree whose values may each represent
+    /// several elements. First splits the values that cross the edges of the range,
+    /// using [`SplittableValue::split_at`], so that the segment consists of exactly the elements
+    /// in the range, and operations on it don't affect the elements around it.
+    /// This is a separate method, since [`SomeTree::slice`] has to work for values that can't be split.
+    ///
+    /// For example, `tree.slice_exact(i..i).split_right()` splits the tree right before
+    /// the `i`-th element, even if that element is in the middle of a value.
+    fn slice_exact<R>(&mut self, range: R) -> slice::Slice<'_, D, Self, std::ops::Range<usize>>
+    where
+        R: std::ops::RangeBounds<usize>,
+        D::Value: SplittableValue,
+        D::Summary: SizedSummary,
+        for<'a> &'a mut Self: ModifiableTreeRef<D>,
+    {
+        use std::ops::Bound;
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.subtree_summary().size(),
+        };
+        split_value_at(self, start);
+        split_value_at(self, end);
+        self.slice(start..end)
+    }
+
+    /// If `index` is on the boundary between two values, tries to merge them
+    /// into one value using [`SplittableValue::try_merge`].
+    /// Returns `true` if the values were merged.
+    fn merge_at(&mut self, index: usize) -> bool
+    where
+        D::Value: SplittableValue,
+        D::Summary: SizedSummary,
+        for<'a> &'a mut Self: ModifiableTreeRef<D>,
+    {
+        if index == 0 {
+            return false;
+        }
+        // checking first, so that the tree isn't changed if the values can't be merged
+        let merged = {
+            let mut values = self.iter_indexed(index - 1..=index);
+            match (values.next(), values.next()) {
+                (Some((_, left)), Some((start, right))) if start == index => left.try_merge(right),
+                _ => None,
+            }
+        };
+        match merged {
+            Some(value) => {
+                self.search(index).delete().unwrap();
+                self.search(index - 1)
+                    .with_value(|left| *left = value)
+                    .unwrap();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the largest `end` such that `pred` holds on the summary of the segment `start..end`,
+    /// in `O(log n)` time. This is the `max_right` operation of segment trees.
+    ///
+    /// `pred` has to be monotone, i.e., if it holds on a segment, it has to hold on all of its
+    /// prefixes, and it has to hold on the empty segment.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter().collect();
+    /// // the longest segment starting at index 2, whose sum is at most 10
+    /// assert_eq!(tree.max_right(2, |summary| summary.sum <= 10), 5);
+    /// // the values from index 1 onwards are all smaller than 10
+    /// assert_eq!(tree.max_right(1, |summary| summary.max < Some(10)), 8);
+    ///```
+    fn max_right<P>(&mut self, start: usize, pred: P) -> usize
+    where
+        P: FnMut(&D::Summary) -> bool,
+        D::Summary: SizedSummary,
+    {
+        segment_algorithms::max_right(self, start, pred)
+    }
+
+    /// Returns the smallest `start` such that `pred` holds on the summary of the segment
+    /// `start..end`, in `O(log n)` time. This is the `min_left` operation of segment trees.
+    ///
+    /// `pred` has to be monotone, i.e., if it holds on a segment, it has to hold on all of its
+    /// suffixes, and it has to hold on the empty segment.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter().collect();
+    /// // the longest segment ending at index 5, whose sum is at most 10
+    /// assert_eq!(tree.min_left(5, |summary| summary.sum <= 10), 2);
+    ///```
+    fn min_left<P>(&mut self, end: usize, pred: P) -> usize
+    where
+        P: FnMut(&D::Summary) -> bool,
+        D::Summary: SizedSummary,
+    {
+        segment_algorithms::min_left(self, end, pred)
+    }
+
+    /// Calls `f` on every value that matches the filter, in order.
+    /// Subtrees that the filter skips aren't visited at all. See [`locators::SubtreeFilter`].
+    ///
+    /// This goes over the tree without restructuring it. Therefore, on splay trees,
+    /// it takes time proportional to the depths of the visited nodes,
+    /// instead of `O(log n)` amortized time per matching segment.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::{NumSummary, RevAffineAction, StdNum};
+    /// use grove::locators::FilterResult::*;
+    ///
+    /// let mut tree: Treap<StdNum> = vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter().collect();
+    /// // the values that are bigger than 4
+    /// let bigger = |summary: &NumSummary| {
+    ///     if summary.max <= Some(4) {
+    ///         Skip
+    ///     } else if summary.min > Some(4) {
+    ///         TakeAll
+    ///     } else {
+    ///         Descend
+    ///     }
+    /// };
+    /// let mut found = vec![];
+    /// tree.for_each_matching(bigger, |&value| found.push(value));
+    /// assert_eq!(found, vec![5, 9, 6]);
+    ///
+    /// tree.act_matching(RevAffineAction { to_reverse: false, mul: 1, add: 10 }, bigger);
+    /// assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![3, 1, 4, 1, 15, 19, 2, 16]);
+    ///```
+    fn for_each_matching<F, C>(&mut self, filter: F, f: C)
+    where
+        F: locators::SubtreeFilter<D>,
+        C: FnMut(&D::Value),
+    {
+        segment_algorithms::for_each_matching(self, filter, f)
+    }
+
+    /// Iterates over the values that match the filter, in order.
+    /// Subtrees that the filter skips aren't visited at all.
+    /// See [`basic_tree::iterators::IterMatching`].
+    ///
+    /// Like [`SomeTree::for_each_matching`], this doesn't restructure splay trees.
+    ///```
+    /// use grove::{SomeTree, avl::AVLTree};
+    /// use grove::example_data::{NumSummary, StdNum};
+    /// use grove::locators::FilterResult::*;
+    ///
+    /// let mut tree: AVLTree<StdNum> = vec![3, 1, 4, 1, 5, 9, 2, 6].into_iter().collect();
+    /// // the values that are smaller than 3
+    /// let smaller = |summary: &NumSummary| {
+    ///     if summary.min >= Some(3) {
+    ///         Skip
+    ///     } else if summary.max < Some(3) {
+    ///         TakeAll
+    ///     } else {
+    ///         Descend
+    ///     }
+    /// };
+    /// assert_eq!(tree.iter_matching(smaller).cloned().collect::<Vec<_>>(), vec![1, 1, 2]);
+    /// # tree.assert_correctness();
+    ///```
+    fn iter_matching<F>(
+        &mut self,
+        filter: F,
+    ) -> basic_tree::iterators::IterMatching<'_, D, F, <Self as sealed::AsBasicTreeMut<D>>::AlgData>
+    where
+        Self: AsBasicTree<D>,
+        F: locators::SubtreeFilter<D>,
+    {
+        basic_tree::iterators::IterMatching::new(self.as_basic_tree_mut(sealed::Token(())), filter)
+    }
+
+    /// Applies an action on every value that matches the filter.
+    /// Subtrees that the filter takes entirely are acted upon at once,
+    /// as in [`SomeTree::act_segment`]. See [`locators::SubtreeFilter`].
+    ///
+    /// Like [`SomeTree::for_each_matching`], this doesn't restructure splay trees.
+    ///
+    /// Panics if the action reverses segments.
+    fn act_matching<F>(&mut self, action: D::Action, filter: F)
+    where
+        F: locators::SubtreeFilter<D>,
+    {
+        segment_algorithms::act_matching(self, action, filter)
+    }
+
+    /// Calls `f` on every value in the segment, in order, and rebuilds the summaries
+    /// of the changed nodes. Takes `O(k + log n)` time for a segment of `k` values.
+    ///
+    /// The locator is called on every value before `f` changes it, in order to find
+    /// where the segment ends. Therefore, `f` shouldn't change the values in a way that
+    /// changes which of the remaining values the locator accepts.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = (0..8).collect();
+    /// tree.for_each_mut(2..5, |value| *value *= 10);
+    /// tree.map_segment(6.., |value| -value);
+    /// assert_eq!(tree.segment_summary(..).sum, 0 + 1 + 20 + 30 + 40 + 5 - 6 - 7);
+    /// assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![0, 1, 20, 30, 40, 5, -6, -7]);
+    ///```
+    fn for_each_mut<L, F>(&mut self, locator: L, f: F)
+    where
+        L: locators::Locator<D>,
+        F: FnMut(&mut D::Value),
+    {
+        segment_algorithms::for_each_mut(self, locator, f)
+    }
+
+    /// Replaces every value in the segment by the result of `f` on it.
+    /// Same as [`SomeTree::for_each_mut`].
+    fn map_segment<L, F>(&mut self, locator: L, mut f: F)
+    where
+        L: locators::Locator<D>,
+        F: FnMut(&D::Value) -> D::Value,
+    {
+        self.for_each_mut(locator, |value| *value = f(value))
+    }
+
+    /// Removes every value in the segment that `pred` returns `false` on,
+    /// and keeps the order of the rest of the values.
+    ///
+    /// The segment is split out of the tree, filtered, and concatenated back.
+    /// This takes `O(k + log n)` time for a segment of `k` values, as long as building
+    /// a tree out of `k` values takes `O(k)` time, as it does for the trees in this crate.
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: Treap<StdNum> = (0..10).collect();
+    /// tree.retain(2..8, |value| value % 3 == 0);
+    /// assert_eq!(tree.segment_summary(..).size, 6);
+    /// assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![0, 1, 3, 6, 8, 9]);
+    ///```
+    fn retain<L, P>(&mut self, locator: L, mut pred: P)
+    where
+        Self: ConcatenableTree<D>,
+        for<'a> &'a mut Self: SplittableTreeRef<D, T = Self>,
+        L: locators::Locator<D>,
+        P: FnMut(&D::Value) -> bool,
+    {
+        self.retain_mut(locator, |value| pred(value))
+    }
+
+    /// Same as [`SomeTree::retain`], but `pred` may also change the values that it keeps.
+    fn retain_mut<L, P>(&mut self, locator: L, pred: P)
+    where
+        Self: ConcatenableTree<D>,
+        for<'a> &'a mut Self: SplittableTreeRef<D, T = Self>,
+        L: locators::Locator<D>,
+        P: FnMut(&mut D::Value) -> bool,
+    {
+        segment_algorithms::retain_mut(self, locator, pred)
+    }
+
     /// This is here just so that the signature for iter_locator can be written out. Don't use this.
     type TreeData;
 
@@ -116,6 +387,114 @@ where
         self.iter_locator(..)
     }
 
+    /// Iterates over a segment, and returns every value together with the summaries
+    /// of all of the values to its left and to its right in the whole tree.
+    /// Each step takes `O(1)` amortized time. See [`basic_tree::iterators::IterWithContext`].
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (1..=6).collect();
+    ///
+    /// // prefix sums
+    /// let sums: Vec<i32> = tree
+    ///     .iter_with_context(2..5)
+    ///     .map(|(left, value, _)| left.sum + value)
+    ///     .collect();
+    /// assert_eq!(sums, vec![6, 10, 15]);
+    ///
+    /// let suffix_sums: Vec<i32> = tree
+    ///     .iter_with_context(..)
+    ///     .rev()
+    ///     .map(|(_, value, right)| value + right.sum)
+    ///     .collect();
+    /// assert_eq!(suffix_sums, vec![6, 11, 15, 18, 20, 21]);
+    /// # tree.assert_correctness();
+    ///```
+    fn iter_with_context<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> basic_tree::iterators::IterWithContext<'a, D, L, Self::TreeData> {
+        self.iter_locator(locator).with_context()
+    }
+
+    /// Iterates over a segment, and returns every value together with its index in the whole tree.
+    /// Each step takes `O(1)` amortized time. See [`basic_tree::iterators::IterIndexed`].
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (20..80).collect();
+    ///
+    /// let indexed: Vec<_> = tree
+    ///     .iter_indexed(3..6)
+    ///     .map(|(index, value)| (index, *value))
+    ///     .collect();
+    /// assert_eq!(indexed, vec![(3, 23), (4, 24), (5, 25)]);
+    /// # tree.assert_correctness();
+    ///```
+    fn iter_indexed<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> basic_tree::iterators::IterIndexed<'a, D, L, Self::TreeData>
+    where
+        D::Summary: SizedSummary,
+    {
+        self.iter_with_context(locator).indexed()
+    }
+
+    /// Iterates over a segment, like [`SomeTree::iter_locator`], but also keeps the number
+    /// of values left, so that the iterator is an [`ExactSizeIterator`].
+    /// The number of values is the size of the segment's summary.
+    /// See [`basic_tree::iterators::ExactSize`].
+    ///```
+    /// use grove::{SomeTree, basic_tree::BasicTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: BasicTree<StdNum> = (20..80).collect();
+    ///
+    /// let mut iter = tree.iter_locator_sized(3..13);
+    /// assert_eq!(iter.len(), 10);
+    /// assert_eq!(iter.next_back(), Some(&32));
+    /// assert_eq!(iter.size_hint(), (9, Some(9)));
+    /// assert_eq!(iter.skip(2).len(), 7);
+    /// # tree.assert_correctness();
+    ///```
+    fn iter_locator_sized<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> basic_tree::iterators::ExactSize<
+        basic_tree::iterators::IterLocator<'a, D, L, Self::TreeData>,
+    >
+    where
+        D::Summary: SizedSummary,
+    {
+        let len = self.segment_summary(locator.clone()).size();
+        basic_tree::iterators::ExactSize::new(self.iter_locator(locator), len)
+    }
+
+    /// Converts the tree into an iterator over its values, like [`IntoIterator::into_iter`],
+    /// but also keeps the number of values left, so that the iterator is an [`ExactSizeIterator`].
+    /// See [`basic_tree::iterators::ExactSize`].
+    ///```
+    /// use grove::{SomeTree, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let tree: Treap<StdNum> = (0..10).collect();
+    ///
+    /// let mut iter = tree.into_iter_sized();
+    /// assert_eq!(iter.len(), 10);
+    /// assert_eq!(iter.next(), Some(0));
+    /// assert_eq!(iter.rev().take(3).collect::<Vec<_>>(), vec![9, 8, 7]);
+    ///```
+    fn into_iter_sized(mut self) -> basic_tree::iterators::ExactSize<Self::IntoIter>
+    where
+        D::Summary: SizedSummary,
+    {
+        let len = self.segment_summary(..).size();
+        basic_tree::iterators::ExactSize::new(self.into_iter(), len)
+    }
+
     /// Used for testing purposes.
     /// Should panic if the invariants aren't satisfied.
     fn assert_correctness(&self)
@@ -123,6 +502,30 @@ where
         D::Summary: Eq;
 }
 
+/// If the `index`-th element is in the middle of a value, splits that value in two
+/// using [`SplittableValue::split_at`], so that `index` is on the boundary between values.
+fn split_value_at<D: Data, T>(tree: &mut T, index: usize)
+where
+    D::Value: SplittableValue,
+    D::Summary: SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+{
+    // using an empty range so that we'll only end up at a node
+    // if we actually need to split that node
+    let mut walker = tree.search(index..index);
+    let left = walker.left_summary().size();
+    let right = walker.with_value(|value| {
+        let (first, second) = value.split_at(index - left);
+        *value = first;
+        second
+    });
+    if let Some(right) = right {
+        walker.next_empty().unwrap(); // not at an empty position
+        walker.insert(right).unwrap();
+    }
+}
+
 /// This is a workaround for not having Generic Associated Types in Rust yet.
 /// Really, the type [`Self::Walker`] should have been defined in [`SomeTree`] and
 /// should have been generic in a lifetime parameter.
@@ -441,3 +844,74 @@ pub trait SplittableWalker<D: Data>: ModifiableWalker<D> {
     /// Otherwise returns [`None`].
     fn split_left(&mut self) -> Option<Self::T>;
 }
+
+/// Trees that are stored as a [`basic_tree::BasicTree`], and can give access to it.
+/// Reading the inner tree doesn't change it, so it can be used for queries that only have `&self`.
+/// Note that reading a splay tree without splaying it may undermine its complexity guarantees.
+///
+/// This trait is sealed: mutable access to the inner tree is only used inside this crate,
+/// since changing the structure of the inner tree may break the tree's balance invariants.
+pub trait AsBasicTree<D: Data>: sealed::AsBasicTreeMut<D> {
+    /// Returns the inner tree.
+    fn as_basic_tree(&self) -> &basic_tree::BasicTree<D, Self::AlgData>;
+}
+
+pub(crate) mod sealed {
+    use super::*;
+
+    /// Gives mutable access to the inner tree of an [`AsBasicTree`].
+    /// Changing the structure of the inner tree may break the tree's balance invariants.
+    /// Changing values is fine, as long as the summaries are rebuilt.
+    pub trait AsBasicTreeMut<D: Data> {
+        /// The algorithm-specific data stored in the nodes.
+        type AlgData;
+
+        /// Returns the inner tree mutably.
+        fn as_basic_tree_mut(
+            &mut self,
+            token: Token,
+        ) -> &mut basic_tree::BasicTree<D, Self::AlgData>;
+    }
+
+    /// Generic code can call the methods of supertraits of its bounds, even if it can't name them.
+    /// Since only this crate can create a token, only it can call
+    /// [`AsBasicTreeMut::as_basic_tree_mut`].
+    pub struct Token(pub(crate) ());
+}
+
+/// A policy for values with equal keys that are found in both trees of a set operation,
+/// such as [`treap::union_with`] or [`avl::intersection_with`].
+/// It is mostly useful when the ordering of the values only compares a part of them, e.g., a key.
+///
+/// Closures of the type `FnMut(left, right) -> merged` are policies that merge the two values.
+pub trait DuplicatePolicy<V> {
+    /// Combines the value from the first (left) tree and the value from the second (right) tree
+    /// into the value that is kept in the result.
+    fn merge(&mut self, left: V, right: V) -> V;
+}
+
+/// A [`DuplicatePolicy`] that keeps the value from the first tree.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct KeepLeft;
+
+/// A [`DuplicatePolicy`] that keeps the value from the second tree.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct KeepRight;
+
+impl<V> DuplicatePolicy<V> for KeepLeft {
+    fn merge(&mut self, left: V, _right: V) -> V {
+        left
+    }
+}
+
+impl<V> DuplicatePolicy<V> for KeepRight {
+    fn merge(&mut self, _left: V, right: V) -> V {
+        right
+    }
+}
+
+impl<V, F: FnMut(V, V) -> V> DuplicatePolicy<V> for F {
+    fn merge(&mut self, left: V, right: V) -> V {
+        self(left, right)
+    }
+}
@@ -0,0 +1,531 @@
+//! The persistent trees module.
+//! This module implements trees whose nodes are shared between different versions of the tree.
+//!
+//! The nodes of a [`PersistentTree`] are reference counted, so cloning a tree takes `O(1)` time,
+//! and the clone shares all of its nodes with the original tree.
+//! A node that is shared is never modified. Instead, when a tree is modified, the nodes on the path
+//! from the root to the modified positions are copied (path copying), and the rest of the nodes
+//! stay shared. Therefore, a clone of a tree is a snapshot that stays the same no matter how the
+//! other versions are modified afterwards.
+//!
+//! Actions are still applied lazily. Pushing an action from a node down to a son that is shared
+//! copies the son first, so that the action never leaks into the other versions.
+//! Queries that only read the tree, like [`PersistentTree::segment_summary`], don't push
+//! actions down at all, and never copy nodes.
+//!
+//! Since nodes are copied, the values in persistent trees have to implement [`Clone`].
+//!
+//! Like [`basic_tree`](super::basic_tree), the [`PersistentTree`] type is an unbalanced tree,
+//! meant to be wrapped by balanced tree implementations. See [`treap::PersistentTreap`].
+
+pub mod treap;
+
+use crate::*;
+use locators::{LocResult, Locator};
+use std::rc::Rc;
+
+/// A persistent tree. might be empty.
+/// Cloning a persistent tree takes `O(1)` time. The clones share their nodes.
+/// The `T` parameter is for algorithm-specific bookeeping data.
+#[derive(Default)]
+pub enum PersistentTree<D: ?Sized + Data, T = ()> {
+    /// An empty tree
+    #[default]
+    Empty,
+    /// A non empty tree, with a shared root node
+    Root(Rc<PersistentNode<D, T>>),
+}
+use PersistentTree::*;
+
+// the default clone implementation requires that D: Clone, which is uneccessary
+impl<D: Data, T> Clone for PersistentTree<D, T> {
+    fn clone(&self) -> Self {
+        match self {
+            Empty => Empty,
+            Root(node) => Root(Rc::clone(node)),
+        }
+    }
+}
+
+impl<D: Data, T> PersistentTree<D, T> {
+    /// Creates an empty tree
+    pub fn new() -> Self {
+        Empty
+    }
+
+    /// Constructs a new non-empty tree from a node.
+    pub fn from_node(node: PersistentNode<D, T>) -> Self {
+        Root(Rc::new(node))
+    }
+
+    /// Returns true if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Empty)
+    }
+
+    /// Returns The inner node.
+    pub fn node(&self) -> Option<&PersistentNode<D, T>> {
+        match self {
+            Empty => None,
+            Root(node) => Some(node),
+        }
+    }
+
+    /// Returns the algorithm-specific data
+    pub fn alg_data(&self) -> Option<&T> {
+        Some(self.node()?.alg_data())
+    }
+
+    /// Returns the action that is (locally) going to be applied to all of
+    /// this tree's nodes.
+    /// Returns `default()` if the tree is empty, and `self.node().action` otherwise
+    pub fn action(&self) -> D::Action {
+        match self.node() {
+            Some(node) => node.action.clone(),
+            None => Default::default(),
+        }
+    }
+
+    /// Returns the summary of all values in this tree.
+    pub fn subtree_summary(&self) -> D::Summary {
+        match self.node() {
+            Some(node) => node.subtree_summary(),
+            None => Default::default(),
+        }
+    }
+
+    /// Compute the summary of a subsegment.
+    /// This doesn't push down any actions, and therefore doesn't copy any nodes.
+    ///```
+    /// use grove::persistent::treap::PersistentTreap;
+    /// use grove::example_data::StdNum;
+    ///
+    /// let tree: PersistentTreap<StdNum> = (20..80).collect();
+    /// assert_eq!(tree.tree().segment_summary(3..13).sum, (23..33).sum());
+    ///```
+    pub fn segment_summary<L>(&self, locator: L) -> D::Summary
+    where
+        L: Locator<D>,
+        D::Value: Clone,
+    {
+        use LocResult::*;
+
+        let mut cursor = ImmCursor::new(self);
+        while let Some(res) = cursor.locate(&locator) {
+            match res {
+                GoRight => {
+                    cursor.go_right();
+                }
+                GoLeft => {
+                    cursor.go_left();
+                }
+
+                // at this point, we split into the two sides
+                Accept => {
+                    let node_summary = cursor.node_summary().unwrap();
+
+                    let mut left = cursor.clone();
+                    left.go_left();
+                    let mut first_half = Default::default();
+                    while let Some(res) = left.locate(&locator) {
+                        match res {
+                            Accept => first_half = left.go_left().unwrap() + first_half,
+                            GoRight => {
+                                left.go_right();
+                            }
+                            GoLeft => panic!("inconsistent locator"),
+                        }
+                    }
+
+                    let mut right = cursor;
+                    right.go_right();
+                    let mut second_half = Default::default();
+                    while let Some(res) = right.locate(&locator) {
+                        match res {
+                            Accept => second_half = second_half + right.go_right().unwrap(),
+                            GoRight => panic!("inconsistent locator"),
+                            GoLeft => {
+                                right.go_left();
+                            }
+                        }
+                    }
+
+                    return first_half + node_summary + second_half;
+                }
+            }
+        }
+
+        // empty segment case
+        Default::default()
+    }
+
+    /// Returns a value that the locator accepts, if there is one.
+    /// This doesn't push down any actions, and therefore doesn't copy any nodes.
+    pub fn find<L>(&self, locator: L) -> Option<D::Value>
+    where
+        L: Locator<D>,
+        D::Value: Clone,
+    {
+        use LocResult::*;
+
+        let mut cursor = ImmCursor::new(self);
+        while let Some(res) = cursor.locate(&locator) {
+            match res {
+                GoRight => {
+                    cursor.go_right();
+                }
+                GoLeft => {
+                    cursor.go_left();
+                }
+                Accept => return cursor.value(),
+            }
+        }
+        None
+    }
+
+    /// Iterates over the values of the tree, by cloning them.
+    /// This doesn't push down any actions, and therefore doesn't copy any nodes.
+    pub fn iter(&self) -> Iter<'_, D, T>
+    where
+        D::Value: Clone,
+    {
+        let mut iter = Iter { stack: vec![] };
+        iter.push_left_spine(self, Default::default());
+        iter
+    }
+
+    /// Checks that invariants remain correct. i.e., that every node's summary
+    /// is the sum of the summaries of its children.
+    /// If it is not, panics.
+    pub fn assert_correctness_with<F>(&self, func: F)
+    where
+        F: Fn(&PersistentNode<D, T>) + Copy,
+    {
+        if let Some(node) = self.node() {
+            func(node);
+            node.left.assert_correctness_with(func);
+            node.right.assert_correctness_with(func);
+        }
+    }
+
+    /// Checks that every node's summary is the sum of the summaries of its children.
+    /// If it is not, panics.
+    pub fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        self.assert_correctness_with(PersistentNode::assert_correctness_locally);
+    }
+}
+
+impl<D: Data, T: Clone> PersistentTree<D, T>
+where
+    D::Value: Clone,
+{
+    /// Returns The inner node, mutably.
+    /// If the node is shared with other versions of the tree, it is copied first.
+    pub fn node_mut(&mut self) -> Option<&mut PersistentNode<D, T>> {
+        match self {
+            Empty => None,
+            Root(node) => Some(Rc::make_mut(node)),
+        }
+    }
+
+    /// Returns The inner node.
+    /// If the node is shared with other versions of the tree, it is copied.
+    pub fn into_node(self) -> Option<PersistentNode<D, T>> {
+        match self {
+            Empty => None,
+            Root(node) => Some(Rc::try_unwrap(node).unwrap_or_else(|node| (*node).clone())),
+        }
+    }
+
+    /// Applies the given action to the whole tree.
+    /// If the root is shared with other versions of the tree, it is copied first.
+    pub fn act_subtree(&mut self, action: D::Action) {
+        if action.is_identity() {
+            return; // don't copy nodes needlessly
+        }
+        if let Some(node) = self.node_mut() {
+            node.act(action);
+        }
+    }
+}
+
+/// A persistent node. can be viewed as a non-empty persistent tree: it always has at least one value.
+/// The `T` parameter is for algorithm-specific bookeeping data.
+pub struct PersistentNode<D: ?Sized + Data, T = ()> {
+    action: D::Action,
+    subtree_summary: D::Summary,
+    pub(crate) node_value: D::Value,
+    pub(crate) left: PersistentTree<D, T>,
+    pub(crate) right: PersistentTree<D, T>,
+    pub(crate) alg_data: T,
+}
+
+// the default clone implementation requires that D: Clone, which is uneccessary
+impl<D: Data, T: Clone> Clone for PersistentNode<D, T>
+where
+    D::Value: Clone,
+{
+    fn clone(&self) -> Self {
+        PersistentNode {
+            action: self.action.clone(),
+            subtree_summary: self.subtree_summary.clone(),
+            node_value: self.node_value.clone(),
+            left: self.left.clone(),
+            right: self.right.clone(),
+            alg_data: self.alg_data.clone(),
+        }
+    }
+}
+
+impl<D: Data, T> PersistentNode<D, T> {
+    /// Creates a node with a single value, and the algorithm specific data.
+    pub fn new_alg(value: D::Value, alg_data: T) -> PersistentNode<D, T> {
+        let subtree_summary = value.to_summary();
+        PersistentNode {
+            action: Default::default(),
+            node_value: value,
+            subtree_summary,
+            left: Empty,
+            right: Empty,
+            alg_data,
+        }
+    }
+
+    /// Returns the algorithm-specific data
+    pub fn alg_data(&self) -> &T {
+        &self.alg_data
+    }
+
+    /// Returns the summary of all values in this node's subtree.
+    pub fn subtree_summary(&self) -> D::Summary {
+        self.action.act(self.subtree_summary.clone())
+    }
+
+    /// Returns a summary for the value in this node specifically,
+    /// and not the subtree.
+    pub fn node_summary(&self) -> D::Summary {
+        let summary = self.node_value.to_summary();
+        self.action.act(summary)
+    }
+
+    /// Returns the value stored in this node specifically.
+    pub fn node_value(&self) -> D::Value
+    where
+        D::Value: Clone,
+    {
+        self.action.act(self.node_value.clone())
+    }
+
+    /// Remakes the data that is stored in this node, based on its sons.
+    /// This is necessary when the data in the sons might have changed.
+    pub(crate) fn rebuild(&mut self) {
+        assert!(self.action.is_identity());
+        let temp = self.node_value.to_summary();
+        self.subtree_summary = self.left.subtree_summary() + temp + self.right.subtree_summary();
+    }
+
+    /// Asserts that the summaries were calculated correctly at the current node.
+    /// Otherwise, panics.
+    pub fn assert_correctness_locally(&self)
+    where
+        D::Summary: Eq,
+    {
+        let ns = self.subtree_summary.clone();
+        let os: D::Summary = self.left.subtree_summary()
+            + self.node_value.to_summary()
+            + self.right.subtree_summary();
+        assert!(ns == os, "Incorrect summaries found.");
+    }
+}
+
+impl<D: Data, T: Clone> PersistentNode<D, T>
+where
+    D::Value: Clone,
+{
+    /// This function applies the given action to its whole subtree.
+    ///
+    /// This function leaves the `action` field "dirty" - after calling
+    /// this you might need to call access, to push the action to this node's sons.
+    ///
+    /// If the action can't act on the subtree's summary (see [`Acts::can_act`]),
+    /// it is applied on the node's value and on its sons separately instead.
+    pub fn act(&mut self, action: D::Action) {
+        if action.can_act(&self.subtree_summary()) {
+            self.action = action + std::mem::take(&mut self.action);
+        } else {
+            self.access();
+            if action.to_reverse() {
+                std::mem::swap(&mut self.left, &mut self.right);
+            }
+            action.act_inplace(&mut self.node_value);
+            self.left.act_subtree(action.clone());
+            self.right.act_subtree(action);
+            self.rebuild();
+        }
+    }
+
+    /// Pushes any actions stored in this node to its sons.
+    /// Sons that are shared with other versions of the tree are copied first.
+    pub(crate) fn access(&mut self) {
+        if self.action.is_identity() {
+            return; // don't copy the sons needlessly
+        }
+
+        // reversing
+        // for data that doesn't implement reversing, this becomes a no-op
+        // and hopefully optimized away
+        if self.action.to_reverse() {
+            std::mem::swap(&mut self.left, &mut self.right);
+        }
+
+        let action = std::mem::take(&mut self.action);
+        action.act_inplace(&mut self.subtree_summary);
+        action.act_inplace(&mut self.node_value);
+        self.left.act_subtree(action.clone());
+        self.right.act_subtree(action);
+    }
+}
+
+/// The left and right sons of a node.
+type Sons<'a, D, T> = (&'a PersistentTree<D, T>, &'a PersistentTree<D, T>);
+
+/// A cursor that walks down a persistent tree without modifying it.
+/// Like `ImmDownBasicWalker`, but for persistent trees.
+struct ImmCursor<'a, D: Data, T> {
+    tree: &'a PersistentTree<D, T>,
+
+    // to be applied to everything in `tree`.
+    // already contains this node's actions.
+    action: D::Action,
+
+    // the summary of everything to the left of the current subtree
+    far_left_summary: D::Summary,
+    // the summary of everything to the right of the current subtree
+    far_right_summary: D::Summary,
+}
+
+// the default clone implementation requires that D: Clone and T: Clone, which is uneccessary
+impl<'a, D: Data, T> Clone for ImmCursor<'a, D, T> {
+    fn clone(&self) -> Self {
+        ImmCursor {
+            tree: self.tree,
+            action: self.action.clone(),
+            far_left_summary: self.far_left_summary.clone(),
+            far_right_summary: self.far_right_summary.clone(),
+        }
+    }
+}
+
+impl<'a, D: Data, T> ImmCursor<'a, D, T> {
+    fn new(tree: &'a PersistentTree<D, T>) -> Self {
+        ImmCursor {
+            tree,
+            action: tree.action(),
+            far_left_summary: Default::default(),
+            far_right_summary: Default::default(),
+        }
+    }
+
+    /// Returns the left and right sons, after taking reversals into account.
+    fn sons(&self) -> Option<Sons<'a, D, T>> {
+        let node = self.tree.node()?;
+        if self.action.to_reverse() {
+            Some((&node.right, &node.left))
+        } else {
+            Some((&node.left, &node.right))
+        }
+    }
+
+    /// Returns the summary of a son, with all the actions above it applied.
+    fn son_summary(&self, son: &PersistentTree<D, T>) -> D::Summary {
+        self.action.act(son.subtree_summary())
+    }
+
+    fn node_summary(&self) -> Option<D::Summary> {
+        Some(self.action.act(self.tree.node()?.node_value.to_summary()))
+    }
+
+    fn value(&self) -> Option<D::Value>
+    where
+        D::Value: Clone,
+    {
+        Some(self.action.act(self.tree.node()?.node_value.clone()))
+    }
+
+    fn locate<L: Locator<D>>(&self, locator: &L) -> Option<LocResult>
+    where
+        D::Value: Clone,
+    {
+        let (left, right) = self.sons()?;
+        let left_summary = self.far_left_summary.clone() + self.son_summary(left);
+        let right_summary = self.son_summary(right) + self.far_right_summary.clone();
+        Some(locator.locate(&left_summary, &self.value()?, &right_summary))
+    }
+
+    /// Goes to the left son.
+    /// If at an empty position, returns [`None`].
+    /// Otherwise, returns the summary of the current node with its right subtree.
+    fn go_left(&mut self) -> Option<D::Summary> {
+        let (left, right) = self.sons()?;
+        let extra = self.node_summary()? + self.son_summary(right);
+        self.far_right_summary = extra.clone() + std::mem::take(&mut self.far_right_summary);
+        self.action = std::mem::take(&mut self.action) + left.action();
+        self.tree = left;
+        Some(extra)
+    }
+
+    /// Goes to the right son.
+    /// If at an empty position, returns [`None`].
+    /// Otherwise, returns the summary of the current node with its left subtree.
+    fn go_right(&mut self) -> Option<D::Summary> {
+        let (left, right) = self.sons()?;
+        let extra = self.son_summary(left) + self.node_summary()?;
+        self.far_left_summary = std::mem::take(&mut self.far_left_summary) + extra.clone();
+        self.action = std::mem::take(&mut self.action) + right.action();
+        self.tree = right;
+        Some(extra)
+    }
+}
+
+/// An iterator over the values of a [`PersistentTree`].
+/// The values are cloned, and all the actions above them are applied to them.
+pub struct Iter<'a, D: Data, T> {
+    // the nodes whose values are yet to be returned, along with all the actions
+    // that apply to them, including their own.
+    stack: Vec<(&'a PersistentNode<D, T>, D::Action)>,
+}
+
+impl<'a, D: Data, T> Iter<'a, D, T> {
+    fn push_left_spine(&mut self, mut tree: &'a PersistentTree<D, T>, mut action: D::Action) {
+        while let Some(node) = tree.node() {
+            action = action + node.action.clone();
+            tree = if action.to_reverse() {
+                &node.right
+            } else {
+                &node.left
+            };
+            self.stack.push((node, action.clone()));
+        }
+    }
+}
+
+impl<'a, D: Data, T> Iterator for Iter<'a, D, T>
+where
+    D::Value: Clone,
+{
+    type Item = D::Value;
+
+    fn next(&mut self) -> Option<D::Value> {
+        let (node, action) = self.stack.pop()?;
+        let right = if action.to_reverse() {
+            &node.left
+        } else {
+            &node.right
+        };
+        self.push_left_spine(right, action.clone());
+        Some(action.act(node.node_value.clone()))
+    }
+}
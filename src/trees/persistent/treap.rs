@@ -0,0 +1,348 @@
+//! A persistent version of the [`Treap`](crate::treap::Treap).
+//!
+//! All of the operations are implemented by splitting and concatenating the tree,
+//! so each operation copies `O(log n)` nodes in expectation, and the rest are shared with the
+//! previous versions of the tree.
+//!
+//! The priorities are drawn from a [`PrioritySource`], like in regular treaps.
+
+use super::*;
+use crate::treap::{PrioritySource, RngPriorities, ThreadRngPriorities};
+use locators::{LeftEdgeOf, RightEdgeOf};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+// The type that is used for bookkeeping.
+// convention: a bigger number should go higher up the tree.
+type T = u64;
+
+/// A persistent treap.
+/// Cloning a treap takes `O(1)` time, and gives an independent version of the tree,
+/// that shares all of its nodes with the original tree.
+/// The `P` parameter is the source of the priorities of the nodes.
+///```
+/// use grove::persistent::treap::PersistentTreap;
+/// use grove::example_data::{StdNum, RevAffineAction};
+///
+/// let mut tree: PersistentTreap<StdNum> = (0..10).collect();
+/// let snapshot = tree.clone();
+///
+/// tree.act_segment(RevAffineAction {to_reverse: true, mul: 1, add: 10}, 2..8);
+/// tree.delete(0).unwrap();
+///
+/// assert_eq!(tree.iter().collect::<Vec<_>>(), vec![1, 17, 16, 15, 14, 13, 12, 8, 9]);
+/// assert_eq!(snapshot.iter().collect::<Vec<_>>(), (0..10).collect::<Vec<_>>());
+/// assert_eq!(snapshot.segment_summary(2..8).sum, (2..8).sum());
+/// # tree.assert_correctness();
+/// # snapshot.assert_correctness();
+///```
+pub struct PersistentTreap<D: Data, P = ThreadRngPriorities> {
+    tree: PersistentTree<D, T>,
+    priorities: P,
+}
+
+// the default clone implementation requires that D: Clone, which is uneccessary
+impl<D: Data, P: Clone> Clone for PersistentTreap<D, P> {
+    fn clone(&self) -> Self {
+        PersistentTreap {
+            tree: self.tree.clone(),
+            priorities: self.priorities.clone(),
+        }
+    }
+}
+
+impl<D: Data> PersistentTreap<D> {
+    /// Creates an empty treap, that draws its priorities from [`rand::thread_rng`].
+    pub fn new() -> PersistentTreap<D> {
+        PersistentTreap::with_priority_source(ThreadRngPriorities)
+    }
+}
+
+impl<D: Data> PersistentTreap<D, RngPriorities<StdRng>> {
+    /// Creates an empty treap, that draws its priorities from a random number generator
+    /// seeded by `seed`. See [`Treap::with_seed`](crate::treap::Treap::with_seed).
+    pub fn with_seed(seed: u64) -> Self {
+        PersistentTreap::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<D: Data, R: RngCore + SeedableRng> PersistentTreap<D, RngPriorities<R>> {
+    /// Creates an empty treap, that draws its priorities from the given random number generator.
+    pub fn with_rng(rng: R) -> Self {
+        PersistentTreap::with_priority_source(RngPriorities(rng))
+    }
+}
+
+impl<D: Data, P> PersistentTreap<D, P> {
+    /// Creates an empty treap, that draws its priorities from the given source.
+    pub fn with_priority_source(priorities: P) -> Self {
+        PersistentTreap {
+            tree: PersistentTree::Empty,
+            priorities,
+        }
+    }
+
+    /// Returns the underlying persistent tree.
+    pub fn tree(&self) -> &PersistentTree<D, T> {
+        &self.tree
+    }
+
+    /// Returns true if the tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Returns the root's priority.
+    /// Returns [`None`] if the tree is empty.
+    pub fn priority(&self) -> Option<T> {
+        self.tree.priority()
+    }
+
+    /// Returns the summary of all values in this tree.
+    pub fn subtree_summary(&self) -> D::Summary {
+        self.tree.subtree_summary()
+    }
+
+    /// Asserts that the priorities maintain the priority invariant,
+    /// and that the summaries are correct.
+    /// Panics otherwise.
+    pub fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        self.tree.assert_correctness_with(|node| {
+            if let Some(left) = node.left.node() {
+                assert!(node.alg_data() > left.alg_data());
+            }
+            if let Some(right) = node.right.node() {
+                assert!(node.alg_data() > right.alg_data());
+            }
+            node.assert_correctness_locally();
+        });
+    }
+}
+
+impl<D: Data, P: PrioritySource> PersistentTreap<D, P>
+where
+    D::Value: Clone,
+{
+    /// Compute the summary of a subsegment.
+    /// This doesn't modify the tree, and doesn't copy any nodes.
+    pub fn segment_summary<L: Locator<D>>(&self, locator: L) -> D::Summary {
+        self.tree.segment_summary(locator)
+    }
+
+    /// Returns a value that the locator accepts, if there is one.
+    /// This doesn't modify the tree, and doesn't copy any nodes.
+    pub fn find<L: Locator<D>>(&self, locator: L) -> Option<D::Value> {
+        self.tree.find(locator)
+    }
+
+    /// Iterates over the values of the tree, by cloning them.
+    pub fn iter(&self) -> Iter<'_, D, T> {
+        self.tree.iter()
+    }
+
+    /// Apply an action on a subsegment.
+    /// Supports reversals.
+    /// Only the nodes that are copied by splitting the tree into the segment and
+    /// the parts around it are modified. Other versions of the tree are unaffected.
+    pub fn act_segment<L: Locator<D>>(&mut self, action: D::Action, locator: L) {
+        let tree = std::mem::take(&mut self.tree);
+        let (left, mut mid, right) = split_segment(tree, locator);
+        mid.act_subtree(action);
+        self.tree = concatenate(concatenate(left, mid), right);
+    }
+
+    /// Inserts a value at the empty position that the locator leads to.
+    /// Returns [`None`] if the locator accepts some value in the tree,
+    /// i.e., doesn't lead to an empty position.
+    /// Other versions of the tree are unaffected.
+    pub fn insert<L: Locator<D>>(&mut self, locator: L, value: D::Value) -> Option<()> {
+        if self.tree.find(locator.clone()).is_some() {
+            return None;
+        }
+        let tree = std::mem::take(&mut self.tree);
+        let (left, right) = split(
+            tree,
+            &LeftEdgeOf(locator),
+            Default::default(),
+            Default::default(),
+        );
+        let node = PersistentNode::new_alg(value, self.priorities.next_priority());
+        self.tree = concatenate(concatenate(left, PersistentTree::from_node(node)), right);
+        Some(())
+    }
+
+    /// Deletes a value that the locator accepts, and returns it.
+    /// Returns [`None`] if the locator doesn't accept any value in the tree.
+    /// Other versions of the tree are unaffected.
+    pub fn delete<L: Locator<D>>(&mut self, locator: L) -> Option<D::Value> {
+        let tree = std::mem::take(&mut self.tree);
+        let (left, mid, right) = split_segment(tree, locator);
+        let (mid, res) = match mid.into_node() {
+            None => (PersistentTree::Empty, None),
+            Some(mut node) => {
+                node.access();
+                (concatenate(node.left, node.right), Some(node.node_value))
+            }
+        };
+        self.tree = concatenate(concatenate(left, mid), right);
+        res
+    }
+
+    /// Concatenates the other tree to the right of this tree.
+    pub fn concatenate_right(&mut self, other: Self) {
+        let tree = std::mem::take(&mut self.tree);
+        self.tree = concatenate(tree, other.tree);
+    }
+
+    /// Splits the tree at the left edge of the locator's segment, and returns the right part.
+    pub fn split_right<L: Locator<D>>(&mut self, locator: L) -> Self {
+        let tree = std::mem::take(&mut self.tree);
+        let (left, right) = split(
+            tree,
+            &LeftEdgeOf(locator),
+            Default::default(),
+            Default::default(),
+        );
+        self.tree = left;
+        PersistentTreap {
+            tree: right,
+            priorities: self.priorities.split(),
+        }
+    }
+}
+
+impl<D: Data, P: PrioritySource> Default for PersistentTreap<D, P> {
+    fn default() -> Self {
+        PersistentTreap::with_priority_source(P::default())
+    }
+}
+
+impl<D: Data, P: PrioritySource> std::iter::FromIterator<D::Value> for PersistentTreap<D, P>
+where
+    D::Value: Clone,
+{
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let mut tree: PersistentTreap<D, P> = Default::default();
+        for value in iter {
+            let node = PersistentNode::new_alg(value, tree.priorities.next_priority());
+            let old = std::mem::take(&mut tree.tree);
+            tree.tree = concatenate(old, PersistentTree::from_node(node));
+        }
+        tree
+    }
+}
+
+impl<'a, D: Data, P> IntoIterator for &'a PersistentTreap<D, P>
+where
+    D::Value: Clone,
+{
+    type Item = D::Value;
+    type IntoIter = Iter<'a, D, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tree.iter()
+    }
+}
+
+impl<D: Data> PersistentTree<D, T> {
+    /// Returns the node's priority.
+    pub fn priority(&self) -> Option<T> {
+        Some(*self.node()?.alg_data())
+    }
+}
+
+/// Splits the tree at the position that the locator leads to.
+/// The locator should never accept a node, e.g., [`LeftEdgeOf`] and [`RightEdgeOf`].
+/// `far_left` and `far_right` are the summaries of the values to the left and right of the tree.
+fn split<D: Data, L: Locator<D>>(
+    tree: PersistentTree<D, T>,
+    locator: &L,
+    far_left: D::Summary,
+    far_right: D::Summary,
+) -> (PersistentTree<D, T>, PersistentTree<D, T>)
+where
+    D::Value: Clone,
+{
+    let mut node = match tree.into_node() {
+        None => return (PersistentTree::Empty, PersistentTree::Empty),
+        Some(node) => node,
+    };
+    node.access();
+    let left_summary = far_left.clone() + node.left.subtree_summary();
+    let right_summary = node.right.subtree_summary() + far_right.clone();
+    match locator.locate(&left_summary, &node.node_value, &right_summary) {
+        LocResult::GoRight => {
+            let far_left = left_summary + node.node_summary();
+            let (left, right) = split(node.right, locator, far_left, far_right);
+            node.right = left;
+            node.rebuild();
+            (PersistentTree::from_node(node), right)
+        }
+        LocResult::GoLeft => {
+            let far_right = node.node_summary() + right_summary;
+            let (left, right) = split(node.left, locator, far_left, far_right);
+            node.left = right;
+            node.rebuild();
+            (left, PersistentTree::from_node(node))
+        }
+        LocResult::Accept => panic!("splitting locators shouldn't accept nodes"),
+    }
+}
+
+/// Splits the tree into the part before the locator's segment, the segment,
+/// and the part after the segment.
+fn split_segment<D: Data, L: Locator<D>>(
+    tree: PersistentTree<D, T>,
+    locator: L,
+) -> (
+    PersistentTree<D, T>,
+    PersistentTree<D, T>,
+    PersistentTree<D, T>,
+)
+where
+    D::Value: Clone,
+{
+    let (left, rest) = split(
+        tree,
+        &LeftEdgeOf(locator.clone()),
+        Default::default(),
+        Default::default(),
+    );
+    let (mid, right) = split(
+        rest,
+        &RightEdgeOf(locator),
+        left.subtree_summary(),
+        Default::default(),
+    );
+    (left, mid, right)
+}
+
+/// Concatenates two trees, by merging their right and left spines according to the priorities.
+fn concatenate<D: Data>(
+    left: PersistentTree<D, T>,
+    right: PersistentTree<D, T>,
+) -> PersistentTree<D, T>
+where
+    D::Value: Clone,
+{
+    match (left.priority(), right.priority()) {
+        (None, _) => right,
+        (_, None) => left,
+        (Some(a), Some(b)) if a > b => {
+            let mut node = left.into_node().unwrap();
+            node.access();
+            node.right = concatenate(node.right, right);
+            node.rebuild();
+            PersistentTree::from_node(node)
+        }
+        _ => {
+            let mut node = right.into_node().unwrap();
+            node.access();
+            node.left = concatenate(left, node.left);
+            node.rebuild();
+            PersistentTree::from_node(node)
+        }
+    }
+}
@@ -0,0 +1,845 @@
+//! Implementation of red-black trees.
+//! Balanced by coloring every node red or black, this is a worst-case balancing
+//! algorithm that performs at most two rotations per insertion, and at most three
+//! rotations per deletion.
+//!
+//! The invariants are:
+//! * The root is black.
+//! * A red node never has a red son.
+//! * Every path from a node down to an empty position passes through the same number
+//!   of black nodes. This number is called the node's black height.
+
+use crate::locators;
+
+use super::basic_tree::*;
+use super::*;
+
+/// The color of a node in a red-black tree.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[allow(missing_docs)]
+pub enum Color {
+    Red,
+    Black,
+}
+use Color::*;
+
+/// The type that is used for color bookkeeping.
+type T = Color;
+
+/// A red-black tree. Balanced by coloring every node red or black, this is a worst-case
+/// balancing algorithm that needs very few rotations per insertion.
+pub struct RBTree<D: Data> {
+    tree: BasicTree<D, T>,
+}
+
+/// For reading and writing colors of trees and nodes alike.
+/// Empty trees are considered black.
+trait Colored {
+    fn color(&self) -> T;
+
+    /// Panics if used on an empty tree.
+    fn set_color(&mut self, color: T);
+
+    /// Returns the number of black nodes on any path from the root
+    /// down to an empty position. Takes `O(log n)` time.
+    fn black_height(&self) -> usize;
+}
+
+impl<D: Data> Colored for BasicTree<D, T> {
+    fn color(&self) -> T {
+        match self.node() {
+            None => Black,
+            Some(node) => node.alg_data,
+        }
+    }
+
+    fn set_color(&mut self, color: T) {
+        self.node_mut().expect("empty trees can't be recolored").alg_data = color;
+    }
+
+    fn black_height(&self) -> usize {
+        // Since the black heights are equal on every path, we can just go left.
+        // Pending reversals therefore don't matter.
+        let mut res = 0;
+        let mut tree = self;
+        while let Some(node) = tree.node() {
+            if node.alg_data == Black {
+                res += 1;
+            }
+            tree = &node.left;
+        }
+        res
+    }
+}
+
+impl<D: Data> RBTree<D> {
+    /// Creates an empty [`RBTree`].
+    pub fn new() -> Self {
+        RBTree {
+            tree: BasicTree::Empty,
+        }
+    }
+
+    /// Returns the number of black nodes on any path from the root
+    /// down to an empty position. Takes `O(log n)` time.
+    pub fn black_height(&self) -> usize {
+        self.tree.black_height()
+    }
+
+    /// Asserts that the colors at the current node are correct.
+    /// Otherwise, panics.
+    pub fn assert_colors_locally(&self) {
+        if let Some(node) = self.tree.node() {
+            Self::assert_colors_locally_internal(node);
+        }
+    }
+
+    fn assert_colors_locally_internal(node: &BasicNode<D, T>) {
+        if node.alg_data == Red {
+            assert!(node.left.color() == Black, "a red node has a red son");
+            assert!(node.right.color() == Black, "a red node has a red son");
+        }
+    }
+
+    /// Asserts that the tree's colors are correct, and that all paths
+    /// have the same black height.
+    /// Otherwise, panics.
+    pub fn assert_colors(&self) {
+        assert!(self.tree.color() == Black, "the root must be black");
+        Self::assert_black_heights(&self.tree);
+    }
+
+    /// Returns the black height, while checking that it is consistent on every path,
+    /// and that there are no two consecutive red nodes.
+    fn assert_black_heights(tree: &BasicTree<D, T>) -> usize {
+        match tree.node() {
+            None => 0,
+            Some(node) => {
+                Self::assert_colors_locally_internal(node);
+                let left = Self::assert_black_heights(&node.left);
+                let right = Self::assert_black_heights(&node.right);
+                assert!(left == right, "black heights don't match");
+                left + if node.alg_data == Black { 1 } else { 0 }
+            }
+        }
+    }
+}
+
+impl<D: Data> Default for RBTree<D> {
+    fn default() -> Self {
+        RBTree::new()
+    }
+}
+
+impl<D: Data> AsBasicTree<D> for RBTree<D> {
+    fn as_basic_tree(&self) -> &BasicTree<D, T> {
+        &self.tree
+    }
+}
+
+impl<D: Data> sealed::AsBasicTreeMut<D> for RBTree<D> {
+    type AlgData = T;
+
+    fn as_basic_tree_mut(&mut self, _: sealed::Token) -> &mut BasicTree<D, T> {
+        &mut self.tree
+    }
+}
+
+impl<D: Data> SomeTree<D> for RBTree<D> {
+    fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
+    where
+        L: crate::Locator<D>,
+        D::Value: Clone,
+    {
+        segment_algorithms::segment_summary_imm(&self.tree, locator)
+    }
+
+    fn segment_summary<L>(&mut self, locator: L) -> D::Summary
+    where
+        L: crate::Locator<D>,
+    {
+        segment_algorithms::segment_summary(self, locator)
+    }
+
+    fn act_segment<L>(&mut self, action: D::Action, locator: L)
+    where
+        L: crate::Locator<D>,
+    {
+        if !action.to_reverse() {
+            segment_algorithms::act_segment(self, action, locator)
+        } else {
+            // split out the middle
+            let mut mid: RBTree<D> = self
+                .slice(locators::LeftEdgeOf(locator.clone()))
+                .split_right()
+                .unwrap();
+
+            let mut walker2 = RBWalker {
+                walker: BasicWalker::new_with_context(
+                    &mut mid.tree,
+                    self.subtree_summary(),
+                    Default::default(),
+                ),
+            };
+            walker2.search_subtree(locators::RightEdgeOf(locator));
+            let right = walker2.split_right().unwrap();
+            drop(walker2);
+
+            // apply action
+            mid.act_subtree(action);
+
+            // glue back together
+            mid.concatenate_right(right);
+            self.concatenate_right(mid);
+        }
+    }
+
+    type TreeData = T;
+    fn iter_locator<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> basic_tree::iterators::IterLocator<'a, D, L, T> {
+        iterators::IterLocator::new(&mut self.tree, locator)
+    }
+
+    /// Checks that invariants remain correct. i.e., that every node's summary
+    /// is the sum of the summaries of its children, and that the colors are correct.
+    /// If it finds any violation, it panics.
+    fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        self.tree
+            .assert_correctness_with(|node| node.assert_correctness_locally());
+        self.assert_colors();
+    }
+}
+
+impl<'a, D: Data> SomeTreeRef<D> for &'a mut RBTree<D> {
+    type Walker = RBWalker<'a, D>;
+
+    fn walker(self) -> Self::Walker {
+        RBWalker {
+            walker: self.tree.walker(),
+        }
+    }
+}
+
+impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut RBTree<D> {
+    type ModifiableWalker = RBWalker<'a, D>;
+}
+
+impl<'a, D: Data> SplittableTreeRef<D> for &'a mut RBTree<D> {
+    type T = RBTree<D>;
+
+    type SplittableWalker = RBWalker<'a, D>;
+}
+
+derive_SomeEntry! {tree, T,
+    impl<D: Data> SomeEntry<D> for RBTree<D> {
+        fn assert_correctness_locally(&self)
+        where
+            D::Summary: Eq,
+        {
+            if let Some(node) = self.tree.node() {
+                Self::assert_colors_locally_internal(node);
+                node.assert_correctness_locally();
+            }
+        }
+    }
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for RBTree<D> {
+    /// This takes `O(n)` amortized time, since inserting at the right edge of the tree
+    /// takes `O(1)` amortized recolorings.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let mut tree: RBTree<D> = Default::default();
+        let mut walker = tree.walker();
+        for val in iter.into_iter() {
+            // note: this relies on the assumption, that after we insert a node, the new position of the walker
+            // will be an ancestor of the location where the value was inserted.
+            while walker.go_right().is_ok() {}
+            walker.insert(val);
+        }
+        drop(walker);
+        tree
+    }
+}
+
+impl<D: Data> IntoIterator for RBTree<D> {
+    type Item = D::Value;
+    type IntoIter = iterators::IntoIter<D, std::ops::RangeFull, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        iterators::IntoIter::new(self.tree, ..)
+    }
+}
+
+/// A walker struct for [`RBTree`].
+pub struct RBWalker<'a, D: Data> {
+    walker: BasicWalker<'a, D, T>,
+}
+
+derive_SomeWalker! {walker,
+    impl<'a, D: Data> SomeWalker<D> for RBWalker<'a, D> {
+        fn go_up(&mut self) -> Result<Side, ()> {
+            self.walker.go_up()
+        }
+    }
+}
+
+derive_SomeEntry! {walker, T,
+    impl<'a, D: Data> SomeEntry<D> for RBWalker<'a, D> {
+        fn assert_correctness_locally(&self)
+        where
+            D::Summary: Eq,
+        {
+            self.walker.assert_correctness_locally();
+            if let Some(node) = self.walker.node() {
+                RBTree::assert_colors_locally_internal(node);
+            }
+        }
+    }
+}
+
+impl<'a, D: Data> RBWalker<'a, D> {
+    /// Returns the color of the current node.
+    /// Empty positions are considered black.
+    pub fn color(&self) -> Color {
+        self.inner().color()
+    }
+
+    fn inner(&self) -> &BasicTree<D, T> {
+        self.walker.inner()
+    }
+
+    fn inner_mut(&mut self) -> &mut BasicTree<D, T> {
+        self.walker.inner_mut()
+    }
+
+    /// Returns the current node's son at the given side.
+    /// Panics if at an empty position.
+    fn son_mut(&mut self, side: Side) -> &mut BasicTree<D, T> {
+        let node = self.walker.node_mut().unwrap();
+        match side {
+            Side::Left => &mut node.left,
+            Side::Right => &mut node.right,
+        }
+    }
+
+    fn go_side(&mut self, side: Side) -> Result<(), ()> {
+        match side {
+            Side::Left => self.walker.go_left(),
+            Side::Right => self.walker.go_right(),
+        }
+    }
+
+    /// This function gets called when the current node is red, and its father might also be red.
+    /// Restores the invariants by recoloring and rotating upwards.
+    /// During rebalancing it may only go up the tree.
+    ///
+    /// Returns `true` if the black height of the whole tree grew by one.
+    fn fix_red(&mut self) -> bool {
+        loop {
+            let side = match self.walker.go_up() {
+                Err(()) => {
+                    // the red node is the root: just recolor it.
+                    self.inner_mut().set_color(Black);
+                    return true;
+                }
+                Ok(side) => side,
+            };
+            if self.color() == Black {
+                return false;
+            }
+
+            // the father is red, and therefore isn't the root.
+            let father_side = self
+                .walker
+                .go_up()
+                .expect("a red node can't be the root");
+            // at the grandfather, which must be black
+            if self.son_mut(father_side.flip()).color() == Red {
+                // the uncle is red: recolor, and continue upwards from the grandfather.
+                self.son_mut(father_side.flip()).set_color(Black);
+                self.son_mut(father_side).set_color(Black);
+                self.inner_mut().set_color(Red);
+                continue;
+            }
+
+            if side != father_side {
+                // zig-zag case: reduce to the zig-zig case
+                self.go_side(father_side).unwrap();
+                self.walker.rot_side(father_side).unwrap();
+                self.walker.go_up().unwrap();
+            }
+            // zig-zig case
+            self.walker.rot_side(father_side.flip()).unwrap();
+            self.inner_mut().set_color(Black);
+            self.son_mut(father_side.flip()).set_color(Red);
+            return false;
+        }
+    }
+
+    /// This function gets called when the current subtree's black height
+    /// is one less than it should be, after a black node has been removed from it.
+    /// Restores the invariants by recoloring and rotating upwards.
+    /// During rebalancing it may only go up the tree.
+    ///
+    /// Returns `true` if the black height of the whole tree shrank by one.
+    fn fix_double_black(&mut self) -> bool {
+        loop {
+            if self.color() == Red {
+                self.inner_mut().set_color(Black);
+                return false;
+            }
+            let side = match self.walker.go_up() {
+                Err(()) => return true,
+                Ok(side) => side,
+            };
+
+            // the sibling must be nonempty, since its black height is at least one.
+            if self.son_mut(side.flip()).color() == Red {
+                // red sibling case: rotate the sibling up, then the new sibling is black.
+                self.walker.rot_side(side).unwrap();
+                self.inner_mut().set_color(Black);
+                self.go_side(side).unwrap();
+                self.inner_mut().set_color(Red);
+            }
+
+            // clean the sibling, so that its sons are on the correct sides.
+            let sibling = self.son_mut(side.flip());
+            sibling.access();
+            let sibling_node = sibling.node().expect("the sibling can't be empty");
+            let (near, far) = match side {
+                Side::Left => (&sibling_node.left, &sibling_node.right),
+                Side::Right => (&sibling_node.right, &sibling_node.left),
+            };
+            let (near, far) = (near.color(), far.color());
+
+            if near == Black && far == Black {
+                // move the deficiency upwards.
+                self.son_mut(side.flip()).set_color(Red);
+                continue;
+            }
+
+            if far == Black {
+                // the near nephew is red: reduce to the case that the far nephew is red.
+                self.go_side(side.flip()).unwrap();
+                self.walker.rot_side(side.flip()).unwrap();
+                self.inner_mut().set_color(Black);
+                self.son_mut(side.flip()).set_color(Red);
+                self.walker.go_up().unwrap();
+            }
+
+            // the far nephew is red.
+            let color = self.color();
+            self.walker.rot_side(side).unwrap();
+            self.inner_mut().set_color(color);
+            self.son_mut(side).set_color(Black);
+            self.son_mut(side.flip()).set_color(Black);
+            return false;
+        }
+    }
+
+    /// Deletes a node and returns it with the box.
+    /// The walker reorganizes the current subtree in order to delete the current node,
+    /// and then rebalances. During rebalancing it may only go up the tree.
+    fn delete_boxed(&mut self) -> Option<Box<BasicNode<D, T>>> {
+        let node = self.walker.node()?;
+        if node.left.is_empty() || node.right.is_empty() {
+            let mut node = self.walker.take_subtree().into_node_boxed().unwrap();
+            // since one of the sons is empty, the other is either empty or a single red node.
+            let son = if node.left.is_empty() {
+                std::mem::replace(&mut node.right, BasicTree::Empty)
+            } else {
+                std::mem::replace(&mut node.left, BasicTree::Empty)
+            };
+            self.walker.put_subtree(son).unwrap();
+            if node.alg_data == Black {
+                self.fix_double_black();
+            }
+            return Some(node);
+        }
+
+        // find the next node and take it out of its place
+        let depth = self.depth();
+        self.walker.go_right().unwrap();
+        while self.walker.go_left().is_ok() {}
+        let res = self.walker.go_up();
+        assert_eq!(res, Ok(Side::Left));
+        let steps = self.depth() - depth;
+
+        let mut replacement = self.walker.take_subtree().into_node_boxed().unwrap();
+        assert!(replacement.left.is_empty());
+        let son = std::mem::replace(&mut replacement.right, BasicTree::Empty);
+        self.walker.put_subtree(son).unwrap();
+
+        // move the next node to the deleted node's position
+        for _ in 0..steps {
+            self.walker.go_up().unwrap();
+        }
+        let mut node = self.walker.take_subtree().into_node_boxed().unwrap();
+        replacement.left = std::mem::replace(&mut node.left, BasicTree::Empty);
+        replacement.right = std::mem::replace(&mut node.right, BasicTree::Empty);
+        let replacement_color = std::mem::replace(&mut replacement.alg_data, node.alg_data);
+        replacement.rebuild();
+        self.walker
+            .put_subtree(BasicTree::from_boxed_node(replacement))
+            .unwrap();
+
+        // go back to where the next node was, and rebalance from there.
+        self.walker.go_right().unwrap();
+        for _ in 1..steps {
+            self.walker.go_left().unwrap();
+        }
+        if replacement_color == Black {
+            self.fix_double_black();
+        }
+        Some(node)
+    }
+}
+
+impl<'a, D: Data> ModifiableWalker<D> for RBWalker<'a, D> {
+    /// Inserts the value into the tree at the current empty position.
+    /// If the current position is not empty, return [`None`].
+    /// When the function returns, the walker will be at a position which is an ancestor of the
+    /// newly inserted node.
+    fn insert(&mut self, val: D::Value) -> Option<()> {
+        self.walker.insert_with_alg_data(val, Red)?;
+        self.fix_red();
+        Some(())
+    }
+
+    /// The walker reorganizes the current subtree in order to delete the current node,
+    /// and then rebalances. During rebalancing it may only go up the tree.
+    fn delete(&mut self) -> Option<D::Value> {
+        Some(self.delete_boxed()?.node_value)
+    }
+}
+
+impl<'a, D: Data> SplittableWalker<D> for RBWalker<'a, D> {
+    type T = RBTree<D>;
+
+    /// Will only do anything if the current position is empty.
+    /// If it is empty, it will split the tree: the elements
+    /// to the left will remain, and the elements to the right
+    /// will be put in the new output tree.
+    /// The walker will be at the root after this operation, if it succeeds.
+    ///
+    ///```
+    /// use grove::{SomeTree, red_black::RBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: RBTree<StdNum> = (17..88).collect();
+    /// let mut tree2 = tree.slice(7..7).split_right().unwrap();
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..24).collect::<Vec<_>>());
+    /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (24..88).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    /// # tree2.assert_correctness();
+    ///```
+    fn split_right(&mut self) -> Option<Self::T> {
+        if !self.is_empty() {
+            return None;
+        }
+        let mut left_t = RBTree::new();
+        let mut right_t = RBTree::new();
+        let mut left = left_t.walker();
+        let mut right = right_t.walker();
+        let mut left_bh = 0;
+        let mut right_bh = 0;
+        // the black height of the subtree at the current position, before it was emptied.
+        let mut current_bh = 0;
+
+        while let Ok(side) = self.walker.go_up() {
+            // `node.action` is the identity, since we just moved up.
+            let mut node = self.walker.take_subtree().into_node_boxed().unwrap();
+            let is_black = node.alg_data == Black;
+            // the sibling has the same black height as the subtree we came from.
+            match side {
+                Side::Left => {
+                    assert!(node.left.is_empty());
+                    let auxiliary_right = RBTree {
+                        tree: std::mem::replace(&mut node.right, BasicTree::Empty),
+                    };
+                    right_bh = RBTree::concatenate_boxed_middle_right(
+                        &mut right,
+                        right_bh,
+                        node,
+                        auxiliary_right,
+                        current_bh,
+                    );
+                }
+                Side::Right => {
+                    assert!(node.right.is_empty());
+                    let auxiliary_left = RBTree {
+                        tree: std::mem::replace(&mut node.left, BasicTree::Empty),
+                    };
+                    left_bh = RBTree::concatenate_boxed_middle_left(
+                        &mut left,
+                        left_bh,
+                        auxiliary_left,
+                        current_bh,
+                        node,
+                    );
+                }
+            }
+            if is_black {
+                current_bh += 1;
+            }
+        }
+
+        // Drop the walkers so that we can access the `left, right` trees themselves.
+        std::mem::drop(left);
+        std::mem::drop(right);
+        // the `self` tree is empty by this point.
+        self.walker.put_subtree(left_t.tree).unwrap();
+        Some(right_t)
+    }
+
+    /// Will only do anything if the current position is empty.
+    /// If it is empty, it will split the tree: the elements
+    /// to the left will remain, and the elements to the right
+    /// will be put in the new output tree.
+    /// The walker will be at the root after this operation, if it succeeds.
+    ///
+    ///```
+    /// use grove::{SomeTree, red_black::RBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: RBTree<StdNum> = (17..88).collect();
+    /// let mut tree2 = tree.slice(7..7).split_left().unwrap();
+    ///
+    /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (17..24).collect::<Vec<_>>());
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (24..88).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    /// # tree2.assert_correctness();
+    ///```
+    fn split_left(&mut self) -> Option<Self::T> {
+        let mut right = self.split_right()?;
+        std::mem::swap(&mut right.tree, self.inner_mut());
+        Some(right)
+    }
+}
+
+impl<D: Data> RBTree<D> {
+    /// Concatenates the trees together, in place, with a given value for the middle.
+    /// Complexity: `O(log n)`.
+    ///```
+    /// use grove::{SomeTree, red_black::RBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: RBTree<StdNum> = (17..=89).collect();
+    /// let tree2: RBTree<StdNum> = (13..=25).collect();
+    /// tree.concatenate_middle_right(5, tree2);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(5..=5).chain(13..=25).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    pub fn concatenate_middle_right(&mut self, mid: D::Value, right: RBTree<D>) {
+        let left_bh = self.black_height();
+        let right_bh = right.black_height();
+        let node = BasicNode::new_alg(mid, Red);
+        Self::concatenate_boxed_middle_right(
+            &mut self.walker(),
+            left_bh,
+            Box::new(node),
+            right,
+            right_bh,
+        );
+    }
+
+    /// Concatenates `left`, `mid` and `right` into the walker's tree.
+    /// The walker must be at its root, and the black heights of the trees must be given.
+    /// The trees' roots are allowed to be red.
+    /// Returns the black height of the resulting tree.
+    /// Complexity: `O(dh + 1)` where `dh` is the difference of black heights between the two trees.
+    fn concatenate_boxed_middle_right(
+        left: &mut RBWalker<D>,
+        mut left_bh: usize,
+        mut mid: Box<BasicNode<D, T>>,
+        mut right: RBTree<D>,
+        mut right_bh: usize,
+    ) -> usize {
+        // The walker must be at its root
+        assert!(left.depth() == 0);
+        assert!(mid.action().is_identity());
+        // make sure both roots are black
+        if left.color() == Red {
+            left.inner_mut().set_color(Black);
+            left_bh += 1;
+        }
+        if right.tree.color() == Red {
+            right.tree.set_color(Black);
+            right_bh += 1;
+        }
+
+        if left_bh < right_bh {
+            std::mem::swap(left.inner_mut(), &mut right.tree);
+            // `right.tree` might still have an action in it, but walkers aren't allowed to.
+            left.inner_mut().access();
+            return Self::concatenate_boxed_middle_left(left, right_bh, right, left_bh, mid);
+        }
+
+        // go down the right spine, until reaching a black subtree of the right black height.
+        let walker = left;
+        let mut current_bh = left_bh;
+        while current_bh > right_bh || walker.color() == Red {
+            if walker.color() == Black {
+                current_bh -= 1;
+            }
+            walker.go_right().unwrap();
+        }
+        mid.alg_data = Red;
+        mid.left = walker.walker.take_subtree();
+        mid.right = right.tree;
+        mid.rebuild();
+        walker
+            .walker
+            .put_subtree(BasicTree::from_boxed_node(mid))
+            .unwrap();
+        let grew = walker.fix_red();
+        walker.go_to_root();
+        if grew {
+            left_bh + 1
+        } else {
+            left_bh
+        }
+    }
+
+    /// Concatenates the trees together, in place, with a given value for the middle.
+    /// Complexity: `O(log n)`.
+    ///```
+    /// use grove::{SomeTree, red_black::RBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let tree1: RBTree<StdNum> = (17..=89).collect();
+    /// let mut tree2: RBTree<StdNum> = (13..=25).collect();
+    /// tree2.concatenate_middle_left(tree1, 5);
+    ///
+    /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(5..=5).chain(13..=25).collect::<Vec<_>>());
+    /// # tree2.assert_correctness();
+    ///```
+    pub fn concatenate_middle_left(&mut self, left: RBTree<D>, mid: D::Value) {
+        let left_bh = left.black_height();
+        let right_bh = self.black_height();
+        let node = BasicNode::new_alg(mid, Red);
+        Self::concatenate_boxed_middle_left(
+            &mut self.walker(),
+            right_bh,
+            left,
+            left_bh,
+            Box::new(node),
+        );
+    }
+
+    /// Concatenates `left`, `mid` and `right` into the walker's tree.
+    /// The walker must be at its root, and the black heights of the trees must be given.
+    /// The trees' roots are allowed to be red.
+    /// Returns the black height of the resulting tree.
+    /// Complexity: `O(dh + 1)` where `dh` is the difference of black heights between the two trees.
+    fn concatenate_boxed_middle_left(
+        right: &mut RBWalker<D>,
+        mut right_bh: usize,
+        mut left: RBTree<D>,
+        mut left_bh: usize,
+        mut mid: Box<BasicNode<D, T>>,
+    ) -> usize {
+        // The walker must be at its root
+        assert!(right.depth() == 0);
+        assert!(mid.action().is_identity());
+        // make sure both roots are black
+        if right.color() == Red {
+            right.inner_mut().set_color(Black);
+            right_bh += 1;
+        }
+        if left.tree.color() == Red {
+            left.tree.set_color(Black);
+            left_bh += 1;
+        }
+
+        if right_bh < left_bh {
+            std::mem::swap(right.inner_mut(), &mut left.tree);
+            // `left.tree` might still have an action in it, but walkers aren't allowed to.
+            right.inner_mut().access();
+            return Self::concatenate_boxed_middle_right(right, left_bh, mid, left, right_bh);
+        }
+
+        // go down the left spine, until reaching a black subtree of the right black height.
+        let walker = right;
+        let mut current_bh = right_bh;
+        while current_bh > left_bh || walker.color() == Red {
+            if walker.color() == Black {
+                current_bh -= 1;
+            }
+            walker.go_left().unwrap();
+        }
+        mid.alg_data = Red;
+        mid.right = walker.walker.take_subtree();
+        mid.left = left.tree;
+        mid.rebuild();
+        walker
+            .walker
+            .put_subtree(BasicTree::from_boxed_node(mid))
+            .unwrap();
+        let grew = walker.fix_red();
+        walker.go_to_root();
+        if grew {
+            right_bh + 1
+        } else {
+            right_bh
+        }
+    }
+}
+
+impl<D: Data> ConcatenableTree<D> for RBTree<D> {
+    /// Concatenates the trees together, in place.
+    /// Complexity: `O(log n)`.
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, red_black::RBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: RBTree<StdNum> = (17..=89).collect();
+    /// let tree2: RBTree<StdNum> = (13..=25).collect();
+    /// tree.concatenate_right(tree2);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(13..=25).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn concatenate_right(&mut self, right: Self) {
+        if !self.is_empty() {
+            let mut walker = self.search(locators::RightEdgeOf(..));
+            walker.go_up().unwrap();
+            let mid = walker.delete_boxed().unwrap();
+            walker.go_to_root();
+            let left_bh = walker.inner().black_height();
+            let right_bh = right.black_height();
+            Self::concatenate_boxed_middle_right(&mut walker, left_bh, mid, right, right_bh);
+        } else {
+            self.tree = right.tree;
+        }
+    }
+}
+
+/// Concatenates the trees together, in place, with a given value for the middle.
+/// Complexity: `O(log n)`.
+///```
+/// use grove::{SomeTree, red_black::RBTree, red_black::concatenate_with_middle};
+/// use grove::example_data::StdNum;
+///
+/// let tree1: RBTree<StdNum> = (17..=89).collect();
+/// let tree2: RBTree<StdNum> = (13..=25).collect();
+/// let mut tree3 = concatenate_with_middle(tree1, 5, tree2);
+///
+/// assert_eq!(tree3.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(5..=5).chain(13..=25).collect::<Vec<_>>());
+/// # tree3.assert_correctness();
+///```
+pub fn concatenate_with_middle<D: Data>(
+    mut left: RBTree<D>,
+    mid: D::Value,
+    right: RBTree<D>,
+) -> RBTree<D> {
+    left.concatenate_middle_right(mid, right);
+    left
+}
@@ -0,0 +1,362 @@
+//! Implementation of scapegoat trees.
+//!
+//! It is a balanced tree algorithm that keeps no bookkeeping data in its nodes at all.
+//! Instead, the tree only remembers its size, and whenever a node ends up too deep,
+//! it finds an unbalanced ancestor of that node (the "scapegoat") and rebuilds that
+//! ancestor's whole subtree into a perfectly balanced tree.
+//!
+//! Its modifying operations take `O(log n)` amortized time, and its queries take
+//! `O(log n)` worst-case time, since the tree's depth is always logarithmic.
+//!
+//! Since the nodes don't have balancing data, finding the scapegoat requires computing the sizes of
+//! subtrees. By default, this is done by counting the nodes. If the summary type already keeps
+//! track of the number of nodes, use [`ScapegoatTree::with_summary_sizes`] to read the sizes
+//! from the summaries instead.
+//!
+//! Scapegoat trees don't support splitting and concatenation, and therefore don't support
+//! reversals of segments: [`SomeTree::act_segment`] panics if it is given a reversing action.
+//! Reversing a whole tree with [`SomeEntry::act_subtree`] is still supported.
+
+use super::basic_tree::*;
+use super::*;
+
+/// The balance parameter `alpha` of the tree, as the fraction
+/// `ALPHA_NUMERATOR / ALPHA_DENOMINATOR`. A son may hold at most an `alpha` fraction
+/// of its parent's nodes before the parent counts as unbalanced.
+const ALPHA_NUMERATOR: usize = 2;
+const ALPHA_DENOMINATOR: usize = 3;
+
+/// A function computing the number of nodes in a subtree.
+type SizeFn<D> = fn(&BasicTree<D>) -> usize;
+
+/// A scapegoat tree. Keeps no balancing data in its nodes, and rebalances
+/// by rebuilding whole subtrees.
+pub struct ScapegoatTree<D: Data> {
+    tree: BasicTree<D>,
+    /// The number of nodes in the tree.
+    size: usize,
+    /// The maximal size the tree had since it was last completely rebuilt.
+    max_size: usize,
+    subtree_size: SizeFn<D>,
+}
+
+/// The maximal depth a node may have in a tree of size `size`, which is `log_{1/alpha}(size)`.
+fn depth_bound(size: usize) -> usize {
+    if size <= 1 {
+        return 0;
+    }
+    let base = ALPHA_DENOMINATOR as f64 / ALPHA_NUMERATOR as f64;
+    ((size as f64).ln() / base.ln()).floor() as usize
+}
+
+/// Counts the nodes of the tree. Takes `O(n)` time.
+fn count_nodes<D: Data>(tree: &BasicTree<D>) -> usize {
+    let mut count = 0;
+    let mut stack = vec![tree];
+    while let Some(tree) = stack.pop() {
+        if let Some(node) = tree.node() {
+            count += 1;
+            stack.push(&node.left);
+            stack.push(&node.right);
+        }
+    }
+    count
+}
+
+/// Reads the size of the tree from its summary. Takes `O(1)` time.
+fn summary_size<D: Data>(tree: &BasicTree<D>) -> usize
+where
+    D::Summary: SizedSummary,
+{
+    tree.subtree_summary().size()
+}
+
+/// Rebuilds the given tree into a perfectly balanced tree with the same values.
+/// Takes `O(n)` time.
+fn rebuild_balanced<D: Data>(tree: &mut BasicTree<D>) {
+    // collect the nodes in order, pushing down all of the actions on the way
+    let mut nodes: Vec<Box<BasicNode<D>>> = vec![];
+    let mut stack: Vec<Box<BasicNode<D>>> = vec![];
+    let mut current = std::mem::replace(tree, BasicTree::Empty);
+    loop {
+        if let Some(mut node) = current.into_node_boxed() {
+            node.access();
+            current = std::mem::replace(&mut node.left, BasicTree::Empty);
+            stack.push(node);
+        } else if let Some(mut node) = stack.pop() {
+            current = std::mem::replace(&mut node.right, BasicTree::Empty);
+            nodes.push(node);
+        } else {
+            break;
+        }
+    }
+
+    let count = nodes.len();
+    *tree = build_balanced(&mut nodes.into_iter(), count);
+}
+
+/// Builds a perfectly balanced tree out of the next `count` nodes of the iterator.
+/// The nodes must not have any sons or unapplied actions.
+fn build_balanced<D: Data, I>(nodes: &mut I, count: usize) -> BasicTree<D>
+where
+    I: Iterator<Item = Box<BasicNode<D>>>,
+{
+    if count == 0 {
+        return BasicTree::Empty;
+    }
+    let left_count = count / 2;
+    let left = build_balanced(nodes, left_count);
+    let mut node = nodes.next().expect("Not enough nodes");
+    node.left = left;
+    node.right = build_balanced(nodes, count - left_count - 1);
+    node.rebuild();
+    BasicTree::from_boxed_node(node)
+}
+
+impl<D: Data> ScapegoatTree<D> {
+    /// Creates an empty [`ScapegoatTree`].
+    pub fn new() -> Self {
+        ScapegoatTree {
+            tree: BasicTree::Empty,
+            size: 0,
+            max_size: 0,
+            subtree_size: count_nodes,
+        }
+    }
+
+    /// Makes the tree read the sizes of subtrees from their summaries, instead of
+    /// counting their nodes. This makes rebalancing faster by a constant factor.
+    ///
+    /// Only use this if the summary's size counts the values in the segment, i.e.,
+    /// if every value's summary has size `1`, as in [`example_data::Size`].
+    /// Otherwise, the tree will still behave correctly, but it might become unbalanced.
+    ///```
+    /// use grove::{*, scapegoat::ScapegoatTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: ScapegoatTree<StdNum> = ScapegoatTree::new().with_summary_sizes();
+    /// for i in 0..100 {
+    ///     tree.slice(i..i).insert(i as i32).unwrap();
+    /// }
+    /// # tree.assert_correctness();
+    ///
+    /// assert_eq!(tree.segment_summary(..).sum, (0..100).sum());
+    /// # tree.assert_correctness();
+    ///```
+    pub fn with_summary_sizes(mut self) -> Self
+    where
+        D::Summary: SizedSummary,
+    {
+        self.subtree_size = summary_size;
+        self
+    }
+
+    /// Asserts that the tree's depth and size are correct.
+    /// Otherwise, panics.
+    pub fn assert_balance(&self) {
+        assert_eq!(self.size, count_nodes(&self.tree));
+        assert!(self.size <= self.max_size);
+        let mut stack = vec![(&self.tree, 0)];
+        while let Some((tree, depth)) = stack.pop() {
+            if let Some(node) = tree.node() {
+                assert!(depth <= depth_bound(self.max_size) + 1);
+                stack.push((&node.left, depth + 1));
+                stack.push((&node.right, depth + 1));
+            }
+        }
+    }
+}
+
+impl<D: Data> Default for ScapegoatTree<D> {
+    fn default() -> Self {
+        ScapegoatTree::new()
+    }
+}
+
+impl<D: Data> AsBasicTree<D> for ScapegoatTree<D> {
+    fn as_basic_tree(&self) -> &BasicTree<D, ()> {
+        &self.tree
+    }
+}
+
+impl<D: Data> sealed::AsBasicTreeMut<D> for ScapegoatTree<D> {
+    type AlgData = ();
+
+    fn as_basic_tree_mut(&mut self, _: sealed::Token) -> &mut BasicTree<D, ()> {
+        &mut self.tree
+    }
+}
+
+impl<D: Data> SomeTree<D> for ScapegoatTree<D> {
+    fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
+    where
+        L: crate::Locator<D>,
+        D::Value: Clone,
+    {
+        segment_algorithms::segment_summary_imm(&self.tree, locator)
+    }
+
+    fn segment_summary<L>(&mut self, locator: L) -> D::Summary
+    where
+        L: crate::Locator<D>,
+    {
+        segment_algorithms::segment_summary(self, locator)
+    }
+
+    /// Applies the action on the segment.
+    /// Panics if the action reverses segments, since scapegoat trees
+    /// can't split and concatenate efficiently.
+    fn act_segment<L>(&mut self, action: D::Action, locator: L)
+    where
+        L: crate::Locator<D>,
+    {
+        segment_algorithms::act_segment(self, action, locator)
+    }
+
+    type TreeData = ();
+    fn iter_locator<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> basic_tree::iterators::IterLocator<'a, D, L> {
+        iterators::IterLocator::new(&mut self.tree, locator)
+    }
+
+    fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        self.tree.assert_correctness();
+        self.assert_balance();
+    }
+}
+
+impl<'a, D: Data> SomeTreeRef<D> for &'a mut ScapegoatTree<D> {
+    type Walker = ScapegoatWalker<'a, D>;
+
+    fn walker(self) -> Self::Walker {
+        ScapegoatWalker {
+            walker: self.tree.walker(),
+            size: &mut self.size,
+            max_size: &mut self.max_size,
+            subtree_size: self.subtree_size,
+        }
+    }
+}
+
+impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut ScapegoatTree<D> {
+    type ModifiableWalker = ScapegoatWalker<'a, D>;
+}
+
+derive_SomeEntry! {tree, (),
+    impl<D: Data> SomeEntry<D> for ScapegoatTree<D> {
+        fn assert_correctness_locally(&self)
+        where
+            D::Summary: Eq,
+        {
+            self.tree.assert_correctness_locally();
+        }
+    }
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for ScapegoatTree<D> {
+    /// This takes `O(n)` worst-case time.
+    fn from_iter<T: IntoIterator<Item = D::Value>>(iter: T) -> Self {
+        let mut size = 0;
+        let tree: BasicTree<D> = iter.into_iter().inspect(|_| size += 1).collect();
+        ScapegoatTree {
+            tree,
+            size,
+            max_size: size,
+            subtree_size: count_nodes,
+        }
+    }
+}
+
+impl<D: Data> IntoIterator for ScapegoatTree<D> {
+    type Item = D::Value;
+    type IntoIter = iterators::IntoIter<D, std::ops::RangeFull>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        iterators::IntoIter::new(self.tree, ..)
+    }
+}
+
+/// A walker struct for [`ScapegoatTree`].
+pub struct ScapegoatWalker<'a, D: Data> {
+    walker: BasicWalker<'a, D>,
+    size: &'a mut usize,
+    max_size: &'a mut usize,
+    subtree_size: SizeFn<D>,
+}
+
+derive_SomeWalker! {walker,
+    impl<'a, D: Data> SomeWalker<D> for ScapegoatWalker<'a, D> {
+        fn go_up(&mut self) -> Result<Side, ()> {
+            self.walker.go_up()
+        }
+    }
+}
+
+derive_SomeEntry! {walker, (),
+    impl<'a, D: Data> SomeEntry<D> for ScapegoatWalker<'a, D> {
+        fn assert_correctness_locally(&self)
+        where
+            D::Summary: Eq,
+        {
+            self.walker.assert_correctness_locally();
+        }
+    }
+}
+
+impl<'a, D: Data> ScapegoatWalker<'a, D> {
+    /// Called after inserting a node at the current position, that is too deep.
+    /// Goes up until finding an unbalanced ancestor, and rebuilds its subtree.
+    /// The walker ends up at the root of the rebuilt subtree.
+    fn rebuild_scapegoat(&mut self) {
+        let mut son_size = (self.subtree_size)(self.walker.inner());
+        while let Ok(side) = self.walker.go_up() {
+            let node = self.walker.node().unwrap();
+            let sibling = match side {
+                Side::Left => &node.right,
+                Side::Right => &node.left,
+            };
+            let size = son_size + (self.subtree_size)(sibling) + 1;
+            if ALPHA_DENOMINATOR * son_size > ALPHA_NUMERATOR * size {
+                rebuild_balanced(self.walker.inner_mut());
+                return;
+            }
+            son_size = size;
+        }
+    }
+}
+
+impl<'a, D: Data> ModifiableWalker<D> for ScapegoatWalker<'a, D> {
+    /// Inserts the value into the tree at the current empty position.
+    /// If the current position is not empty, return [`None`].
+    /// When the function returns, the walker will be at the position the node
+    /// was inserted, or at an ancestor of it, if the tree had to be rebalanced.
+    fn insert(&mut self, value: D::Value) -> Option<()> {
+        self.walker.insert_with_alg_data(value, ())?;
+        *self.size += 1;
+        *self.max_size = std::cmp::max(*self.max_size, *self.size);
+        if self.walker.depth() > depth_bound(*self.size) {
+            self.rebuild_scapegoat();
+        }
+        Some(())
+    }
+
+    /// Removes the current value from the tree, and returns it.
+    /// If currently at an empty position, returns [`None`].
+    /// After deletion, the walker will stay at the same position, unless the
+    /// whole tree had to be rebuilt, in which case it will be at the root.
+    fn delete(&mut self) -> Option<D::Value> {
+        let (value, ()) = self.walker.delete_with_alg_data()?;
+        *self.size -= 1;
+        if ALPHA_DENOMINATOR * *self.size < ALPHA_NUMERATOR * *self.max_size {
+            self.walker.go_to_root();
+            rebuild_balanced(self.walker.inner_mut());
+            *self.max_size = *self.size;
+        }
+        Some(value)
+    }
+}
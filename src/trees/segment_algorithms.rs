@@ -1,5 +1,8 @@
-//! This file contains algorithms for computing the summary of a segment
-//! and for applying an action on a segment.
+//! This file contains algorithms for computing the summary of a segment,
+//! for applying an action on a segment, for binary searching
+//! over the summaries of segments that start or end at a given index,
+//! for visiting or acting on the values that match a [`SubtreeFilter`],
+//! and for changing or removing the values of a segment.
 //!
 //! Since different balanced tree algorithms are different, the generic functions
 //! may not work as intended.
@@ -123,10 +126,10 @@ where
 
             // at this point, we split into the two sides
             Accept => {
-                walker.act_node(action);
+                walker.act_node(action.clone());
                 let depth = walker.depth();
                 walker.go_left().unwrap();
-                act_on_suffix(&mut walker, action, locator.clone());
+                act_on_suffix(&mut walker, action.clone(), locator.clone());
                 // get back to the original node
                 for _ in 0..walker.depth() - depth {
                     walker.go_up().unwrap();
@@ -150,8 +153,8 @@ where
     while let Some(dir) = query_locator(walker, &locator) {
         match dir {
             Accept => {
-                walker.act_node(action);
-                walker.act_right_subtree(action).unwrap();
+                walker.act_node(action.clone());
+                walker.act_right_subtree(action.clone()).unwrap();
                 walker.go_left().unwrap();
             }
             GoRight => walker.go_right().unwrap(),
@@ -171,8 +174,8 @@ where
     while let Some(dir) = query_locator(walker, &locator) {
         match dir {
             Accept => {
-                walker.act_node(action);
-                walker.act_left_subtree(action).unwrap();
+                walker.act_node(action.clone());
+                walker.act_left_subtree(action.clone()).unwrap();
                 walker.go_right().unwrap();
             }
             GoRight => panic!("inconsistent locator"),
@@ -181,6 +184,311 @@ where
     }
 }
 
+/// Returns the largest `end` such that `pred` holds on the summary of the segment `start..end`.
+/// Assumes that `pred` is monotone: if it holds on a segment, it holds on all of its prefixes.
+///
+/// Goes up from the position at `start`, adding the nodes and right subtrees after it
+/// to the summary, until `pred` fails. Then goes down into the subtree where it failed.
+/// If `start` is in the middle of a value, the segment starts at that value.
+///
+/// Do not use with splay trees - it might mess up the complexity,
+/// because it uses go_up().
+///
+/// Instead, use [`SomeTree::max_right`]
+pub fn max_right<TR, P, D: Data>(tree: TR, start: usize, mut pred: P) -> usize
+where
+    TR: SomeTreeRef<D>,
+    P: FnMut(&D::Summary) -> bool,
+    D::Summary: SizedSummary,
+{
+    let mut walker = tree.walker();
+    walker.search_subtree(LeftEdgeOf(start..start));
+    let mut acc: D::Summary = Default::default();
+    loop {
+        match walker.go_up() {
+            // every value after `start` is in the segment
+            Err(()) => return walker.subtree_summary().size(),
+            // the parent is before `start`
+            Ok(Side::Right) => (),
+            Ok(Side::Left) => {
+                let with_node = acc + walker.node_summary();
+                if !pred(&with_node) {
+                    return walker.left_summary().size();
+                }
+                let with_right = with_node.clone() + walker.right_subtree_summary().unwrap();
+                if pred(&with_right) {
+                    acc = with_right;
+                } else {
+                    walker.go_right().unwrap();
+                    // the boundary is inside the current subtree
+                    return max_right_in_subtree(&mut walker, with_node, pred);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the largest `end` such that `pred` holds on the summary of the values before `end`
+/// in the current subtree, added to `acc`, by going down the current subtree.
+/// The segment that `acc` summarizes has to end right before the current subtree.
+pub(crate) fn max_right_in_subtree<W, P, D: Data>(
+    walker: &mut W,
+    mut acc: D::Summary,
+    mut pred: P,
+) -> usize
+where
+    W: SomeWalker<D>,
+    P: FnMut(&D::Summary) -> bool,
+    D::Summary: SizedSummary,
+{
+    while !walker.is_empty() {
+        let with_left = acc.clone() + walker.left_subtree_summary().unwrap();
+        if !pred(&with_left) {
+            walker.go_left().unwrap();
+            continue;
+        }
+        let with_node = with_left + walker.node_summary();
+        if !pred(&with_node) {
+            return walker.left_summary().size();
+        }
+        acc = with_node;
+        walker.go_right().unwrap();
+    }
+    walker.left_summary().size()
+}
+
+/// Returns the smallest `start` such that `pred` holds on the summary of the segment `start..end`.
+/// Assumes that `pred` is monotone: if it holds on a segment, it holds on all of its suffixes.
+///
+/// This is the mirror image of [`max_right`].
+/// If `end` is in the middle of a value, the segment ends at that value.
+///
+/// Do not use with splay trees - it might mess up the complexity,
+/// because it uses go_up().
+///
+/// Instead, use [`SomeTree::min_left`]
+pub fn min_left<TR, P, D: Data>(tree: TR, end: usize, mut pred: P) -> usize
+where
+    TR: SomeTreeRef<D>,
+    P: FnMut(&D::Summary) -> bool,
+    D::Summary: SizedSummary,
+{
+    let mut walker = tree.walker();
+    walker.search_subtree(RightEdgeOf(end..end));
+    let mut acc: D::Summary = Default::default();
+    loop {
+        match walker.go_up() {
+            // every value before `end` is in the segment
+            Err(()) => return 0,
+            // the parent is after `end`
+            Ok(Side::Left) => (),
+            Ok(Side::Right) => {
+                let with_node = walker.node_summary() + acc;
+                if !pred(&with_node) {
+                    return walker.left_summary().size() + walker.node_summary().size();
+                }
+                let with_left = walker.left_subtree_summary().unwrap() + with_node.clone();
+                if pred(&with_left) {
+                    acc = with_left;
+                } else {
+                    walker.go_left().unwrap();
+                    // the boundary is inside the current subtree
+                    return min_left_in_subtree(&mut walker, with_node, pred);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the smallest `start` such that `pred` holds on the summary of the values after
+/// `start` in the current subtree, added to `acc`, by going down the current subtree.
+/// The segment that `acc` summarizes has to start right after the current subtree.
+pub(crate) fn min_left_in_subtree<W, P, D: Data>(
+    walker: &mut W,
+    mut acc: D::Summary,
+    mut pred: P,
+) -> usize
+where
+    W: SomeWalker<D>,
+    P: FnMut(&D::Summary) -> bool,
+    D::Summary: SizedSummary,
+{
+    while !walker.is_empty() {
+        let with_right = walker.right_subtree_summary().unwrap() + acc.clone();
+        if !pred(&with_right) {
+            walker.go_right().unwrap();
+            continue;
+        }
+        let with_node = walker.node_summary() + with_right;
+        if !pred(&with_node) {
+            return walker.left_summary().size() + walker.node_summary().size();
+        }
+        acc = with_node;
+        walker.go_left().unwrap();
+    }
+    walker.left_summary().size()
+}
+
+/// Visits the matching values in the subtree of the walker, in order,
+/// and returns the walker to where it started.
+///
+/// On every subtree that the filter takes entirely, calls `take_all`.
+/// If it returns `false`, the values of the subtree are visited one by one.
+/// Calls `visit` on every matching value that is visited, with the walker at its node.
+fn traverse_matching<W, F, D: Data>(
+    walker: &mut W,
+    filter: &F,
+    mut take_all: impl FnMut(&mut W) -> bool,
+    mut visit: impl FnMut(&mut W),
+) where
+    W: SomeWalker<D>,
+    F: SubtreeFilter<D>,
+{
+    use FilterResult::*;
+
+    let start_depth = walker.depth();
+    // the depth of the subtree whose values are all taken, if we are inside one
+    let mut taken: Option<usize> = None;
+    loop {
+        // at this point, we just arrived at the current subtree from above
+        let enter = !walker.is_empty()
+            && (taken.is_some()
+                || match filter.filter(&walker.subtree_summary()) {
+                    Skip => false,
+                    TakeAll if take_all(walker) => false,
+                    TakeAll => {
+                        taken = Some(walker.depth());
+                        true
+                    }
+                    Descend => true,
+                });
+        if enter {
+            walker.go_left().unwrap();
+            continue;
+        }
+        // go up until we return from a left subtree
+        loop {
+            if walker.depth() == start_depth {
+                return;
+            }
+            if taken == Some(walker.depth()) {
+                taken = None;
+            }
+            if walker.go_up().unwrap() == Side::Left {
+                break;
+            }
+        }
+        if taken.is_some() || filter.matches(walker.value().unwrap()) {
+            visit(walker);
+        }
+        walker.go_right().unwrap();
+    }
+}
+
+/// Calls `f` on every value that matches the filter, in order.
+/// On splay trees, the visited nodes aren't splayed, so this takes time proportional
+/// to their depths. See [`SomeTree::for_each_matching`].
+pub fn for_each_matching<TR, F, C, D: Data>(tree: TR, filter: F, mut f: C)
+where
+    TR: SomeTreeRef<D>,
+    F: SubtreeFilter<D>,
+    C: FnMut(&D::Value),
+{
+    let mut walker = tree.walker();
+    traverse_matching(
+        &mut walker,
+        &filter,
+        |_| false,
+        |walker| f(walker.value().unwrap()),
+    );
+}
+
+/// Applies an action on every value that matches the filter.
+/// Whole subtrees that match are acted upon at once.
+/// On splay trees, the visited nodes aren't splayed, so this takes time proportional
+/// to their depths. See [`SomeTree::act_matching`].
+///
+/// Don't use with actions that reverse segments. Panics otherwise.
+pub fn act_matching<TR, F, D: Data>(tree: TR, action: D::Action, filter: F)
+where
+    TR: SomeTreeRef<D>,
+    F: SubtreeFilter<D>,
+{
+    assert!(
+        !action.to_reverse(),
+        "Reversals can't be applied on matching values"
+    );
+
+    let mut walker = tree.walker();
+    traverse_matching(
+        &mut walker,
+        &filter,
+        |walker| {
+            walker.act_subtree(action.clone());
+            true
+        },
+        |walker| walker.act_node(action.clone()).unwrap(),
+    );
+}
+
+/// Calls `f` on every value in the locator's segment, in order.
+/// Every changed node is rebuilt, and its ancestors are rebuilt when the walker goes back up.
+/// Do not use with splay trees - it might mess up the complexity,
+/// because it uses go_up().
+///
+/// Instead, use [`SomeTree::for_each_mut`]
+pub fn for_each_mut<TR, L, F, D: Data>(tree: TR, locator: L, mut f: F)
+where
+    TR: SomeTreeRef<D>,
+    L: Locator<D>,
+    F: FnMut(&mut D::Value),
+{
+    let mut walker = tree.walker();
+    // the empty position right before the segment
+    walker.search_subtree(LeftEdgeOf(locator.clone()));
+    while walker.next_filled().is_ok() {
+        match query_locator(&mut walker, &locator) {
+            Some(LocResult::Accept) => walker.with_value(&mut f).unwrap(),
+            // the segment has ended
+            _ => break,
+        }
+    }
+}
+
+/// Removes the values in the locator's segment that `pred` returns `false` on.
+/// The segment is split out of the tree, filtered, and concatenated back,
+/// so this takes `O(k + log n)` time if building a tree out of `k` values
+/// takes `O(k)` time.
+pub fn retain_mut<TR, L, P, D: Data>(tree: &mut TR, locator: L, mut pred: P)
+where
+    TR: ConcatenableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+    L: Locator<D>,
+    P: FnMut(&mut D::Value) -> bool,
+{
+    let suffix = tree
+        .slice(RightEdgeOf(locator.clone()))
+        .split_right()
+        .unwrap();
+    // the locator should still see the suffix to the right of the segment
+    let suffix_summary = suffix.subtree_summary();
+    let segment = tree
+        .slice(LeftEdgeOf(
+            move |left: &D::Summary, value: &D::Value, right: &D::Summary| {
+                locator.locate(left, value, &(right.clone() + suffix_summary.clone()))
+            },
+        ))
+        .split_right()
+        .unwrap();
+
+    let kept: TR = segment
+        .into_iter()
+        .filter_map(|mut value| if pred(&mut value) { Some(value) } else { None })
+        .collect();
+    tree.concatenate_right(kept);
+    tree.concatenate_right(suffix);
+}
+
 const SUDDENLY_EMPTY_ERROR: &str = "The locator unexpectedly became empty";
 const INCONSISTENT_LOCATOR_ERROR: &str = "inconsistent locator";
 
@@ -0,0 +1,218 @@
+//! This file contains join-based algorithms for set operations on trees
+//! whose values are sorted, such as intersections and differences.
+//!
+//! The algorithms only use three operations: splitting a tree by a key,
+//! exposing the root of a tree, and joining two trees with a value in the middle.
+//! Using them, every set operation on trees of sizes `m <= n` takes `O(m*log(1+n/m))` time,
+//! for any tree algorithm whose `join` takes time logarithmic in the ratio of the sizes
+//! of the trees, such as AVL trees and treaps.
+//! See "Just Join for Parallel Ordered Sets" by Blelloch, Ferizovic and Sun.
+//!
+//! Except for `union`, the operations assume that neither tree has several values with equal keys.
+
+use crate::*;
+use locators::*;
+
+/// Trees that can be taken apart at their root, and joined with a value in the middle.
+pub(crate) trait JoinableTree<D: Data>: ConcatenableTree<D>
+where
+    for<'a> &'a mut Self: SplittableTreeRef<D, T = Self>,
+{
+    /// Splits a non-empty tree into its left subtree, the value at its root,
+    /// and its right subtree.
+    fn expose(self) -> Option<(Self, D::Value, Self)>;
+
+    /// Concatenates the trees, with the given value in the middle.
+    fn join(left: Self, mid: D::Value, right: Self) -> Self;
+}
+
+/// Splits the tree into the values with keys smaller than `key`,
+/// the values with keys equal to `key`, and the values with keys bigger than `key`.
+fn split_by_key<D: Data, TR>(mut tree: TR, key: &D::Value) -> (TR, TR, TR)
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+{
+    let mut right = tree.slice(LeftEdgeOf(ByKey((key,)))).split_right().unwrap();
+    let mid = right
+        .slice(RightEdgeOf(ByKey((key,))))
+        .split_left()
+        .unwrap();
+    (tree, mid, right)
+}
+
+/// Merges the values of `tree1` that are equal to `value` into it, using the policy.
+/// Returns `value` itself if there are none.
+fn merge_into<D: Data, TR, P>(tree1: TR, value: D::Value, policy: &mut P) -> D::Value
+where
+    TR: SomeTree<D>,
+    for<'a> &'a mut TR: SomeTreeRef<D>,
+    P: DuplicatePolicy<D::Value>,
+{
+    let mut values = tree1.into_iter();
+    match values.next() {
+        None => value,
+        Some(first) => {
+            let left = values.fold(first, |acc, value| policy.merge(acc, value));
+            policy.merge(left, value)
+        }
+    }
+}
+
+/// Computes the union of two trees, keeping all of the values with equal keys.
+pub(crate) fn union<D: Data, TR>(tree1: TR, tree2: TR) -> TR
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+{
+    if tree1.is_empty() {
+        return tree2;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return tree1,
+        Some(parts) => parts,
+    };
+    // values equal to `mid` are put to its right
+    let (left1, mid1, right1) = split_by_key(tree1, &mid);
+    let mut right = union(right1, right2);
+    right.concatenate_left(mid1);
+    TR::join(union(left1, left2), mid, right)
+}
+
+/// Computes the union of two trees, merging values with equal keys using the policy.
+pub(crate) fn union_with<D: Data, TR, P>(tree1: TR, tree2: TR, policy: &mut P) -> TR
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+    P: DuplicatePolicy<D::Value>,
+{
+    if tree1.is_empty() {
+        return tree2;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return tree1,
+        Some(parts) => parts,
+    };
+    let (left1, mid1, right1) = split_by_key(tree1, &mid);
+    let left = union_with(left1, left2, policy);
+    let mid = merge_into(mid1, mid, policy);
+    let right = union_with(right1, right2, policy);
+    TR::join(left, mid, right)
+}
+
+/// Computes the intersection of two trees, merging values with equal keys using the policy.
+pub(crate) fn intersection_with<D: Data, TR, P>(tree1: TR, tree2: TR, policy: &mut P) -> TR
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+    P: DuplicatePolicy<D::Value>,
+{
+    if tree1.is_empty() {
+        return tree1;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return TR::default(),
+        Some(parts) => parts,
+    };
+    let (left1, mid1, right1) = split_by_key(tree1, &mid);
+    let found = !mid1.is_empty();
+    let left = intersection_with(left1, left2, policy);
+    let mid = merge_into(mid1, mid, policy);
+    let right = intersection_with(right1, right2, policy);
+    if found {
+        TR::join(left, mid, right)
+    } else {
+        TR::concatenate(left, right)
+    }
+}
+
+/// Computes the difference of two trees: keeps the values of `tree1` whose keys
+/// aren't in `tree2`.
+pub(crate) fn difference<D: Data, TR>(tree1: TR, tree2: TR) -> TR
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+{
+    if tree1.is_empty() {
+        return tree1;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return tree1,
+        Some(parts) => parts,
+    };
+    let (left1, _, right1) = split_by_key(tree1, &mid);
+    TR::concatenate(difference(left1, left2), difference(right1, right2))
+}
+
+/// Computes the symmetric difference of two trees: keeps the values whose keys
+/// are in exactly one of the trees.
+pub(crate) fn symmetric_difference<D: Data, TR>(tree1: TR, tree2: TR) -> TR
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+{
+    if tree1.is_empty() {
+        return tree2;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return tree1,
+        Some(parts) => parts,
+    };
+    let (left1, mid1, right1) = split_by_key(tree1, &mid);
+    let left = symmetric_difference(left1, left2);
+    let right = symmetric_difference(right1, right2);
+    if mid1.is_empty() {
+        TR::join(left, mid, right)
+    } else {
+        TR::concatenate(left, right)
+    }
+}
+
+/// Checks whether the keys of `tree1` are a subset of the keys of `tree2`.
+/// The trees are taken apart during the check, and put back together afterwards.
+pub(crate) fn is_subset<D: Data, TR>(tree1: &mut TR, tree2: &mut TR) -> bool
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+{
+    let (res, new1, new2) = is_subset_internal(std::mem::take(tree1), std::mem::take(tree2));
+    *tree1 = new1;
+    *tree2 = new2;
+    res
+}
+
+fn is_subset_internal<D: Data, TR>(tree1: TR, tree2: TR) -> (bool, TR, TR)
+where
+    D::Value: Ord,
+    TR: JoinableTree<D>,
+    for<'a> &'a mut TR: SplittableTreeRef<D, T = TR>,
+{
+    // here, the first tree is the one that is exposed,
+    // so that the recursion stops as soon as a missing value is found.
+    let (left1, mid, right1) = match tree1.expose() {
+        None => return (true, TR::default(), tree2),
+        Some(parts) => parts,
+    };
+    let (left2, mut mid2, right2) = split_by_key(tree2, &mid);
+    let (res, left1, left2, right1, right2) = if mid2.is_empty() {
+        (false, left1, left2, right1, right2)
+    } else {
+        let (res, left1, left2) = is_subset_internal(left1, left2);
+        if res {
+            let (res, right1, right2) = is_subset_internal(right1, right2);
+            (res, left1, left2, right1, right2)
+        } else {
+            (false, left1, left2, right1, right2)
+        }
+    };
+    mid2.concatenate_left(left2);
+    mid2.concatenate_right(right2);
+    (res, TR::join(left1, mid, right1), mid2)
+}
@@ -63,6 +63,41 @@ where
     pub fn iter(self) -> basic_tree::iterators::IterLocator<'a, D, L, T::TreeData> {
         self.tree.iter_locator(self.locator)
     }
+
+    /// Iterating on values, together with the summaries of everything to their left and right.
+    /// See [`SomeTree::iter_with_context`].
+    pub fn iter_with_context(
+        self,
+    ) -> basic_tree::iterators::IterWithContext<'a, D, L, T::TreeData> {
+        self.tree.iter_with_context(self.locator)
+    }
+
+    /// Iterating on values, together with their indices.
+    /// See [`SomeTree::iter_indexed`].
+    pub fn iter_indexed(self) -> basic_tree::iterators::IterIndexed<'a, D, L, T::TreeData>
+    where
+        D::Summary: SizedSummary,
+    {
+        self.tree.iter_indexed(self.locator)
+    }
+
+    /// Calls `f` on every value in this subsegment.
+    /// See [`SomeTree::for_each_mut`].
+    pub fn for_each_mut<F>(&mut self, f: F)
+    where
+        F: FnMut(&mut D::Value),
+    {
+        self.tree.for_each_mut(self.locator.clone(), f);
+    }
+
+    /// Replaces every value in this subsegment by the result of `f` on it.
+    /// See [`SomeTree::map_segment`].
+    pub fn map<F>(&mut self, f: F)
+    where
+        F: FnMut(&D::Value) -> D::Value,
+    {
+        self.tree.map_segment(self.locator.clone(), f);
+    }
 }
 
 impl<'a, D: Data, T: SomeTree<D>, L: Locator<D>> Slice<'a, D, T, L>
@@ -111,3 +146,26 @@ where
         walker.split_left()
     }
 }
+
+impl<'a, D: Data, T: ConcatenableTree<D>, L: Locator<D>> Slice<'a, D, T, L>
+where
+    for<'b> &'b mut T: SplittableTreeRef<D, T = T>,
+{
+    /// Removes every value in this subsegment that `pred` returns `false` on.
+    /// See [`SomeTree::retain`].
+    pub fn retain<P>(&mut self, pred: P)
+    where
+        P: FnMut(&D::Value) -> bool,
+    {
+        self.tree.retain(self.locator.clone(), pred);
+    }
+
+    /// Same as [`Slice::retain`], but `pred` may also change the values that it keeps.
+    /// See [`SomeTree::retain_mut`].
+    pub fn retain_mut<P>(&mut self, pred: P)
+    where
+        P: FnMut(&mut D::Value) -> bool,
+    {
+        self.tree.retain_mut(self.locator.clone(), pred);
+    }
+}
@@ -184,35 +184,7 @@ impl<'a, D: Data> SplayWalker<'a, D> {
     /// `log(new_node.size) - log(old_node.size) - 1`
     /// The -1 covers the complexity of going down the tree in the first place.
     pub fn splay_step(&mut self) {
-        // if the walker points to an empty position,
-        // we can't splay it, just go upwards once.
-        if self.walker.is_empty() {
-            let _ = self.walker.go_up();
-            return;
-        }
-
-        let b1 = match self.walker.go_up() {
-            Err(()) => return, // already the root
-            Ok(b1) => b1,
-        };
-
-        let b2 = match self.walker.is_left_son() {
-            None => {
-                self.walker.rot_side(b1.flip()).unwrap();
-                return;
-            } // became the root - zig step
-            Some(b2) => b2,
-        };
-
-        if b1 == b2 {
-            // zig-zig case
-            self.walker.rot_up().unwrap();
-            self.walker.rot_side(b1.flip()).unwrap();
-        } else {
-            // zig-zag case
-            self.walker.rot_side(b1.flip()).unwrap();
-            self.walker.rot_up().unwrap();
-        }
+        splay_step(&mut self.walker);
     }
 
     /// Same as [`SplayWalker::splay_step`], but splays up to the specified depth.
@@ -244,16 +216,7 @@ impl<'a, D: Data> SplayWalker<'a, D> {
                 None => panic!(), // we couldn't have gone into this branch
                 Some(b2) => b2,
             };
-
-            if b1 == b2 {
-                // zig-zig case
-                self.walker.rot_up().unwrap();
-                self.walker.rot_side(b1.flip()).unwrap();
-            } else {
-                // zig-zag case
-                self.walker.rot_side(b1.flip()).unwrap();
-                self.walker.rot_up().unwrap();
-            }
+            double_rotation(&mut self.walker, b1, b2);
         }
     }
 
@@ -285,6 +248,112 @@ impl<'a, D: Data> Drop for SplayWalker<'a, D> {
     }
 }
 
+/// A position in a tree, with the operations that splaying needs.
+/// The splay steps are written once over this trait, so that they can be used both
+/// by [`SplayWalker`], through its [`BasicWalker`], and by the splay trees of
+/// [`link_cut`](super::link_cut), whose nodes are stored in an arena.
+pub(crate) trait SplayPosition {
+    /// Returns true if at an empty position.
+    fn is_empty(&self) -> bool;
+
+    /// Goes up to the parent, and returns which son the current position was.
+    /// Returns `Err(())` at the root.
+    /// This is named differently from [`SomeWalker::go_up`], so that the two don't clash.
+    fn go_to_parent(&mut self) -> Result<Side, ()>;
+
+    /// Returns which son the current position is, or [`None`] at the root.
+    fn is_left_son(&self) -> Option<Side>;
+
+    /// Performs a left rotation if `side` is [`Side::Left`], and a right rotation otherwise,
+    /// so that the current position holds the son that moved up.
+    /// Returns [`None`] if there is no such son.
+    fn rot_side(&mut self, side: Side) -> Option<()>;
+
+    /// Rotates so that the current node moves up.
+    /// Fails if the current node is the root.
+    fn rot_up(&mut self) -> Result<Side, ()> {
+        let b = self.go_to_parent()?;
+        self.rot_side(b.flip())
+            .expect("original node went missing?");
+        Ok(b)
+    }
+}
+
+impl<'a, D: Data, T> SplayPosition for BasicWalker<'a, D, T> {
+    fn is_empty(&self) -> bool {
+        BasicWalker::is_empty(self)
+    }
+
+    fn go_to_parent(&mut self) -> Result<Side, ()> {
+        self.go_up()
+    }
+
+    fn is_left_son(&self) -> Option<Side> {
+        BasicWalker::is_left_son(self)
+    }
+
+    fn rot_side(&mut self, side: Side) -> Option<()> {
+        BasicWalker::rot_side(self, side)
+    }
+
+    fn rot_up(&mut self) -> Result<Side, ()> {
+        BasicWalker::rot_up(self)
+    }
+}
+
+/// Does a single splay step upwards. See [`SplayWalker::splay_step`].
+pub(crate) fn splay_step<P: SplayPosition>(pos: &mut P) {
+    // if the position is empty,
+    // we can't splay it, just go upwards once.
+    if pos.is_empty() {
+        let _ = pos.go_to_parent();
+        return;
+    }
+
+    let b1 = match pos.go_to_parent() {
+        Err(()) => return, // already the root
+        Ok(b1) => b1,
+    };
+
+    let b2 = match pos.is_left_son() {
+        None => {
+            pos.rot_side(b1.flip()).unwrap();
+            return;
+        } // became the root - zig step
+        Some(b2) => b2,
+    };
+    double_rotation(pos, b1, b2);
+}
+
+/// The zig-zig and zig-zag cases of a splay step.
+/// The position is at the parent of the splayed node, which was its `b1` son,
+/// and the parent is the `b2` son of the grandparent.
+fn double_rotation<P: SplayPosition>(pos: &mut P, b1: Side, b2: Side) {
+    if b1 == b2 {
+        // zig-zig case
+        pos.rot_up().unwrap();
+        pos.rot_side(b1.flip()).unwrap();
+    } else {
+        // zig-zag case
+        pos.rot_side(b1.flip()).unwrap();
+        pos.rot_up().unwrap();
+    }
+}
+
+impl<D: Data> AsBasicTree<D> for SplayTree<D> {
+    fn as_basic_tree(&self) -> &BasicTree<D, ()> {
+        &self.tree
+    }
+}
+
+impl<D: Data> sealed::AsBasicTreeMut<D> for SplayTree<D> {
+    type AlgData = ();
+
+    fn as_basic_tree_mut(&mut self, _: sealed::Token) -> &mut BasicTree<D, ()> {
+        &mut self.tree
+    }
+}
+
 impl<D: Data> SomeTree<D> for SplayTree<D> {
     /// Note: calling this is inefficient
     /// and panicks if debug assertions are on.
@@ -323,6 +392,45 @@ impl<D: Data> SomeTree<D> for SplayTree<D> {
         walker.act_subtree(action);
     }
 
+    /// Isolates the suffix that starts at `start`, and searches for the boundary inside it.
+    /// The boundary is splayed when the walker is dropped.
+    fn max_right<P>(&mut self, start: usize, pred: P) -> usize
+    where
+        P: FnMut(&D::Summary) -> bool,
+        D::Summary: SizedSummary,
+    {
+        let mut walker = self.isolate_segment(start..);
+        segment_algorithms::max_right_in_subtree(&mut walker, Default::default(), pred)
+    }
+
+    /// Isolates the prefix that ends at `end`, and searches for the boundary inside it.
+    /// The boundary is splayed when the walker is dropped.
+    fn min_left<P>(&mut self, end: usize, pred: P) -> usize
+    where
+        P: FnMut(&D::Summary) -> bool,
+        D::Summary: SizedSummary,
+    {
+        let mut walker = self.isolate_segment(..end);
+        segment_algorithms::min_left_in_subtree(&mut walker, Default::default(), pred)
+    }
+
+    /// Isolates the segment, and then goes over its subtree, which holds exactly the
+    /// segment's values. The last node visited is splayed when the walker is dropped.
+    /// This takes `O(k + log n)` amortized time for a segment of `k` values.
+    fn for_each_mut<L, F>(&mut self, locator: L, mut f: F)
+    where
+        L: locators::Locator<D>,
+        F: FnMut(&mut D::Value),
+    {
+        let mut walker = self.isolate_segment(locator);
+        let depth = walker.depth();
+        // the empty position right before the segment
+        while walker.go_left().is_ok() {}
+        while walker.next_filled().is_ok() && walker.depth() >= depth {
+            walker.with_value(&mut f).unwrap();
+        }
+    }
+
     type TreeData = ();
     fn iter_locator<'a, L: locators::Locator<D>>(
         &'a mut self,
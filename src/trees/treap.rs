@@ -10,23 +10,96 @@
 //! They are in the correct structure mandated byy thos priorities.
 //!
 //! The tree's structure is completely independent of the actions that were performed on it.
+//!
+//! The priorities are drawn from a [`PrioritySource`]. By default, this is [`ThreadRngPriorities`],
+//! which uses [`rand::thread_rng`]. In order to get reproducible tree shapes, use a seeded
+//! source instead, e.g., with [`Treap::with_seed`] or [`Treap::with_rng`]: then the same
+//! sequence of operations always produces the same tree.
 
 use crate::locators;
 
 use super::basic_tree::*;
 use super::*;
-use rand;
+use rand::{self, rngs::StdRng, RngCore, SeedableRng};
 
 // The type that is used for bookkeeping.
 // convention: a bigger number should go higher up the tree.
 type T = u64;
 
+/// A source of priorities for the nodes of a [`Treap`].
+/// The priorities should be independent and uniformly distributed,
+/// in order for the treap to be balanced.
+pub trait PrioritySource: Default {
+    /// Returns the priority for a newly inserted node.
+    fn next_priority(&mut self) -> u64;
+
+    /// Returns a new source for a tree that is split off of the current tree.
+    /// The priorities it returns should be independent of this source's priorities.
+    fn split(&mut self) -> Self;
+}
+
+/// The default [`PrioritySource`], which draws priorities from [`rand::thread_rng`].
+/// Trees using it don't have reproducible shapes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ThreadRngPriorities;
+
+impl PrioritySource for ThreadRngPriorities {
+    fn next_priority(&mut self) -> u64 {
+        rand::random()
+    }
+
+    fn split(&mut self) -> Self {
+        ThreadRngPriorities
+    }
+}
+
+/// A [`PrioritySource`] that draws priorities from a user supplied random number generator.
+/// If the generator is seeded, the tree's shape is completely determined by the seed
+/// and the sequence of operations performed on the tree.
+///
+/// Trees that are split off of a tree get a new generator, seeded by the original generator.
+/// The default instance uses the seed `0`.
+#[derive(Clone, Debug)]
+pub struct RngPriorities<R>(pub R);
+
+impl<R: RngCore + SeedableRng> Default for RngPriorities<R> {
+    fn default() -> Self {
+        RngPriorities(R::seed_from_u64(0))
+    }
+}
+
+impl<R: RngCore + SeedableRng> PrioritySource for RngPriorities<R> {
+    fn next_priority(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn split(&mut self) -> Self {
+        RngPriorities(R::seed_from_u64(self.0.next_u64()))
+    }
+}
+
 /// A Treap.
-pub struct Treap<D: Data> {
+/// The `P` parameter is the source of the priorities of the nodes.
+pub struct Treap<D: Data, P = ThreadRngPriorities> {
     tree: BasicTree<D, T>,
+    priorities: P,
+}
+
+impl<D: Data, P> AsBasicTree<D> for Treap<D, P> {
+    fn as_basic_tree(&self) -> &BasicTree<D, T> {
+        &self.tree
+    }
 }
 
-impl<D: Data> SomeTree<D> for Treap<D> {
+impl<D: Data, P> sealed::AsBasicTreeMut<D> for Treap<D, P> {
+    type AlgData = T;
+
+    fn as_basic_tree_mut(&mut self, _: sealed::Token) -> &mut BasicTree<D, T> {
+        &mut self.tree
+    }
+}
+
+impl<D: Data, P: PrioritySource> SomeTree<D> for Treap<D, P> {
     fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
     where
         L: locators::Locator<D>,
@@ -50,7 +123,7 @@ impl<D: Data> SomeTree<D> for Treap<D> {
             segment_algorithms::act_segment(self, action, locator)
         } else {
             // split out the middle
-            let mut mid: Treap<D> = self
+            let mut mid: Treap<D, P> = self
                 .slice(locators::LeftEdgeOf(locator.clone()))
                 .split_right()
                 .unwrap();
@@ -61,6 +134,7 @@ impl<D: Data> SomeTree<D> for Treap<D> {
                     self.subtree_summary(),
                     Default::default(),
                 ),
+                priorities: &mut mid.priorities,
             };
             walker2.search_subtree(locators::RightEdgeOf(locator));
             let right = walker2.split_right().unwrap();
@@ -97,34 +171,35 @@ impl<D: Data> SomeTree<D> for Treap<D> {
     }
 }
 
-impl<D: Data> Default for Treap<D> {
+impl<D: Data, P: PrioritySource> Default for Treap<D, P> {
     fn default() -> Self {
-        Treap::new()
+        Treap::with_priority_source(P::default())
     }
 }
 
-impl<'a, D: Data> SomeTreeRef<D> for &'a mut Treap<D> {
-    type Walker = TreapWalker<'a, D>;
+impl<'a, D: Data, P: PrioritySource> SomeTreeRef<D> for &'a mut Treap<D, P> {
+    type Walker = TreapWalker<'a, D, P>;
 
     fn walker(self) -> Self::Walker {
         TreapWalker {
             walker: self.tree.walker(),
+            priorities: &mut self.priorities,
         }
     }
 }
 
-impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut Treap<D> {
-    type ModifiableWalker = TreapWalker<'a, D>;
+impl<'a, D: Data, P: PrioritySource> ModifiableTreeRef<D> for &'a mut Treap<D, P> {
+    type ModifiableWalker = TreapWalker<'a, D, P>;
 }
 
 derive_SomeEntry! {tree, T,
-    impl<D: Data> SomeEntry<D> for Treap<D> {
+    impl<D: Data, P> SomeEntry<D> for Treap<D, P> {
         fn assert_correctness_locally(&self)
         where
             D::Summary: Eq,
         {
             if let Some(node) = self.tree.node() {
-                Self::assert_priorities_locally_internal(node);
+                Treap::<D>::assert_priorities_locally_internal(node);
                 node.assert_correctness_locally();
             }
         }
@@ -139,10 +214,47 @@ impl<D: Data> BasicTree<D, T> {
 }
 
 impl<D: Data> Treap<D> {
-    /// Creates an empty treap.
+    /// Creates an empty treap, that draws its priorities from [`rand::thread_rng`].
     pub fn new() -> Treap<D> {
+        Treap::with_priority_source(ThreadRngPriorities)
+    }
+}
+
+impl<D: Data> Treap<D, RngPriorities<StdRng>> {
+    /// Creates an empty treap, that draws its priorities from a random number generator
+    /// seeded by `seed`. The tree's shape is completely determined by the seed and
+    /// the sequence of operations performed on the tree.
+    ///```
+    /// use grove::{*, treap::Treap};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree1 = Treap::<StdNum, _>::with_seed(13);
+    /// let mut tree2 = Treap::<StdNum, _>::with_seed(13);
+    /// for i in 0..100 {
+    ///     tree1.slice(i..i).insert(i as i32).unwrap();
+    ///     tree2.slice(i..i).insert(i as i32).unwrap();
+    /// }
+    /// assert_eq!(tree1.priority(), tree2.priority());
+    /// # tree1.assert_correctness();
+    ///```
+    pub fn with_seed(seed: u64) -> Self {
+        Treap::with_rng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl<D: Data, R: RngCore + SeedableRng> Treap<D, RngPriorities<R>> {
+    /// Creates an empty treap, that draws its priorities from the given random number generator.
+    pub fn with_rng(rng: R) -> Self {
+        Treap::with_priority_source(RngPriorities(rng))
+    }
+}
+
+impl<D: Data, P> Treap<D, P> {
+    /// Creates an empty treap, that draws its priorities from the given source.
+    pub fn with_priority_source(priorities: P) -> Self {
         Treap {
             tree: BasicTree::Empty,
+            priorities,
         }
     }
 
@@ -152,6 +264,24 @@ impl<D: Data> Treap<D> {
         self.tree.priority()
     }
 
+    /// Finds any node that the locator `Accept`s, with only immutable access to the tree.
+    /// Same as [`BasicTree::search_imm`].
+    pub fn search_imm<L: locators::Locator<D>>(&self, locator: L) -> ImmDownBasicWalker<'_, D, T> {
+        self.tree.search_imm(locator)
+    }
+
+    /// Returns any value that the locator `Accept`s, together with its pending actions,
+    /// with only immutable access to the tree. Same as [`BasicTree::get`].
+    pub fn get<L: locators::Locator<D>>(&self, locator: L) -> Option<ValueRef<'_, D>> {
+        self.tree.get(locator)
+    }
+
+    /// Iterates over all of the values, with only immutable access to the tree.
+    /// Same as [`BasicTree::iter_imm`].
+    pub fn iter_imm(&self) -> iterators::IterImm<'_, D, T> {
+        self.tree.iter_imm()
+    }
+
     /// Computes the union of two splay trees, ordered by keys.
     /// We order the resulting tree based on the `D::Value: Keyed` instance, assuming that
     /// the values in the existing trees are also in the correct order.
@@ -166,13 +296,76 @@ impl<D: Data> Treap<D> {
     /// This has the effect that if you start with `n` different singletone trees,
     /// and you united them together in any way whatsoever, the overall complexity would be
     /// `O(n*log(n))`.
-    pub fn union(&mut self, tree2: Treap<D>)
+    pub fn union(&mut self, tree2: Treap<D, P>)
     where
         D::Value: Ord,
+        P: PrioritySource,
     {
         union_internal(&mut self.tree, tree2);
     }
 
+    /// Keeps only the values that are equal to some value of `other`, ordered by keys.
+    /// We assume that the values in both trees are in sorted order, without duplicates.
+    /// See [`intersection`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the average case.
+    pub fn intersection(&mut self, other: Treap<D, P>)
+    where
+        D::Value: Ord,
+        P: PrioritySource,
+    {
+        let tree = std::mem::take(self);
+        *self = intersection(tree, other);
+    }
+
+    /// Removes all the values that are equal to some value of `other`, ordered by keys.
+    /// We assume that the values in both trees are in sorted order.
+    /// See [`difference`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the average case.
+    pub fn difference(&mut self, other: Treap<D, P>)
+    where
+        D::Value: Ord,
+        P: PrioritySource,
+    {
+        let tree = std::mem::take(self);
+        *self = difference(tree, other);
+    }
+
+    /// Keeps the values that are in exactly one of the trees, ordered by keys.
+    /// We assume that the values in both trees are in sorted order, without duplicates.
+    /// See [`symmetric_difference`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the average case.
+    pub fn symmetric_difference(&mut self, other: Treap<D, P>)
+    where
+        D::Value: Ord,
+        P: PrioritySource,
+    {
+        let tree = std::mem::take(self);
+        *self = symmetric_difference(tree, other);
+    }
+
+    /// Returns true if every value of this tree is equal to some value of `other`.
+    /// See [`is_subset`].
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the average case.
+    pub fn is_subset(&mut self, other: &mut Treap<D, P>) -> bool
+    where
+        D::Value: Ord,
+        P: PrioritySource,
+    {
+        is_subset(self, other)
+    }
+
     /// Asserts that the priorities maintain the priority invariant
     /// at the current node.
     /// Panics otherwise.
@@ -199,14 +392,12 @@ impl<D: Data> Treap<D> {
     }
 }
 
-impl<D: Data> std::iter::FromIterator<D::Value> for Treap<D> {
+impl<D: Data, P: PrioritySource> std::iter::FromIterator<D::Value> for Treap<D, P> {
     /// This takes [`O(n)`] worst-case time.
     fn from_iter<T: IntoIterator<Item = D::Value>>(iter: T) -> Self {
         // TODO: write a specific instantiation instead of calling insert,
         // because we know that we're not using all of insert's generality.
-        let mut tree = Treap {
-            tree: BasicTree::Empty,
-        };
+        let mut tree: Treap<D, P> = Treap::default();
         let mut walker = tree.walker();
         for val in iter {
             walker.insert(val).unwrap();
@@ -218,7 +409,7 @@ impl<D: Data> std::iter::FromIterator<D::Value> for Treap<D> {
     }
 }
 
-impl<D: Data> IntoIterator for Treap<D> {
+impl<D: Data, P> IntoIterator for Treap<D, P> {
     type Item = D::Value;
     type IntoIter = iterators::IntoIter<D, std::ops::RangeFull, T>;
 
@@ -228,12 +419,13 @@ impl<D: Data> IntoIterator for Treap<D> {
 }
 
 /// A walker for a [`Treap`].
-pub struct TreapWalker<'a, D: Data> {
+pub struct TreapWalker<'a, D: Data, P = ThreadRngPriorities> {
     walker: BasicWalker<'a, D, T>,
+    priorities: &'a mut P,
 }
 
 derive_SomeWalker! {walker,
-    impl<'a, D: Data> SomeWalker<D> for TreapWalker<'a, D> {
+    impl<'a, D: Data, P> SomeWalker<D> for TreapWalker<'a, D, P> {
         fn go_up(&mut self) -> Result<Side, ()> {
             self.walker.go_up()
         }
@@ -241,20 +433,20 @@ derive_SomeWalker! {walker,
 }
 
 derive_SomeEntry! {walker, T,
-    impl<'a, D: Data> SomeEntry<D> for TreapWalker<'a, D> {
+    impl<'a, D: Data, P> SomeEntry<D> for TreapWalker<'a, D, P> {
         fn assert_correctness_locally(&self)
         where
             D::Summary: Eq,
         {
             self.walker.assert_correctness_locally();
             if let Some(node) = self.walker.node() {
-                Treap::assert_priorities_locally_internal(node);
+                Treap::<D>::assert_priorities_locally_internal(node);
             }
         }
     }
 }
 
-impl<'a, D: Data> TreapWalker<'a, D> {
+impl<'a, D: Data, P> TreapWalker<'a, D, P> {
     /// Returns the priority of the current node. Lower numbers means
     /// The node is closer to the root.
     pub fn priority(&self) -> Option<T> {
@@ -266,7 +458,7 @@ impl<'a, D: Data> TreapWalker<'a, D> {
     }
 }
 
-impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
+impl<'a, D: Data, P: PrioritySource> ModifiableWalker<D> for TreapWalker<'a, D, P> {
     /// Inserts the value into the tree at the current empty position.
     /// If the current position is not empty, return [`None`].
     /// When the function returns, the walker will be at the position the node
@@ -276,7 +468,7 @@ impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
             return None;
         }
 
-        let priority: T = rand::random();
+        let priority: T = self.priorities.next_priority();
         let mut temp = BasicTree::Empty;
         // in the first round, this value is irrelevent. choosing this will skip the first if.
         let mut prev_side = self.walker.is_left_son().unwrap_or(Side::Right);
@@ -328,10 +520,9 @@ impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
     /// The walker stays in the same position, and only the current node's subtree changes.
     fn delete(&mut self) -> Option<D::Value> {
         let tree = std::mem::replace(self.walker.inner_mut(), BasicTree::Empty);
-        let node = tree.into_node()?;
-        let left = Treap { tree: node.left };
-        let right = Treap { tree: node.right };
-        *self.walker.inner_mut() = ConcatenableTree::concatenate(left, right).tree;
+        let mut node = tree.into_node()?;
+        concatenate_internal(&mut node.left, node.right);
+        *self.walker.inner_mut() = node.left;
         Some(node.node_value)
     }
 }
@@ -350,7 +541,7 @@ impl<'a, D: Data> ModifiableWalker<D> for TreapWalker<'a, D> {
 /// This has the effect that if you start with `n` different singletone trees,
 /// and you united them together in any way whatsoever, the overall complexity would be
 /// `O(n*log(n))`.
-fn union_internal<D: Data>(tree1: &mut BasicTree<D, T>, mut tree2: Treap<D>)
+fn union_internal<D: Data, P: PrioritySource>(tree1: &mut BasicTree<D, T>, mut tree2: Treap<D, P>)
 where
     D::Value: Ord,
 {
@@ -426,7 +617,7 @@ where
 /// This has the effect that if you start with `n` different singletone trees,
 /// and you united them together in any way whatsoever, the overall complexity would be
 /// `O(n*log(n))`.
-pub fn union<D: Data>(mut tree1: Treap<D>, tree2: Treap<D>) -> Treap<D>
+pub fn union<D: Data, P: PrioritySource>(mut tree1: Treap<D, P>, tree2: Treap<D, P>) -> Treap<D, P>
 where
     D::Value: Ord,
 {
@@ -434,7 +625,225 @@ where
     tree1
 }
 
-impl<D: Data> ConcatenableTree<D> for Treap<D> {
+/// Computes the union of two trees, ordered by keys.
+/// Unlike [`union`], values with equal keys are combined into a single value using the policy.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, KeepRight, treap, treap::Treap};
+///use grove::example_data::{PlainData};
+///
+///type T = Treap<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = treap::union_with(tree1, tree2, KeepRight);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), (0..9).collect::<Vec<_>>());
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the average case.
+pub fn union_with<D: Data, P: PrioritySource, DP>(
+    tree1: Treap<D, P>,
+    tree2: Treap<D, P>,
+    mut policy: DP,
+) -> Treap<D, P>
+where
+    D::Value: Ord,
+    DP: DuplicatePolicy<D::Value>,
+{
+    set_algorithms::union_with(tree1, tree2, &mut policy)
+}
+
+/// Computes the intersection of two trees, ordered by keys:
+/// Keeps the values of `tree1` that are equal to some value of `tree2`.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, treap, treap::Treap};
+///use grove::example_data::{PlainData};
+///
+///type T = Treap<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = treap::intersection(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![4,5,6]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the average case.
+pub fn intersection<D: Data, P: PrioritySource>(
+    tree1: Treap<D, P>,
+    tree2: Treap<D, P>,
+) -> Treap<D, P>
+where
+    D::Value: Ord,
+{
+    intersection_with(tree1, tree2, KeepLeft)
+}
+
+/// Computes the intersection of two trees, ordered by keys.
+/// Values with equal keys are combined into a single value using the policy.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, treap, treap::Treap};
+///use grove::example_data::{PlainData};
+///
+///// word counts, that are ordered only by their words
+///#[derive(Debug)]
+///struct Count(&'static str, u32);
+///impl PartialEq for Count { fn eq(&self, other: &Self) -> bool { self.0 == other.0 } }
+///impl Eq for Count {}
+///impl PartialOrd for Count {
+///    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+///}
+///impl Ord for Count { fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.0.cmp(other.0) } }
+///
+///type T = Treap<PlainData<Count>>;
+///let tree1: T = vec![Count("a", 1), Count("b", 2), Count("c", 3)].into_iter().collect();
+///let tree2: T = vec![Count("b", 10), Count("c", 20), Count("d", 30)].into_iter().collect();
+///let tree = treap::intersection_with(tree1, tree2, |Count(word, a), Count(_, b)| Count(word, a + b));
+/// # tree.assert_correctness();
+///let counts: Vec<_> = tree.into_iter().map(|Count(word, count)| (word, count)).collect();
+///assert_eq!(counts, vec![("b", 12), ("c", 23)]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the average case.
+pub fn intersection_with<D: Data, P: PrioritySource, DP>(
+    tree1: Treap<D, P>,
+    tree2: Treap<D, P>,
+    mut policy: DP,
+) -> Treap<D, P>
+where
+    D::Value: Ord,
+    DP: DuplicatePolicy<D::Value>,
+{
+    set_algorithms::intersection_with(tree1, tree2, &mut policy)
+}
+
+/// Computes the difference of two trees, ordered by keys:
+/// Keeps the values of `tree1` that are not equal to any value of `tree2`.
+/// We assume that the values in both trees are in sorted order.
+///
+///```rust
+///use grove::{SomeTree, treap, treap::Treap};
+///use grove::example_data::{PlainData};
+///
+///type T = Treap<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = treap::difference(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![0,1,2,3]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the average case.
+pub fn difference<D: Data, P: PrioritySource>(tree1: Treap<D, P>, tree2: Treap<D, P>) -> Treap<D, P>
+where
+    D::Value: Ord,
+{
+    set_algorithms::difference(tree1, tree2)
+}
+
+/// Computes the symmetric difference of two trees, ordered by keys:
+/// Keeps the values that are in exactly one of the trees.
+/// We assume that the values in both trees are in sorted order, without duplicates.
+///
+///```rust
+///use grove::{SomeTree, treap, treap::Treap};
+///use grove::example_data::{PlainData};
+///
+///type T = Treap<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = treap::symmetric_difference(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![0,1,2,3,7,8]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the average case.
+pub fn symmetric_difference<D: Data, P: PrioritySource>(
+    tree1: Treap<D, P>,
+    tree2: Treap<D, P>,
+) -> Treap<D, P>
+where
+    D::Value: Ord,
+{
+    set_algorithms::symmetric_difference(tree1, tree2)
+}
+
+/// Returns true if every value of `tree1` is equal to some value of `tree2`.
+/// We assume that the values in both trees are in sorted order.
+///
+/// The trees are split apart during the check, and joined back afterwards,
+/// so they keep their values, but not necessarily their shapes.
+///
+///```rust
+///use grove::{SomeTree, treap, treap::Treap};
+///use grove::example_data::{PlainData};
+///
+///type T = Treap<PlainData<i32>>;
+///let mut tree1: T = (3..7).collect();
+///let mut tree2: T = (0..9).collect();
+///assert!(treap::is_subset(&mut tree1, &mut tree2));
+///assert!(!treap::is_subset(&mut tree2, &mut tree1));
+/// # tree1.assert_correctness();
+/// # tree2.assert_correctness();
+///assert_eq!(tree1.into_iter().collect::<Vec<_>>(), (3..7).collect::<Vec<_>>());
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the average case.
+pub fn is_subset<D: Data, P: PrioritySource>(
+    tree1: &mut Treap<D, P>,
+    tree2: &mut Treap<D, P>,
+) -> bool
+where
+    D::Value: Ord,
+{
+    set_algorithms::is_subset(tree1, tree2)
+}
+
+impl<D: Data, P: PrioritySource> set_algorithms::JoinableTree<D> for Treap<D, P> {
+    fn expose(self) -> Option<(Self, D::Value, Self)> {
+        let Treap {
+            tree,
+            mut priorities,
+        } = self;
+        let mut node = tree.into_node()?;
+        node.access();
+        let left = Treap {
+            tree: std::mem::replace(&mut node.left, BasicTree::Empty),
+            priorities: priorities.split(),
+        };
+        let right = Treap {
+            tree: std::mem::replace(&mut node.right, BasicTree::Empty),
+            priorities,
+        };
+        Some((left, node.node_value, right))
+    }
+
+    fn join(mut left: Self, mid: D::Value, right: Self) -> Self {
+        let priority = left.priorities.next_priority();
+        let mid = BasicTree::from_node(BasicNode::new_alg(mid, priority));
+        concatenate_internal(&mut left.tree, mid);
+        concatenate_internal(&mut left.tree, right.tree);
+        left
+    }
+}
+
+impl<D: Data, P: PrioritySource> ConcatenableTree<D> for Treap<D, P> {
     /// Concatenates the trees together, in place.
     ///```
     /// use grove::{SomeTree, ConcatenableTree, treap::Treap};
@@ -447,43 +856,48 @@ impl<D: Data> ConcatenableTree<D> for Treap<D> {
     /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(13..=25).collect::<Vec<_>>());
     /// # tree.assert_correctness();
     ///```
-    fn concatenate_right(&mut self, tree2: Treap<D>) {
-        let mut walker = self.walker();
-        let mut tree_r = tree2.tree;
-
-        // if we don't access here, then tree_r might be swapped into the walker
-        // (in the first std::mem::swap) when it's not in a clean state, which is an assumed invariant.
-        // this can mess up things, especially when reversals are present.
-        tree_r.access();
-        loop {
-            match (walker.priority(), tree_r.priority()) {
-                (None, _) => {
-                    *walker.walker.inner_mut() = tree_r;
-                    break;
-                }
-                (_, None) => break,
-                (Some(a), Some(b)) if a > b => {
-                    walker.go_right().unwrap();
-                }
-                _ => {
-                    std::mem::swap(walker.walker.inner_mut(), &mut tree_r);
-                    walker.go_left().unwrap();
-                    std::mem::swap(walker.walker.inner_mut(), &mut tree_r);
-                }
+    fn concatenate_right(&mut self, tree2: Treap<D, P>) {
+        concatenate_internal(&mut self.tree, tree2.tree);
+    }
+}
+
+/// Concatenates `tree_r` to the right of `tree`, in place.
+/// This doesn't need a [`PrioritySource`], since it doesn't create new nodes.
+fn concatenate_internal<D: Data>(tree: &mut BasicTree<D, T>, mut tree_r: BasicTree<D, T>) {
+    let mut walker = tree.walker();
+
+    // if we don't access here, then tree_r might be swapped into the walker
+    // (in the first std::mem::swap) when it's not in a clean state, which is an assumed invariant.
+    // this can mess up things, especially when reversals are present.
+    tree_r.access();
+    loop {
+        match (walker.inner().priority(), tree_r.priority()) {
+            (None, _) => {
+                *walker.inner_mut() = tree_r;
+                break;
+            }
+            (_, None) => break,
+            (Some(a), Some(b)) if a > b => {
+                walker.go_right().unwrap();
+            }
+            _ => {
+                std::mem::swap(walker.inner_mut(), &mut tree_r);
+                walker.go_left().unwrap();
+                std::mem::swap(walker.inner_mut(), &mut tree_r);
             }
         }
-        // the walker is responsible for going up the tree
-        // and rebuilding all the nodes
     }
+    // the walker is responsible for going up the tree
+    // and rebuilding all the nodes
 }
 
-impl<'a, D: Data> SplittableTreeRef<D> for &'a mut Treap<D> {
-    type T = Treap<D>;
-    type SplittableWalker = TreapWalker<'a, D>;
+impl<'a, D: Data, P: PrioritySource> SplittableTreeRef<D> for &'a mut Treap<D, P> {
+    type T = Treap<D, P>;
+    type SplittableWalker = TreapWalker<'a, D, P>;
 }
 
-impl<'a, D: Data> SplittableWalker<D> for TreapWalker<'a, D> {
-    type T = Treap<D>;
+impl<'a, D: Data, P: PrioritySource> SplittableWalker<D> for TreapWalker<'a, D, P> {
+    type T = Treap<D, P>;
 
     /// Will only do anything if the current position is empty.
     /// If it is empty, it will split the tree: the elements
@@ -502,7 +916,7 @@ impl<'a, D: Data> SplittableWalker<D> for TreapWalker<'a, D> {
     /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (24..88).collect::<Vec<_>>());
     /// # tree.assert_correctness();
     ///```
-    fn split_right(&mut self) -> Option<Treap<D>> {
+    fn split_right(&mut self) -> Option<Treap<D, P>> {
         if !self.is_empty() {
             return None;
         }
@@ -527,7 +941,10 @@ impl<'a, D: Data> SplittableWalker<D> for TreapWalker<'a, D> {
         if prev_side == Side::Left {
             std::mem::swap(self.walker.inner_mut(), &mut temp);
         }
-        Some(Treap { tree: temp })
+        Some(Treap {
+            tree: temp,
+            priorities: self.priorities.split(),
+        })
     }
 
     /// Will only do anything if the current position is empty.
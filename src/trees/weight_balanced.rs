@@ -0,0 +1,884 @@
+//! Implementation of weight-balanced trees, also known as BB[α] trees.
+//!
+//! It is a balanced tree algorithm that supports reversals, splitting and concatenation.
+//! Its operations take `O(log n)` worst-case time.
+//!
+//! The tree is balanced by keeping the size of every subtree in its root node, and making
+//! sure that for every node, each of its sons holds at least an `α` fraction of its weight
+//! (the weight of a tree is its size plus one).
+//!
+//! Since the sizes of all subtrees are known, concatenating trees is efficient, and the
+//! join-based set operations [`union`], [`intersection`] and [`difference`] take
+//! `O(m*log(1+n/m))` worst-case time, where `m <= n` are the sizes of the two trees.
+
+use crate::locators;
+
+use super::basic_tree::*;
+use super::*;
+
+/// The type that is used for bookkeeping: the size of the subtree.
+type T = usize;
+
+/// The balance parameter `α` of the tree, as the fraction `ALPHA_NUMERATOR / ALPHA_DENOMINATOR`.
+/// Rebalancing by rotations works for `2/11 < α <= 1 - 1/sqrt(2)`.
+const ALPHA_NUMERATOR: usize = 1;
+const ALPHA_DENOMINATOR: usize = 4;
+
+/// A tree split into its left subtree, its root node, and its right subtree.
+type Exposed<D> = (WBTree<D>, Box<BasicNode<D, T>>, WBTree<D>);
+
+/// A weight-balanced tree. Balanced by keeping track of the subtree sizes, and keeping the sizes
+/// of the sons of every node within a constant factor of each other.
+pub struct WBTree<D: Data> {
+    tree: BasicTree<D, T>,
+}
+
+/// Returns `true` if two sibling trees with the weights `a` and `b` are balanced,
+/// i.e., each of them has at least an `α` fraction of their total weight.
+fn like(a: usize, b: usize) -> bool {
+    let total = ALPHA_NUMERATOR * (a + b);
+    ALPHA_DENOMINATOR * a >= total && ALPHA_DENOMINATOR * b >= total
+}
+
+/// For implementing `size` and `weight` for
+/// trees, nodes and walkers alike.
+trait Weighted {
+    fn size(&self) -> usize;
+
+    /// The weight of a tree, which is its size plus one.
+    fn weight(&self) -> usize {
+        self.size() + 1
+    }
+}
+
+impl<D: Data> Weighted for BasicTree<D, T> {
+    fn size(&self) -> usize {
+        match self.node() {
+            None => 0,
+            Some(node) => node.size(),
+        }
+    }
+}
+
+impl<D: Data> Weighted for BasicNode<D, T> {
+    fn size(&self) -> usize {
+        *self.alg_data()
+    }
+}
+
+impl<D: Data> BasicNode<D, T> {
+    /// Returns `true` if the size of the current node had to be updated,
+    /// `false` if it was correct.
+    fn rebuild_size(&mut self) -> bool {
+        let new_size = self.left.size() + self.right.size() + 1;
+        let changed = self.size() != new_size;
+        self.alg_data = new_size;
+        changed
+    }
+
+    /// Returns the weights of the left and right sons, taking into account
+    /// a reversal that might not have been pushed down yet.
+    fn son_weights(&self) -> (usize, usize) {
+        let (left, right) = (self.left.weight(), self.right.weight());
+        if self.action().to_reverse() {
+            (right, left)
+        } else {
+            (left, right)
+        }
+    }
+}
+
+impl<D: Data> WBTree<D> {
+    /// Creates an empty [`WBTree`].
+    pub fn new() -> Self {
+        WBTree {
+            tree: BasicTree::Empty,
+        }
+    }
+
+    /// Returns the number of values in the tree. Takes `O(1)` time.
+    pub fn size(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Asserts that the sizes and the balance at the current node are correct.
+    /// Otherwise, panics.
+    pub fn assert_weights_locally(&self) {
+        if let Some(node) = self.tree.node() {
+            Self::assert_weights_locally_internal(node);
+        }
+    }
+
+    fn assert_weights_locally_internal(node: &BasicNode<D, T>) {
+        assert_eq!(node.size(), node.left.size() + node.right.size() + 1);
+        assert!(like(node.left.weight(), node.right.weight()));
+    }
+
+    /// Asserts that the tree's sizes and balance are correct.
+    /// Otherwise, panics.
+    pub fn assert_weights(&self) {
+        self.tree
+            .assert_correctness_with(Self::assert_weights_locally_internal);
+    }
+
+    /// Splits the tree into the values with keys smaller than `key`,
+    /// the values with keys equal to `key`, and the values with keys bigger than `key`.
+    fn split_by_key(mut self, key: &D::Value) -> (WBTree<D>, WBTree<D>, WBTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let mut right = self
+            .slice(locators::LeftEdgeOf(locators::ByKey((key,))))
+            .split_right()
+            .unwrap();
+        let mid = right
+            .slice(locators::RightEdgeOf(locators::ByKey((key,))))
+            .split_left()
+            .unwrap();
+        (self, mid, right)
+    }
+
+    /// Splits a non-empty tree into its root and its two subtrees.
+    fn expose(self) -> Option<Exposed<D>> {
+        let mut node = self.tree.into_node_boxed()?;
+        node.access();
+        let left = WBTree {
+            tree: std::mem::replace(&mut node.left, BasicTree::Empty),
+        };
+        let right = WBTree {
+            tree: std::mem::replace(&mut node.right, BasicTree::Empty),
+        };
+        Some((left, node, right))
+    }
+
+    /// Concatenates the trees, with the given node in the middle.
+    fn join(mut left: WBTree<D>, mid: Box<BasicNode<D, T>>, right: WBTree<D>) -> WBTree<D> {
+        Self::concatenate_boxed_middle_right(&mut left.walker(), mid, right);
+        left
+    }
+
+    /// Computes the union of two trees, ordered by keys.
+    /// We order the resulting tree based on the `D::Value: Ord` instance, assuming that
+    /// the values in the existing trees are also in the correct order.
+    /// This is different from concatenate, because concatenate puts first all elements of the first tree,
+    /// and then all of the elements of the second tree.
+    ///
+    /// If elements with equal keys are found, all of them are kept, in an arbitrary order.
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn union(&mut self, other: WBTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let tree = std::mem::take(self);
+        *self = union(tree, other);
+    }
+
+    /// Keeps only the values that are equal to some value of `other`, ordered by keys.
+    /// We assume that the values in both trees are in sorted order.
+    ///
+    /// If `self` has several values equal to a value of `other`, all of them are kept.
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn intersection(&mut self, other: WBTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let tree = std::mem::take(self);
+        *self = intersection(tree, other);
+    }
+
+    /// Removes all the values that are equal to some value of `other`, ordered by keys.
+    /// We assume that the values in both trees are in sorted order.
+    ///
+    /// # Complexity
+    /// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+    /// `O(m*log(1+n/m))` in the worst case.
+    pub fn difference(&mut self, other: WBTree<D>)
+    where
+        D::Value: Ord,
+    {
+        let tree = std::mem::take(self);
+        *self = difference(tree, other);
+    }
+}
+
+impl<D: Data> Default for WBTree<D> {
+    fn default() -> Self {
+        WBTree::new()
+    }
+}
+
+impl<D: Data> AsBasicTree<D> for WBTree<D> {
+    fn as_basic_tree(&self) -> &BasicTree<D, T> {
+        &self.tree
+    }
+}
+
+impl<D: Data> sealed::AsBasicTreeMut<D> for WBTree<D> {
+    type AlgData = T;
+
+    fn as_basic_tree_mut(&mut self, _: sealed::Token) -> &mut BasicTree<D, T> {
+        &mut self.tree
+    }
+}
+
+impl<D: Data> SomeTree<D> for WBTree<D> {
+    fn segment_summary_imm<L>(&self, locator: L) -> D::Summary
+    where
+        L: crate::Locator<D>,
+        D::Value: Clone,
+    {
+        segment_algorithms::segment_summary_imm(&self.tree, locator)
+    }
+
+    fn segment_summary<L>(&mut self, locator: L) -> D::Summary
+    where
+        L: crate::Locator<D>,
+    {
+        segment_algorithms::segment_summary(self, locator)
+    }
+
+    fn act_segment<L>(&mut self, action: D::Action, locator: L)
+    where
+        L: crate::Locator<D>,
+    {
+        if !action.to_reverse() {
+            segment_algorithms::act_segment(self, action, locator)
+        } else {
+            // split out the middle
+            let mut mid: WBTree<D> = self
+                .slice(locators::LeftEdgeOf(locator.clone()))
+                .split_right()
+                .unwrap();
+
+            let mut walker2 = WBWalker {
+                walker: BasicWalker::new_with_context(
+                    &mut mid.tree,
+                    self.subtree_summary(),
+                    Default::default(),
+                ),
+            };
+            walker2.search_subtree(locators::RightEdgeOf(locator));
+            let right = walker2.split_right().unwrap();
+            drop(walker2);
+
+            // apply action
+            mid.act_subtree(action);
+
+            // glue back together
+            mid.concatenate_right(right);
+            self.concatenate_right(mid);
+        }
+    }
+
+    type TreeData = T;
+    fn iter_locator<'a, L: locators::Locator<D>>(
+        &'a mut self,
+        locator: L,
+    ) -> basic_tree::iterators::IterLocator<'a, D, L, T> {
+        iterators::IterLocator::new(&mut self.tree, locator)
+    }
+
+    fn assert_correctness(&self)
+    where
+        D::Summary: Eq,
+    {
+        self.tree.assert_correctness_with(|node| {
+            node.assert_correctness_locally();
+            Self::assert_weights_locally_internal(node);
+        });
+    }
+}
+
+impl<'a, D: Data> SomeTreeRef<D> for &'a mut WBTree<D> {
+    type Walker = WBWalker<'a, D>;
+
+    fn walker(self) -> Self::Walker {
+        WBWalker {
+            walker: self.tree.walker(),
+        }
+    }
+}
+
+impl<'a, D: Data> ModifiableTreeRef<D> for &'a mut WBTree<D> {
+    type ModifiableWalker = WBWalker<'a, D>;
+}
+
+impl<'a, D: Data> SplittableTreeRef<D> for &'a mut WBTree<D> {
+    type T = WBTree<D>;
+
+    type SplittableWalker = WBWalker<'a, D>;
+}
+
+derive_SomeEntry! {tree, T,
+    impl<D: Data> SomeEntry<D> for WBTree<D> {
+        fn assert_correctness_locally(&self)
+        where
+            D::Summary: Eq,
+        {
+            if let Some(node) = self.tree.node() {
+                Self::assert_weights_locally_internal(node);
+                node.assert_correctness_locally();
+            }
+        }
+    }
+}
+
+/// Builds a perfectly balanced tree out of the next `count` values of the iterator.
+fn build_balanced<D: Data, I>(values: &mut I, count: usize) -> BasicTree<D, T>
+where
+    I: Iterator<Item = D::Value>,
+{
+    if count == 0 {
+        return BasicTree::Empty;
+    }
+    let left_count = count / 2;
+    let left = build_balanced(values, left_count);
+    let mut node = BasicNode::new_alg(values.next().expect("Not enough values"), count);
+    node.left = left;
+    node.right = build_balanced(values, count - left_count - 1);
+    node.rebuild();
+    BasicTree::from_node(node)
+}
+
+impl<D: Data> std::iter::FromIterator<D::Value> for WBTree<D> {
+    /// This takes `O(n)` worst-case time.
+    fn from_iter<I: IntoIterator<Item = D::Value>>(iter: I) -> Self {
+        let values: Vec<D::Value> = iter.into_iter().collect();
+        let count = values.len();
+        WBTree {
+            tree: build_balanced(&mut values.into_iter(), count),
+        }
+    }
+}
+
+impl<D: Data> IntoIterator for WBTree<D> {
+    type Item = D::Value;
+    type IntoIter = iterators::IntoIter<D, std::ops::RangeFull, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        iterators::IntoIter::new(self.tree, ..)
+    }
+}
+
+/// A walker struct for [`WBTree`].
+pub struct WBWalker<'a, D: Data> {
+    walker: BasicWalker<'a, D, T>,
+}
+
+derive_SomeWalker! {walker,
+    impl<'a, D: Data> SomeWalker<D> for WBWalker<'a, D> {
+        fn go_up(&mut self) -> Result<Side, ()> {
+            let res = self.walker.go_up()?;
+            let changed = self.walker.node_mut().unwrap().rebuild_size();
+            assert!(!changed); // it shouldn't have changed without being rebalanced already
+            Ok(res)
+        }
+    }
+}
+
+derive_SomeEntry! {walker, T,
+    impl<'a, D: Data> SomeEntry<D> for WBWalker<'a, D> {
+        fn assert_correctness_locally(&self)
+        where
+            D::Summary: Eq,
+        {
+            self.walker.assert_correctness_locally();
+            if let Some(node) = self.walker.node() {
+                WBTree::assert_weights_locally_internal(node);
+            }
+        }
+    }
+}
+
+impl<'a, D: Data> Weighted for WBWalker<'a, D> {
+    /// Returns the size of the current subtree.
+    fn size(&self) -> usize {
+        self.walker.inner().size()
+    }
+}
+
+impl<'a, D: Data> WBWalker<'a, D> {
+    fn inner_mut(&mut self) -> &mut BasicTree<D, T> {
+        self.walker.inner_mut()
+    }
+
+    fn rot_left(&mut self) -> Option<()> {
+        let rebuilder = |node: &mut BasicNode<D, T>| {
+            node.rebuild_size();
+        };
+        self.walker.rot_left_with_custom_rebuilder(rebuilder)
+    }
+
+    fn rot_right(&mut self) -> Option<()> {
+        let rebuilder = |node: &mut BasicNode<D, T>| {
+            node.rebuild_size();
+        };
+        self.walker.rot_right_with_custom_rebuilder(rebuilder)
+    }
+
+    fn rot_up(&mut self) -> Result<Side, ()> {
+        let rebuilder = |node: &mut BasicNode<D, T>| {
+            node.rebuild_size();
+        };
+        self.walker.rot_up_with_custom_rebuilder(rebuilder)
+    }
+
+    /// Fixes the size of the current node, and rebalances it, assuming
+    /// both of its subtrees are already balanced.
+    /// The walker stays at the same position, which might now hold a different node.
+    fn balance_node(&mut self) {
+        let node = match self.walker.node_mut() {
+            None => return,
+            Some(node) => node,
+        };
+        node.rebuild_size();
+        let (left, right) = node.son_weights();
+        if like(left, right) {
+            return;
+        }
+
+        if left < right {
+            // right is too heavy
+            let (right_left, right_right) = node.right.node().unwrap().son_weights();
+            if like(left, right_left) && like(left + right_left, right_right) {
+                self.rot_left().unwrap();
+            } else {
+                self.walker.go_right().unwrap();
+                self.rot_right().unwrap();
+                let res = self.rot_up();
+                assert!(res == Ok(Side::Right));
+            }
+        } else {
+            // left is too heavy
+            let (left_left, left_right) = node.left.node().unwrap().son_weights();
+            if like(right, left_right) && like(right + left_right, left_left) {
+                self.rot_right().unwrap();
+            } else {
+                self.walker.go_left().unwrap();
+                self.rot_left().unwrap();
+                let res = self.rot_up();
+                assert!(res == Ok(Side::Left));
+            }
+        }
+    }
+
+    /// This function gets called when the current subtree was changed, e.g.,
+    /// a node was inserted or deleted, or trees were joined.
+    /// Rebalances all the nodes up to the root, and ends at the root.
+    fn rebalance(&mut self) {
+        loop {
+            self.balance_node();
+            // sizes may be incorrect, so go up with the inner walker
+            if self.walker.go_up().is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Deletes a node and returns it with the box.
+    /// The walker reorganizes the current subtree in order to delete the current node,
+    /// and then rebalances, ending at the root.
+    fn delete_boxed(&mut self) -> Option<Box<BasicNode<D, T>>> {
+        // the delete implementation is modified from `BasicTree`,
+        // in order for rebalancing to be done properly.
+        let mut node = self.walker.take_subtree().into_node_boxed()?;
+        if node.right.is_empty() {
+            self.walker.put_subtree(node.left).unwrap();
+            node.left = BasicTree::Empty;
+        } else {
+            // find the next node and move it to the current position
+            let mut walker = node.right.walker();
+            while walker.go_left().is_ok() {}
+            let res = walker.go_up();
+            assert_eq!(res, Ok(Side::Left));
+
+            let mut boxed_replacement_node = walker.take_subtree().into_node_boxed().unwrap();
+            assert!(boxed_replacement_node.left.is_empty());
+            walker.put_subtree(boxed_replacement_node.right).unwrap();
+            WBWalker { walker }.rebalance(); // rebalance here
+
+            boxed_replacement_node.left = node.left;
+            node.left = BasicTree::Empty;
+            boxed_replacement_node.right = node.right;
+            node.right = BasicTree::Empty;
+            boxed_replacement_node.rebuild();
+            self.walker
+                .put_subtree(BasicTree::from_boxed_node(boxed_replacement_node))
+                .unwrap();
+        }
+        self.rebalance(); // rebalance here
+        Some(node)
+    }
+}
+
+impl<'a, D: Data> ModifiableWalker<D> for WBWalker<'a, D> {
+    /// Inserts the value into the tree at the current empty position.
+    /// If the current position is not empty, return [`None`].
+    /// When the function returns, the walker will be at the root of the tree.
+    fn insert(&mut self, val: D::Value) -> Option<()> {
+        self.walker
+            .insert_with_alg_data(val, 1 /* size of a single node */)?;
+        self.rebalance();
+        Some(())
+    }
+
+    /// The walker reorganizes the current subtree in order to delete the current node,
+    /// and then rebalances. When the function returns, the walker will be at the root of the tree.
+    fn delete(&mut self) -> Option<D::Value> {
+        Some(self.delete_boxed()?.node_value)
+    }
+}
+
+impl<'a, D: Data> SplittableWalker<D> for WBWalker<'a, D> {
+    type T = WBTree<D>;
+
+    /// Will only do anything if the current position is empty.
+    /// If it is empty, it will split the tree: the elements
+    /// to the left will remain, and the elements to the right
+    /// will be put in the new output tree.
+    /// The walker will be at the root after this operation, if it succeeds.
+    ///
+    ///```
+    /// use grove::{SomeTree, weight_balanced::WBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: WBTree<StdNum> = (17..88).collect();
+    /// let mut tree2 = tree.slice(7..7).split_right().unwrap();
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..24).collect::<Vec<_>>());
+    /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (24..88).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn split_right(&mut self) -> Option<Self::T> {
+        if !self.is_empty() {
+            return None;
+        }
+        let mut left_t = WBTree::new();
+        let mut right_t = WBTree::new();
+        let mut left = left_t.walker();
+        let mut right = right_t.walker();
+
+        // sizes may be incorrect, so go up with the inner walker
+        while let Ok(side) = self.walker.go_up() {
+            // `node.action` is the identity, since we just moved up.
+            let mut node = self.walker.take_subtree().into_node_boxed().unwrap();
+            match side {
+                Side::Left => {
+                    assert!(node.left.is_empty());
+                    let auxiliary_right = WBTree { tree: node.right };
+                    node.right = BasicTree::Empty;
+                    WBTree::concatenate_boxed_middle_right(&mut right, node, auxiliary_right);
+                }
+                Side::Right => {
+                    assert!(node.right.is_empty());
+                    let auxiliary_left = WBTree { tree: node.left };
+                    node.left = BasicTree::Empty;
+                    WBTree::concatenate_boxed_middle_left(&mut left, auxiliary_left, node);
+                }
+            }
+        }
+
+        // Drop the walkers so that we can access the `left, right` trees themselves.
+        std::mem::drop(left);
+        std::mem::drop(right);
+        // the `self` tree is empty by this point.
+        self.walker.put_subtree(left_t.tree).unwrap();
+        Some(right_t)
+    }
+
+    /// Will only do anything if the current position is empty.
+    /// If it is empty, it will split the tree: the elements
+    /// to the left will remain, and the elements to the right
+    /// will be put in the new output tree.
+    /// The walker will be at the root after this operation, if it succeeds.
+    ///
+    ///```
+    /// use grove::{SomeTree, weight_balanced::WBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: WBTree<StdNum> = (17..88).collect();
+    /// let mut tree2 = tree.slice(7..7).split_left().unwrap();
+    ///
+    /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (17..24).collect::<Vec<_>>());
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (24..88).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn split_left(&mut self) -> Option<Self::T> {
+        let mut right = self.split_right()?;
+        std::mem::swap(&mut right.tree, self.inner_mut());
+        Some(right)
+    }
+}
+
+impl<D: Data> WBTree<D> {
+    /// Concatenates the trees together, in place, with a given value for the middle.
+    /// Complexity: `O(log n)`. More precisely, `O(1 + log(n/m))` where `m <= n` are the sizes
+    /// of the two trees.
+    ///```
+    /// use grove::{SomeTree, weight_balanced::WBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: WBTree<StdNum> = (17..=89).collect();
+    /// let tree2: WBTree<StdNum> = (13..=25).collect();
+    /// tree.concatenate_middle_right(5, tree2);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(5..=5).chain(13..=25).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    pub fn concatenate_middle_right(&mut self, mid: D::Value, right: WBTree<D>) {
+        let node = BasicNode::new_alg(mid, 1);
+        Self::concatenate_boxed_middle_right(&mut self.walker(), Box::new(node), right);
+    }
+
+    fn concatenate_boxed_middle_right(
+        left: &mut WBWalker<D>,
+        mut mid: Box<BasicNode<D, T>>,
+        mut right: WBTree<D>,
+    ) {
+        // The walker must be at its root
+        assert!(left.depth() == 0);
+        assert!(mid.action().is_identity());
+        if left.size() < right.size() {
+            std::mem::swap(left.inner_mut(), &mut right.tree);
+            // `right.tree` might still have an action in it, but walkers aren't allowed to.
+            left.inner_mut().access();
+            Self::concatenate_boxed_middle_left(left, right, mid);
+            return;
+        }
+        let walker = left;
+        let right_weight = right.tree.weight();
+        while !like(walker.weight(), right_weight) {
+            walker.go_right().unwrap();
+        }
+        mid.left = walker.walker.take_subtree();
+        mid.right = right.tree;
+        mid.rebuild();
+        mid.rebuild_size();
+        walker
+            .walker
+            .put_subtree(BasicTree::from_boxed_node(mid))
+            .unwrap();
+        walker.rebalance();
+    }
+
+    /// Concatenates the trees together, in place, with a given value for the middle.
+    /// Complexity: `O(log n)`. More precisely, `O(1 + log(n/m))` where `m <= n` are the sizes
+    /// of the two trees.
+    ///```
+    /// use grove::{SomeTree, weight_balanced::WBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let tree1: WBTree<StdNum> = (17..=89).collect();
+    /// let mut tree2: WBTree<StdNum> = (13..=25).collect();
+    /// tree2.concatenate_middle_left(tree1, 5);
+    ///
+    /// assert_eq!(tree2.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(5..=5).chain(13..=25).collect::<Vec<_>>());
+    /// # tree2.assert_correctness();
+    ///```
+    pub fn concatenate_middle_left(&mut self, left: WBTree<D>, mid: D::Value) {
+        let node = BasicNode::new_alg(mid, 1);
+        Self::concatenate_boxed_middle_left(&mut self.walker(), left, Box::new(node));
+    }
+
+    fn concatenate_boxed_middle_left(
+        right: &mut WBWalker<D>,
+        mut left: WBTree<D>,
+        mut mid: Box<BasicNode<D, T>>,
+    ) {
+        // The walker must be at its root
+        assert!(right.depth() == 0);
+        assert!(mid.action().is_identity());
+        if right.size() < left.size() {
+            std::mem::swap(right.inner_mut(), &mut left.tree);
+            // `left.tree` might still have an action in it, but walkers aren't allowed to.
+            right.inner_mut().access();
+            Self::concatenate_boxed_middle_right(right, mid, left);
+            return;
+        }
+        let walker = right;
+        let left_weight = left.tree.weight();
+        while !like(walker.weight(), left_weight) {
+            walker.go_left().unwrap();
+        }
+        mid.right = walker.walker.take_subtree();
+        mid.left = left.tree;
+        mid.rebuild();
+        mid.rebuild_size();
+        walker
+            .walker
+            .put_subtree(BasicTree::from_boxed_node(mid))
+            .unwrap();
+        walker.rebalance();
+    }
+}
+
+impl<D: Data> ConcatenableTree<D> for WBTree<D> {
+    /// Concatenates the trees together, in place.
+    /// Complexity: `O(log n)`.
+    ///```
+    /// use grove::{SomeTree, ConcatenableTree, weight_balanced::WBTree};
+    /// use grove::example_data::StdNum;
+    ///
+    /// let mut tree: WBTree<StdNum> = (17..=89).collect();
+    /// let tree2: WBTree<StdNum> = (13..=25).collect();
+    /// tree.concatenate_right(tree2);
+    ///
+    /// assert_eq!(tree.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(13..=25).collect::<Vec<_>>());
+    /// # tree.assert_correctness();
+    ///```
+    fn concatenate_right(&mut self, right: Self) {
+        if !self.is_empty() {
+            let mut walker = self.search(locators::RightEdgeOf(..));
+            walker.go_up().unwrap();
+            let mid = walker.delete_boxed().unwrap();
+            Self::concatenate_boxed_middle_right(&mut walker, mid, right);
+        } else {
+            self.tree = right.tree;
+        }
+    }
+}
+
+/// Concatenates the trees together, in place, with a given value for the middle.
+/// Complexity: `O(log n)`. More precisely, `O(1 + log(n/m))` where `m <= n` are the sizes
+/// of the two trees.
+///```
+/// use grove::{SomeTree, weight_balanced::WBTree, weight_balanced::concatenate_with_middle};
+/// use grove::example_data::StdNum;
+///
+/// let tree1: WBTree<StdNum> = (17..=89).collect();
+/// let tree2: WBTree<StdNum> = (13..=25).collect();
+/// let mut tree3 = concatenate_with_middle(tree1, 5, tree2);
+///
+/// assert_eq!(tree3.iter().cloned().collect::<Vec<_>>(), (17..=89).chain(5..=5).chain(13..=25).collect::<Vec<_>>());
+/// # tree3.assert_correctness();
+///```
+pub fn concatenate_with_middle<D: Data>(
+    mut left: WBTree<D>,
+    mid: D::Value,
+    right: WBTree<D>,
+) -> WBTree<D> {
+    left.concatenate_middle_right(mid, right);
+    left
+}
+
+/// Computes the union of two trees, ordered by keys.
+/// We order the resulting tree based on the `D::Value: Ord` instance, assuming that
+/// the values in the existing trees are also in the correct order.
+/// This is different from concatenate, because concatenate puts first all elements of the first tree,
+/// and then all of the elements of the second tree.
+///
+/// If elements with equal keys are found, all of them are kept, in an arbitrary order.
+///
+///```rust
+///use grove::{SomeTree, weight_balanced, weight_balanced::WBTree};
+///use grove::example_data::{PlainData};
+///
+///type T = WBTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = weight_balanced::union(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(),
+///    [0,1,2,3,4,4,5,5,6,6,7,8].iter().cloned().collect::<Vec<_>>());
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn union<D: Data>(tree1: WBTree<D>, tree2: WBTree<D>) -> WBTree<D>
+where
+    D::Value: Ord,
+{
+    if tree1.is_empty() {
+        return tree2;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return tree1,
+        Some(parts) => parts,
+    };
+    // values equal to `mid` are put to its right
+    let (left1, mid1, right1) = tree1.split_by_key(mid.node_value_clean());
+    let mut right = union(right1, right2);
+    right.concatenate_left(mid1);
+    WBTree::join(union(left1, left2), mid, right)
+}
+
+/// Computes the intersection of two trees, ordered by keys:
+/// Keeps the values of `tree1` that are equal to some value of `tree2`.
+/// We assume that the values in both trees are in sorted order.
+///
+/// If `tree1` has several values equal to a value of `tree2`, all of them are kept.
+///
+///```rust
+///use grove::{SomeTree, weight_balanced, weight_balanced::WBTree};
+///use grove::example_data::{PlainData};
+///
+///type T = WBTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = weight_balanced::intersection(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![4,5,6]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn intersection<D: Data>(tree1: WBTree<D>, tree2: WBTree<D>) -> WBTree<D>
+where
+    D::Value: Ord,
+{
+    if tree1.is_empty() {
+        return tree1;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return WBTree::new(),
+        Some(parts) => parts,
+    };
+    let (left1, mut mid1, right1) = tree1.split_by_key(mid.node_value_clean());
+    let left = intersection(left1, left2);
+    let right = intersection(right1, right2);
+    mid1.concatenate_left(left);
+    mid1.concatenate_right(right);
+    mid1
+}
+
+/// Computes the difference of two trees, ordered by keys:
+/// Keeps the values of `tree1` that are not equal to any value of `tree2`.
+/// We assume that the values in both trees are in sorted order.
+///
+///```rust
+///use grove::{SomeTree, weight_balanced, weight_balanced::WBTree};
+///use grove::example_data::{PlainData};
+///
+///type T = WBTree<PlainData<i32>>;
+///let tree1: T = (0..7).collect();
+///let tree2: T = (4..9).collect();
+///let tree = weight_balanced::difference(tree1, tree2);
+/// # tree.assert_correctness();
+///assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![0,1,2,3]);
+///```
+///
+/// # Complexity
+/// If the sizes of the two trees are `m,n`, with `m <= n`, then the complexity is
+/// `O(m*log(1+n/m))` in the worst case.
+pub fn difference<D: Data>(tree1: WBTree<D>, tree2: WBTree<D>) -> WBTree<D>
+where
+    D::Value: Ord,
+{
+    if tree1.is_empty() {
+        return tree1;
+    }
+    let (left2, mid, right2) = match tree2.expose() {
+        None => return tree1,
+        Some(parts) => parts,
+    };
+    let (left1, _, right1) = tree1.split_by_key(mid.node_value_clean());
+    WBTree::concatenate(difference(left1, left2), difference(right1, right2))
+}
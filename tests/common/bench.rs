@@ -2,7 +2,9 @@ use super::*;
 extern crate test;
 use test::Bencher;
 
-use grove::{avl::AVLTree, splay::SplayTree, treap::Treap};
+use grove::{
+    avl::AVLTree, red_black::RBTree, splay::SplayTree, treap::Treap, weight_balanced::WBTree,
+};
 
 pub fn bench_tree<D, T>(b: &mut Bencher, mutable: bool)
 where
@@ -18,7 +20,7 @@ where
     let range = 0..(len as _);
     let mut tree: T = range.clone().collect();
     b.iter(|| {
-        let round_action = random_round_action::<D>(&mut rng, len);
+        let round_action = random_round_action::<D, _>(&mut rng, len, true);
         let res = run_round(round_action.clone(), &mut tree, len, mutable);
         test::bench::black_box(res);
 
@@ -56,6 +58,16 @@ fn bench_avl(b: &mut Bencher) {
     bench_tree::<StdNum, AVLTree<_>>(b, true)
 }
 
+#[bench]
+fn bench_rb(b: &mut Bencher) {
+    bench_tree::<StdNum, RBTree<_>>(b, true)
+}
+
+#[bench]
+fn bench_wb(b: &mut Bencher) {
+    bench_tree::<StdNum, WBTree<_>>(b, true)
+}
+
 #[bench]
 fn bench_splay_imm(b: &mut Bencher) {
     bench_tree::<StdNum, SplayTree<_>>(b, false)
@@ -70,3 +82,13 @@ fn bench_treap_imm(b: &mut Bencher) {
 fn bench_avl_imm(b: &mut Bencher) {
     bench_tree::<StdNum, AVLTree<_>>(b, false)
 }
+
+#[bench]
+fn bench_rb_imm(b: &mut Bencher) {
+    bench_tree::<StdNum, RBTree<_>>(b, false)
+}
+
+#[bench]
+fn bench_wb_imm(b: &mut Bencher) {
+    bench_tree::<StdNum, WBTree<_>>(b, false)
+}
@@ -1,9 +1,11 @@
 #[cfg(feature = "bench")]
 pub mod bench;
 
-use example_data::{RevAffineAction, StdNum};
+use example_data::{
+    BeatsSummary, ChminChmaxAction, ChminChmaxSum, NumSummary, PlainData, RevAffineAction, StdNum,
+};
 use grove::*;
-use rand::{self, Rng};
+use rand::{self, rngs::StdRng, Rng, SeedableRng};
 use std::ops::Range;
 
 /// Something to perform in one round of tests
@@ -33,8 +35,25 @@ enum RoundResult<D: Data> {
     Value(D::Value),
 }
 
-fn random_range(len: usize) -> Range<usize> {
-    let mut rng = rand::thread_rng();
+/// The environment variable that can be used to replay a failing randomized test.
+const SEED_VAR: &str = "GROVE_TEST_SEED";
+
+/// Returns a seeded random number generator for randomized tests.
+/// The seed is taken from the `GROVE_TEST_SEED` environment variable if it is set,
+/// and is chosen randomly otherwise. The seed is printed, so that
+/// a failing run can be replayed by setting `GROVE_TEST_SEED`.
+pub fn test_rng() -> StdRng {
+    let seed = match std::env::var(SEED_VAR) {
+        Ok(seed) => seed
+            .parse()
+            .unwrap_or_else(|_| panic!("{} should be a u64", SEED_VAR)),
+        Err(_) => rand::random(),
+    };
+    println!("{}={}", SEED_VAR, seed);
+    StdRng::seed_from_u64(seed)
+}
+
+fn random_range<R: Rng>(rng: &mut R, len: usize) -> Range<usize> {
     let res = (rng.gen_range(0..len + 1), rng.gen_range(0..len + 1));
     if res.0 <= res.1 {
         res.0..res.1
@@ -44,15 +63,61 @@ fn random_range(len: usize) -> Range<usize> {
 }
 
 const MAX_ADD: i32 = 200;
-fn random_action(rng: &mut rand::prelude::ThreadRng) -> RevAffineAction {
+fn random_action<R: Rng>(rng: &mut R, allow_reversals: bool) -> RevAffineAction {
     RevAffineAction {
-        to_reverse: rng.gen(),
+        to_reverse: allow_reversals && rng.gen(),
         mul: if rng.gen() { 1 } else { -1 },
         add: rng.gen_range(-MAX_ADD..=MAX_ADD),
     }
 }
 
-fn random_round_action<D>(rng: &mut rand::prelude::ThreadRng, len: usize) -> RoundAction<D>
+/// Applies an action to a segment of a reference vector, the way a tree applies it to a segment.
+fn apply_to_reference<A: Action + Acts<i32>>(segment: &mut [i32], action: A) {
+    if action.to_reverse() {
+        segment.reverse();
+    }
+    for value in segment.iter_mut() {
+        action.act_inplace(value);
+    }
+}
+
+/// Runs random rounds on a tree and on a vector of its values, and checks the tree
+/// after every round. Every round either inserts a random value from `values` at a random index,
+/// deletes the value at a random index, or calls `round` to act on or query the tree and the vector.
+fn run_vec_rounds<D, T, F>(
+    num_rounds: u32,
+    values: Range<i32>,
+    mut rng: StdRng,
+    tree: &mut T,
+    reference: &mut Vec<i32>,
+    mut round: F,
+) where
+    D: Data<Value = i32>,
+    D::Summary: Eq + SizedSummary,
+    T: SomeTree<D>,
+    for<'a> &'a mut T: ModifiableTreeRef<D>,
+    F: FnMut(&mut StdRng, &mut T, &mut Vec<i32>),
+{
+    for _ in 0..num_rounds {
+        let len = reference.len();
+        match rng.gen_range(0..4) {
+            0 => {
+                let index = rng.gen_range(0..=len);
+                let value = rng.gen_range(values.clone());
+                tree.slice(index..index).insert(value).unwrap();
+                reference.insert(index, value);
+            }
+            1 if len > 0 => {
+                let index = rng.gen_range(0..len);
+                assert_eq!(tree.slice(index).delete(), Some(reference.remove(index)));
+            }
+            _ => round(&mut rng, tree, reference),
+        }
+        tree.assert_correctness();
+    }
+}
+
+fn random_round_action<D, R: Rng>(rng: &mut R, len: usize, allow_reversals: bool) -> RoundAction<D>
 where
     D: Data<Value = i32, Action = RevAffineAction>,
     D::Summary: std::fmt::Debug + Eq + SizedSummary,
@@ -61,13 +126,13 @@ where
     match rng.gen_range(0..4) {
         // act on a segment
         0 => {
-            let range = random_range(len);
-            let action = random_action(rng);
+            let range = random_range(rng, len);
+            let action = random_action(rng, allow_reversals);
             Act { action, range }
         }
         // query a segment
         1 => {
-            let range = random_range(len);
+            let range = random_range(rng, len);
             Query { range }
         }
         // insert a value
@@ -143,6 +208,8 @@ where
 }
 
 const INITIAL_SIZE: usize = 200;
+/// The number of rounds in the set algorithm tests, whose rounds each build new trees.
+const NUM_SET_ROUNDS: u32 = if cfg!(not(miri)) { 200 } else { 10 }; // miri is too slow
 pub fn check_consistency<D, T1, T2>(num_rounds: u32)
 where
     D: Data<Value = i32, Action = RevAffineAction>,
@@ -153,7 +220,36 @@ where
     T2: SomeTree<D>,
     for<'a> &'a mut T2: ModifiableTreeRef<D>,
 {
-    let mut rng = rand::thread_rng();
+    check_consistency_with::<D, T1, T2>(num_rounds, true);
+}
+
+/// Same as [`check_consistency`], but never reverses segments.
+/// For trees that don't support reversals.
+pub fn check_consistency_without_reversals<D, T1, T2>(num_rounds: u32)
+where
+    D: Data<Value = i32, Action = RevAffineAction>,
+    D: Clone + std::fmt::Debug + Eq, // useless bounds because the auto-generated clone instance for RoundAction requires it
+    D::Summary: std::fmt::Debug + Eq + SizedSummary,
+    T1: SomeTree<D>,
+    for<'a> &'a mut T1: ModifiableTreeRef<D>,
+    T2: SomeTree<D>,
+    for<'a> &'a mut T2: ModifiableTreeRef<D>,
+{
+    check_consistency_with::<D, T1, T2>(num_rounds, false);
+}
+
+/// Runs the same random rounds on both trees, and checks that their results are the same.
+fn check_consistency_with<D, T1, T2>(num_rounds: u32, allow_reversals: bool)
+where
+    D: Data<Value = i32, Action = RevAffineAction>,
+    D: Clone + std::fmt::Debug + Eq, // useless bounds because the auto-generated clone instance for RoundAction requires it
+    D::Summary: std::fmt::Debug + Eq + SizedSummary,
+    T1: SomeTree<D>,
+    for<'a> &'a mut T1: ModifiableTreeRef<D>,
+    T2: SomeTree<D>,
+    for<'a> &'a mut T2: ModifiableTreeRef<D>,
+{
+    let mut rng = test_rng();
     let mut len: usize = INITIAL_SIZE;
 
     let range = 0..(len as _);
@@ -161,7 +257,7 @@ where
     let mut tree2: T2 = range.collect();
 
     for _ in 0..num_rounds {
-        let round_action = random_round_action::<D>(&mut rng, len);
+        let round_action = random_round_action::<D, _>(&mut rng, len, allow_reversals);
         let res1 = run_round(round_action.clone(), &mut tree1, len, true);
         let res2 = run_round(round_action.clone(), &mut tree2, len, false);
         assert_eq!(res1, res2);
@@ -190,6 +286,58 @@ where
     }
 }
 
+/// Checks deleting values with a walker, when their sons have pending actions.
+/// Deleting moves a son into the deleted value's place, where it must be accessed.
+pub fn check_delete_with_pending_actions<T>(num_rounds: u32, allow_reversals: bool)
+where
+    T: SomeTree<StdNum>,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+{
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE as i32).collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    for _ in 0..num_rounds {
+        if reference.len() < INITIAL_SIZE / 2 {
+            let value = rng.gen_range(-MAX_ADD..=MAX_ADD);
+            tree.slice(0..0).insert(value).unwrap();
+            reference.insert(0, value);
+        }
+        let range = random_range(&mut rng, reference.len());
+        let action = random_action(&mut rng, allow_reversals);
+        tree.act_segment(action, range.clone());
+        apply_to_reference(&mut reference[range], action);
+
+        let index = rng.gen_range(0..reference.len());
+        let mut walker = tree.search(index);
+        assert_eq!(walker.delete(), Some(reference.remove(index)));
+        // the walker must stay at an accessed node, whose value is up to date
+        if let Some(&value) = walker.value() {
+            assert!(reference.contains(&value));
+        }
+        drop(walker);
+        tree.assert_correctness();
+    }
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), reference);
+}
+
+/// Checks the summaries that a walker sees to its left and to its right.
+pub fn check_walker_summaries<T>()
+where
+    T: SomeTree<StdNum>,
+    for<'a> &'a mut T: SomeTreeRef<StdNum>,
+{
+    let mut tree: T = (0..INITIAL_SIZE as i32).collect();
+    for index in 0..INITIAL_SIZE {
+        let walker = tree.search(index);
+        assert_eq!(walker.left_summary().size, index as i32);
+        assert_eq!(
+            walker.right_summary().size,
+            (INITIAL_SIZE - 1 - index) as i32
+        );
+    }
+}
+
 pub fn check_delete<T>()
 where
     T: SomeTree<StdNum>,
@@ -246,3 +394,2087 @@ where
         );
     }
 }
+
+/// Checks the set operations of a tree type against the same operations on sorted vectors.
+pub fn check_set_operations<T>(
+    union: impl Fn(T, T) -> T,
+    intersection: impl Fn(T, T) -> T,
+    difference: impl Fn(T, T) -> T,
+) where
+    T: SomeTree<PlainData<i32>>,
+    for<'a> &'a mut T: SomeTreeRef<PlainData<i32>>,
+{
+    let mut rng = test_rng();
+    for _ in 0..NUM_SET_ROUNDS {
+        let len1 = rng.gen_range(0..300);
+        let len2 = rng.gen_range(0..300);
+        let mut vec1: Vec<i32> = (0..len1).map(|_| rng.gen_range(0..500)).collect();
+        let mut vec2: Vec<i32> = (0..len2).map(|_| rng.gen_range(0..500)).collect();
+        vec1.sort_unstable();
+        vec1.dedup();
+        vec2.sort_unstable();
+        vec2.dedup();
+        let tree = |vec: &Vec<i32>| vec.iter().cloned().collect::<T>();
+
+        let mut expected: Vec<i32> = vec1.iter().chain(vec2.iter()).cloned().collect();
+        expected.sort_unstable();
+        let res = union(tree(&vec1), tree(&vec2));
+        res.assert_correctness();
+        assert_eq!(res.into_iter().collect::<Vec<_>>(), expected);
+
+        let expected: Vec<i32> = vec1.iter().filter(|x| vec2.contains(x)).cloned().collect();
+        let res = intersection(tree(&vec1), tree(&vec2));
+        res.assert_correctness();
+        assert_eq!(res.into_iter().collect::<Vec<_>>(), expected);
+
+        let expected: Vec<i32> = vec1.iter().filter(|x| !vec2.contains(x)).cloned().collect();
+        let res = difference(tree(&vec1), tree(&vec2));
+        res.assert_correctness();
+        assert_eq!(res.into_iter().collect::<Vec<_>>(), expected);
+    }
+}
+
+/// Runs random rounds on a [`PersistentTreap`] and on a regular tree, and compares their results.
+/// A snapshot of the persistent treap is kept after every round, and in the end,
+/// every snapshot is checked to be unaffected by the later rounds.
+///
+/// [`PersistentTreap`]: grove::persistent::treap::PersistentTreap
+pub fn check_persistent_treap(num_rounds: u32) {
+    use grove::{persistent::treap::PersistentTreap, splay::SplayTree};
+    use RoundAction::*;
+
+    let mut rng = test_rng();
+    let mut len: usize = INITIAL_SIZE;
+
+    let range = 0..(len as _);
+    let mut reference: SplayTree<StdNum> = range.clone().collect();
+    let mut tree: PersistentTreap<StdNum> = range.collect();
+    let mut snapshots = vec![];
+
+    for _ in 0..num_rounds {
+        let round_action = random_round_action::<StdNum, _>(&mut rng, len, true);
+        let expected = run_round(round_action.clone(), &mut reference, len, true);
+        let res = match round_action {
+            Act { range, action } => {
+                tree.act_segment(action, range);
+                RoundResult::Empty
+            }
+            Query { range } => RoundResult::Summary(tree.segment_summary(range)),
+            Insert { index, value } => {
+                tree.insert(index..index, value).unwrap();
+                len += 1;
+                RoundResult::Empty
+            }
+            Delete { index } if len > 0 => {
+                len -= 1;
+                RoundResult::Value(tree.delete(index..=index).unwrap())
+            }
+            Delete { .. } => {
+                assert!(tree.is_empty());
+                RoundResult::Empty
+            }
+        };
+        assert_eq!(res, expected);
+        assert_eq!(tree.subtree_summary(), reference.subtree_summary());
+        snapshots.push((tree.clone(), reference.iter().cloned().collect::<Vec<_>>()));
+    }
+
+    for (snapshot, values) in snapshots {
+        snapshot.assert_correctness();
+        assert_eq!(snapshot.iter().collect::<Vec<_>>(), values);
+        let range = random_range(&mut rng, values.len());
+        let expected = values[range.clone()]
+            .iter()
+            .fold(Default::default(), |acc, value| acc + value.to_summary());
+        assert_eq!(snapshot.segment_summary(range), expected);
+    }
+}
+
+/// Runs random rounds on an [`ArenaTreap`] and on a regular tree, and compares their results,
+/// and the results of the walker-based algorithms.
+/// Also checks that freed nodes are reused, and that clearing the tree keeps its memory.
+///
+/// [`ArenaTreap`]: grove::arena::treap::ArenaTreap
+pub fn check_arena_treap(num_rounds: u32) {
+    use grove::{arena::treap::ArenaTreap, splay::SplayTree};
+    use RoundAction::*;
+
+    let mut rng = test_rng();
+    let mut len: usize = INITIAL_SIZE;
+
+    let range = 0..(len as _);
+    let mut reference: SplayTree<StdNum> = range.clone().collect();
+    let mut tree: ArenaTreap<StdNum> = range.collect();
+    let mut max_len = len;
+
+    for _ in 0..num_rounds {
+        let round_action = random_round_action::<StdNum, _>(&mut rng, len, true);
+        let expected = run_round(round_action.clone(), &mut reference, len, true);
+        let res = match round_action {
+            Act { range, action } => {
+                tree.act_segment(action, range);
+                RoundResult::Empty
+            }
+            Query { range } => RoundResult::Summary(tree.segment_summary(range)),
+            Insert { index, value } => {
+                tree.insert(index..index, value).unwrap();
+                len += 1;
+                RoundResult::Empty
+            }
+            Delete { index } if len > 0 => {
+                len -= 1;
+                RoundResult::Value(tree.delete(index..=index).unwrap())
+            }
+            Delete { .. } => {
+                assert!(tree.is_empty());
+                RoundResult::Empty
+            }
+        };
+        assert_eq!(res, expected);
+        assert_eq!(tree.len(), len);
+        assert_eq!(tree.subtree_summary(), reference.subtree_summary());
+        max_len = max_len.max(len);
+        // freed slots are reused, so the arena never holds more slots than the maximum size
+        assert!(tree.arena().0.capacity() <= 2 * max_len);
+        tree.assert_correctness();
+
+        let index = rng.gen_range(0..=len);
+        let bound = rng.gen_range(0..INITIAL_SIZE as i32);
+        let below = |summary: &NumSummary| summary.max < Some(bound);
+        assert_eq!(
+            tree.max_right(index, below),
+            reference.max_right(index, below)
+        );
+        assert_eq!(
+            tree.min_left(index, below),
+            reference.min_left(index, below)
+        );
+        tree.assert_correctness();
+    }
+    tree.for_each_mut(len / 3..len / 2, |value| *value += 1);
+    reference.for_each_mut(len / 3..len / 2, |value| *value += 1);
+    tree.assert_correctness();
+    assert!(tree.iter().eq(reference.iter()));
+
+    let capacity = tree.arena().0.capacity();
+    tree.clear();
+    assert!(tree.is_empty());
+    assert_eq!(tree.arena().0.capacity(), capacity);
+    tree.insert(0..0, 5).unwrap();
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), vec![5]);
+}
+
+/// Runs random rounds on an [`ArenaTreap`], and checks after every round that the handles
+/// returned by [`ArenaTreap::insert`] still refer to their values, against a regular tree.
+///
+/// [`ArenaTreap`]: grove::arena::treap::ArenaTreap
+/// [`ArenaTreap::insert`]: grove::arena::treap::ArenaTreap::insert
+pub fn check_arena_handles(num_rounds: u32) {
+    use grove::{arena::treap::ArenaTreap, splay::SplayTree};
+    use RoundAction::*;
+
+    let mut rng = test_rng();
+    let mut tree: ArenaTreap<StdNum> = ArenaTreap::new();
+    let mut reference: SplayTree<StdNum> = (0..(INITIAL_SIZE as _)).collect();
+    // the handles, in the order of their values in the tree
+    let mut handles = vec![];
+    for value in 0..(INITIAL_SIZE as _) {
+        handles.push(tree.insert(handles.len()..handles.len(), value).unwrap());
+    }
+
+    for _ in 0..num_rounds {
+        let len = handles.len();
+        let round_action = random_round_action::<StdNum, _>(&mut rng, len, true);
+        let expected = run_round(round_action.clone(), &mut reference, len, true);
+        let res = match round_action {
+            Act { range, action } => {
+                if action.to_reverse {
+                    handles[range.clone()].reverse();
+                }
+                tree.act_segment(action, range);
+                RoundResult::Empty
+            }
+            Query { range } => RoundResult::Summary(tree.segment_summary(range)),
+            Insert { index, value } => {
+                handles.insert(index, tree.insert(index..index, value).unwrap());
+                RoundResult::Empty
+            }
+            Delete { index } if len > 0 => RoundResult::Value(tree.remove(handles.remove(index))),
+            Delete { .. } => RoundResult::Empty,
+        };
+        assert_eq!(res, expected);
+        tree.assert_correctness();
+
+        if handles.is_empty() {
+            continue;
+        }
+        // the handles should still refer to the same values, at their current positions
+        let index = rng.gen_range(0..handles.len());
+        let handle = handles[index];
+        let left_summary = reference.segment_summary(..index);
+        let value = *reference.slice(index..=index).iter().next().unwrap();
+        assert_eq!(tree.index_of(handle), index);
+        assert_eq!(tree.summary_left_of(handle), left_summary);
+        assert_eq!(*tree.get(handle), value);
+        let walker = tree.walker_at(handle);
+        assert_eq!(walker.handle(), Some(handle));
+        assert_eq!(walker.value(), Some(&value));
+        assert_eq!(walker.left_summary(), left_summary);
+        drop(walker);
+        tree.assert_correctness();
+    }
+    assert!(tree.iter().eq(reference.iter()));
+}
+
+/// A naive rooted forest, to check [`grove::link_cut::LinkCutForest`]
+/// and [`grove::euler_tour::EulerTourForest`] against.
+struct NaiveForest {
+    values: Vec<i32>,
+    parent: Vec<Option<usize>>,
+}
+
+impl NaiveForest {
+    fn root(&self, mut v: usize) -> usize {
+        while let Some(p) = self.parent[v] {
+            v = p;
+        }
+        v
+    }
+
+    /// The vertices from `v` up to its root.
+    fn ancestors(&self, mut v: usize) -> Vec<usize> {
+        let mut res = vec![v];
+        while let Some(p) = self.parent[v] {
+            res.push(p);
+            v = p;
+        }
+        res
+    }
+
+    fn make_root(&mut self, v: usize) {
+        let (mut prev, mut current) = (None, Some(v));
+        while let Some(c) = current {
+            current = std::mem::replace(&mut self.parent[c], prev);
+            prev = Some(c);
+        }
+    }
+
+    fn lca(&self, u: usize, v: usize) -> Option<usize> {
+        let ancestors = self.ancestors(u);
+        self.ancestors(v)
+            .into_iter()
+            .find(|w| ancestors.contains(w))
+    }
+
+    /// The vertices in the subtree of `v`.
+    fn subtree(&self, v: usize) -> Vec<usize> {
+        (0..self.parent.len())
+            .filter(|&w| self.ancestors(w).contains(&v))
+            .collect()
+    }
+
+    /// The values of the vertices, in the same order.
+    fn values_of(&self, vertices: Vec<usize>) -> Vec<i32> {
+        vertices.into_iter().map(|w| self.values[w]).collect()
+    }
+
+    fn summary(&self, vertices: Vec<usize>) -> <StdNum as Data>::Summary {
+        vertices
+            .into_iter()
+            .map(|w| self.values[w].to_summary())
+            .fold(Default::default(), |acc, summary| acc + summary)
+    }
+
+    /// Reroots at `u`, and returns the path from `u` to `v`.
+    fn path(&mut self, u: usize, v: usize) -> Option<Vec<usize>> {
+        if self.root(u) != self.root(v) {
+            return None;
+        }
+        self.make_root(u);
+        let mut path = self.ancestors(v);
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Runs random links, cuts, path queries, path actions, lowest common ancestor queries and
+/// rerootings on a [`LinkCutForest`] and on a [`NaiveForest`], and compares their results.
+///
+/// [`LinkCutForest`]: grove::link_cut::LinkCutForest
+pub fn check_link_cut_forest(num_rounds: u32) {
+    use grove::link_cut::LinkCutForest;
+
+    const NUM_VERTICES: usize = 40;
+    let mut rng = test_rng();
+    let mut forest: LinkCutForest<StdNum> = LinkCutForest::new();
+    let mut naive = NaiveForest {
+        values: (0..NUM_VERTICES as i32).collect(),
+        parent: vec![None; NUM_VERTICES],
+    };
+    let vertices: Vec<_> = naive
+        .values
+        .iter()
+        .map(|&value| forest.add_vertex(value))
+        .collect();
+    let index_of = |vertex| vertices.iter().position(|&w| w == vertex).unwrap();
+
+    for _ in 0..num_rounds {
+        let u = rng.gen_range(0..NUM_VERTICES);
+        let v = rng.gen_range(0..NUM_VERTICES);
+        let connected = naive.root(u) == naive.root(v);
+        match rng.gen_range(0..8) {
+            0 => {
+                let can_link = naive.parent[u].is_none() && !connected;
+                assert_eq!(forest.link(vertices[u], vertices[v]).is_some(), can_link);
+                if can_link {
+                    naive.parent[u] = Some(v);
+                }
+            }
+            1 => {
+                assert_eq!(
+                    forest.link_any(vertices[u], vertices[v]).is_some(),
+                    !connected
+                );
+                if !connected {
+                    naive.make_root(u);
+                    naive.parent[u] = Some(v);
+                }
+            }
+            2 => {
+                // cut some edge
+                if let Some(p) = naive.parent[u] {
+                    if rng.gen() {
+                        forest.cut(vertices[u], vertices[p]).unwrap();
+                    } else {
+                        forest.cut_any(vertices[p], vertices[u]).unwrap();
+                    }
+                    naive.parent[u] = None;
+                } else {
+                    assert_eq!(forest.cut(vertices[u], vertices[v]), None);
+                }
+            }
+            3 => {
+                let expected = naive.path(u, v).map(|path| naive.summary(path));
+                assert_eq!(forest.path_summary(vertices[u], vertices[v]), expected);
+            }
+            4 => {
+                let action = random_action(&mut rng, false);
+                let res = forest.path_act(vertices[u], vertices[v], action);
+                match naive.path(u, v) {
+                    Some(path) => {
+                        res.unwrap();
+                        for w in path {
+                            action.act_inplace(&mut naive.values[w]);
+                        }
+                    }
+                    None => assert_eq!(res, None),
+                }
+            }
+            5 => {
+                let expected = naive.lca(u, v).map(|w| vertices[w]);
+                assert_eq!(forest.lca(vertices[u], vertices[v]), expected);
+            }
+            6 => {
+                forest.make_root(vertices[u]);
+                naive.make_root(u);
+            }
+            _ => {
+                assert_eq!(index_of(forest.find_root(vertices[u])), naive.root(u));
+                assert_eq!(forest.parent(vertices[u]).map(index_of), naive.parent[u]);
+                assert_eq!(*forest.get(vertices[u]), naive.values[u]);
+            }
+        }
+        forest.assert_correctness();
+    }
+}
+
+/// Runs random links, cuts, subtree queries, subtree and component actions and rerootings
+/// on an [`EulerTourForest`] and on a [`NaiveForest`], and compares their results.
+///
+/// [`EulerTourForest`]: grove::euler_tour::EulerTourForest
+pub fn check_euler_tour_forest(num_rounds: u32) {
+    use grove::euler_tour::EulerTourForest;
+
+    const NUM_VERTICES: usize = 40;
+    let mut rng = test_rng();
+    let mut forest: EulerTourForest<StdNum, _> = EulerTourForest::with_seed(rng.gen());
+    let mut naive = NaiveForest {
+        values: (0..NUM_VERTICES as i32).collect(),
+        parent: vec![None; NUM_VERTICES],
+    };
+    let vertices: Vec<_> = naive
+        .values
+        .iter()
+        .map(|&value| forest.add_vertex(value))
+        .collect();
+    let index_of = |vertex| vertices.iter().position(|&w| w == vertex).unwrap();
+
+    for _ in 0..num_rounds {
+        let u = rng.gen_range(0..NUM_VERTICES);
+        let v = rng.gen_range(0..NUM_VERTICES);
+        let connected = naive.root(u) == naive.root(v);
+        assert_eq!(forest.connected(vertices[u], vertices[v]), connected);
+        match rng.gen_range(0..7) {
+            0 | 1 => {
+                assert_eq!(forest.link(vertices[u], vertices[v]).is_some(), !connected);
+                if !connected {
+                    naive.make_root(u);
+                    naive.parent[u] = Some(v);
+                }
+            }
+            2 => {
+                let is_edge = naive.parent[u] == Some(v) || naive.parent[v] == Some(u);
+                assert_eq!(forest.has_edge(vertices[u], vertices[v]), is_edge);
+                assert_eq!(forest.cut(vertices[u], vertices[v]).is_some(), is_edge);
+                if naive.parent[u] == Some(v) {
+                    naive.parent[u] = None;
+                } else if naive.parent[v] == Some(u) {
+                    naive.parent[v] = None;
+                }
+            }
+            3 => {
+                let expected = if naive.parent[u] == Some(v) {
+                    Some(naive.summary(naive.subtree(u)))
+                } else {
+                    None
+                };
+                assert_eq!(forest.subtree_summary(vertices[u], vertices[v]), expected);
+            }
+            4 => {
+                let action = random_action(&mut rng, true);
+                let res = forest.subtree_act(vertices[u], vertices[v], action);
+                if naive.parent[u] == Some(v) {
+                    res.unwrap();
+                    for w in naive.subtree(u) {
+                        action.act_inplace(&mut naive.values[w]);
+                    }
+                } else {
+                    assert_eq!(res, None);
+                }
+            }
+            5 => {
+                let action = random_action(&mut rng, true);
+                forest.component_act(vertices[u], action);
+                let root = naive.root(u);
+                for w in naive.subtree(root) {
+                    action.act_inplace(&mut naive.values[w]);
+                }
+            }
+            _ => {
+                forest.reroot(vertices[u]);
+                naive.make_root(u);
+            }
+        }
+        let root = naive.root(u);
+        assert_eq!(index_of(forest.root(vertices[u])), root);
+        assert_eq!(
+            forest.component_summary(vertices[u]),
+            naive.summary(naive.subtree(root))
+        );
+        assert_eq!(*forest.get(vertices[u]), naive.values[u]);
+        forest.assert_correctness();
+    }
+    assert_eq!(forest.len(), NUM_VERTICES);
+}
+
+/// Runs random operations on a [`Seq`] and on a [`Vec`], and compares their results.
+///
+/// [`Seq`]: grove::collections::Seq
+pub fn check_seq<T>(num_rounds: u32)
+where
+    T: ConcatenableTree<example_data::SizeData<i32>> + AsBasicTree<example_data::SizeData<i32>>,
+    for<'a> &'a mut T: SplittableTreeRef<example_data::SizeData<i32>, T = T>
+        + ModifiableTreeRef<example_data::SizeData<i32>>,
+{
+    use grove::collections::Seq;
+
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE as i32).collect();
+    let mut seq: Seq<i32, T> = reference.iter().cloned().collect();
+
+    for _ in 0..num_rounds {
+        let len = reference.len();
+        match rng.gen_range(0..8) {
+            0 => {
+                let index = rng.gen_range(0..=len);
+                let value = rng.gen_range(-200..200);
+                seq.insert(index, value);
+                reference.insert(index, value);
+            }
+            1 if len > 0 => {
+                let index = rng.gen_range(0..len);
+                assert_eq!(seq.remove(index), reference.remove(index));
+            }
+            2 => {
+                let value = rng.gen_range(-200..200);
+                if rng.gen() {
+                    seq.push_front(value);
+                    reference.insert(0, value);
+                } else {
+                    seq.push_back(value);
+                    reference.push(value);
+                }
+            }
+            3 => {
+                if rng.gen() {
+                    let expected = if len > 0 {
+                        Some(reference.remove(0))
+                    } else {
+                        None
+                    };
+                    assert_eq!(seq.pop_front(), expected);
+                } else {
+                    assert_eq!(seq.pop_back(), reference.pop());
+                }
+            }
+            4 => {
+                let index = rng.gen_range(0..len + 10);
+                assert_eq!(seq.get(index), reference.get(index));
+                if index < len {
+                    assert_eq!(seq[index], reference[index]);
+                }
+            }
+            5 => {
+                let index = rng.gen_range(0..len + 10);
+                let add = rng.gen_range(-200..200);
+                match seq.get_mut(index) {
+                    Some(mut value) => *value += add,
+                    None => assert!(index >= len),
+                }
+                if let Some(value) = reference.get_mut(index) {
+                    *value += add;
+                }
+            }
+            6 => {
+                let at = rng.gen_range(0..=len);
+                let mut tail = seq.split_off(at);
+                let reference_tail = reference.split_off(at);
+                assert_eq!(tail.len(), reference_tail.len());
+                // put the tail back in front, to move the values around
+                tail.append(&mut seq);
+                assert!(seq.is_empty());
+                seq = tail;
+                reference = reference_tail.into_iter().chain(reference).collect();
+            }
+            _ => {
+                let count = rng.gen_range(0..10);
+                let values: Vec<i32> = (0..count).map(|_| rng.gen_range(-200..200)).collect();
+                seq.extend(values.iter().cloned());
+                reference.extend(values);
+            }
+        }
+        assert_eq!(seq.len(), reference.len());
+        seq.tree().assert_correctness();
+    }
+    assert!(seq.iter().eq(reference.iter()));
+    assert_eq!(seq.into_iter().collect::<Vec<_>>(), reference);
+}
+
+/// Runs random operations on an [`OrderedMap`] and on a [`BTreeMap`], and compares their results.
+///
+/// [`OrderedMap`]: grove::collections::OrderedMap
+/// [`BTreeMap`]: std::collections::BTreeMap
+pub fn check_ordered_map<T>(num_rounds: u32)
+where
+    T: SomeTree<example_data::SizeData<(i32, i32)>>,
+    for<'a> &'a mut T: ModifiableTreeRef<example_data::SizeData<(i32, i32)>>,
+{
+    use grove::collections::ordered::{Entry, OrderedMap};
+    use std::collections::BTreeMap;
+
+    const MAX_KEY: i32 = 300;
+    let mut rng = test_rng();
+    let mut reference: BTreeMap<i32, i32> = (0..INITIAL_SIZE as i32)
+        .map(|_| (rng.gen_range(0..MAX_KEY), rng.gen_range(-200..200)))
+        .collect();
+    let mut map: OrderedMap<i32, i32, _, T> = reference.clone().into_iter().collect();
+
+    for _ in 0..num_rounds {
+        let key = rng.gen_range(0..MAX_KEY);
+        let value = rng.gen_range(-200..200);
+        match rng.gen_range(0..8) {
+            0 => assert_eq!(map.insert(key, value), reference.insert(key, value)),
+            1 => assert_eq!(map.remove(&key), reference.remove(&key)),
+            2 => {
+                assert_eq!(map.get(&key), reference.get(&key));
+                assert_eq!(map.rank(&key), reference.range(..key).count());
+            }
+            3 => match map.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    if rng.gen() {
+                        assert_eq!(entry.insert(value), reference.insert(key, value).unwrap());
+                    } else {
+                        assert_eq!(entry.remove(), reference.remove(&key).unwrap());
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    assert!(!reference.contains_key(&key));
+                    let entry = entry.insert(value);
+                    assert_eq!((entry.key(), entry.get()), (&key, &value));
+                    reference.insert(key, value);
+                }
+            },
+            4 => {
+                map.entry(key)
+                    .and_modify(|old| *old += value)
+                    .or_insert(value);
+                *reference.entry(key).or_insert(0) += value;
+            }
+            5 => {
+                let index = rng.gen_range(0..reference.len() + 5);
+                assert_eq!(map.nth(index), reference.iter().nth(index));
+            }
+            6 => {
+                let end = rng.gen_range(key..=MAX_KEY);
+                let expected: Vec<_> = reference.range(key..end).collect();
+                assert_eq!(map.range(key..end).collect::<Vec<_>>(), expected);
+                assert_eq!(map.range_summary(key..end).size, expected.len());
+                let count = reference.range(..=key).count();
+                assert_eq!(map.range(..=key).count(), count);
+            }
+            _ => {
+                if rng.gen() {
+                    assert_eq!(map.pop_first(), reference.pop_first());
+                } else {
+                    assert_eq!(map.pop_last(), reference.pop_last());
+                }
+                assert_eq!(map.first_key_value(), reference.first_key_value());
+                assert_eq!(map.last_key_value(), reference.last_key_value());
+            }
+        }
+        assert_eq!(map.len(), reference.len());
+        map.tree().assert_correctness();
+    }
+    assert!(map.iter().eq(reference.iter()));
+    assert_eq!(
+        map.into_iter().collect::<Vec<_>>(),
+        reference.into_iter().collect::<Vec<_>>()
+    );
+}
+
+/// Runs random operations on an [`OrderedSet`] and on a [`BTreeSet`], and compares their results.
+///
+/// [`OrderedSet`]: grove::collections::OrderedSet
+/// [`BTreeSet`]: std::collections::BTreeSet
+pub fn check_ordered_set<T>(num_rounds: u32)
+where
+    T: SomeTree<example_data::SizeData<i32>>,
+    for<'a> &'a mut T: ModifiableTreeRef<example_data::SizeData<i32>>,
+{
+    use grove::collections::OrderedSet;
+    use std::collections::BTreeSet;
+
+    const MAX_KEY: i32 = 300;
+    let mut rng = test_rng();
+    let mut reference: BTreeSet<i32> = (0..INITIAL_SIZE)
+        .map(|_| rng.gen_range(0..MAX_KEY))
+        .collect();
+    let mut set: OrderedSet<i32, _, T> = reference.iter().cloned().collect();
+
+    for _ in 0..num_rounds {
+        let key = rng.gen_range(0..MAX_KEY);
+        match rng.gen_range(0..5) {
+            0 => assert_eq!(set.insert(key), reference.insert(key)),
+            1 => assert_eq!(set.remove(&key), reference.remove(&key)),
+            2 => {
+                assert_eq!(set.contains(&key), reference.contains(&key));
+                assert_eq!(set.rank(&key), reference.range(..key).count());
+            }
+            3 => {
+                let end = rng.gen_range(key..=MAX_KEY);
+                let expected: Vec<_> = reference.range(key..=end).collect();
+                assert_eq!(set.range(key..=end).collect::<Vec<_>>(), expected);
+                assert_eq!(set.range_summary(key..=end).size, expected.len());
+            }
+            _ => {
+                assert_eq!(set.first(), reference.iter().next());
+                assert_eq!(set.last(), reference.iter().next_back());
+                if rng.gen() {
+                    assert_eq!(set.pop_first(), reference.pop_first());
+                } else {
+                    assert_eq!(set.pop_last(), reference.pop_last());
+                }
+            }
+        }
+        assert_eq!(set.len(), reference.len());
+        set.tree().assert_correctness();
+    }
+    assert_eq!(
+        set.into_iter().collect::<Vec<_>>(),
+        reference.into_iter().collect::<Vec<_>>()
+    );
+}
+
+/// A value that is ordered only by its key, used for checking duplicate policies.
+#[derive(Clone, Copy, Debug)]
+pub struct Keyed(pub i32, pub i32);
+
+impl PartialEq for Keyed {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Keyed {}
+
+impl PartialOrd for Keyed {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Keyed {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+/// Checks the set operations that take a [`DuplicatePolicy`] and the symmetric difference
+/// and subset operations of a tree type, against the same operations on sorted vectors.
+pub fn check_set_policies<T>(
+    union_with: impl Fn(T, T, fn(Keyed, Keyed) -> Keyed) -> T,
+    intersection_with: impl Fn(T, T, KeepRight) -> T,
+    symmetric_difference: impl Fn(T, T) -> T,
+    is_subset: impl Fn(&mut T, &mut T) -> bool,
+) where
+    T: SomeTree<PlainData<Keyed>>,
+    for<'a> &'a mut T: SomeTreeRef<PlainData<Keyed>>,
+{
+    let mut rng = test_rng();
+    for _ in 0..NUM_SET_ROUNDS {
+        let len1 = rng.gen_range(0..300);
+        let len2 = rng.gen_range(0..300);
+        let random_vec = |rng: &mut StdRng, len| {
+            let mut vec: Vec<Keyed> = (0..len)
+                .map(|_| Keyed(rng.gen_range(0..500), rng.gen_range(0..100)))
+                .collect();
+            vec.sort();
+            vec.dedup();
+            vec
+        };
+        let vec1 = random_vec(&mut rng, len1);
+        let vec2 = random_vec(&mut rng, len2);
+        let find = |vec: &Vec<Keyed>, x: &Keyed| vec.binary_search(x).ok().map(|i| vec[i]);
+        let tree = |vec: &Vec<Keyed>| vec.iter().cloned().collect::<T>();
+        let pairs = |tree: T| {
+            tree.into_iter()
+                .map(|Keyed(key, value)| (key, value))
+                .collect::<Vec<_>>()
+        };
+
+        let mut expected: Vec<Keyed> = vec1
+            .iter()
+            .map(|x| match find(&vec2, x) {
+                Some(y) => Keyed(x.0, x.1 - y.1),
+                None => *x,
+            })
+            .chain(vec2.iter().filter(|y| find(&vec1, y).is_none()).cloned())
+            .collect();
+        expected.sort();
+        let res = union_with(tree(&vec1), tree(&vec2), |x, y| Keyed(x.0, x.1 - y.1));
+        res.assert_correctness();
+        assert_eq!(pairs(res), pairs(expected.into_iter().collect()));
+
+        let expected: Vec<Keyed> = vec2
+            .iter()
+            .filter(|y| find(&vec1, y).is_some())
+            .cloned()
+            .collect();
+        let res = intersection_with(tree(&vec1), tree(&vec2), KeepRight);
+        res.assert_correctness();
+        assert_eq!(pairs(res), pairs(expected.into_iter().collect()));
+
+        let mut expected: Vec<Keyed> = vec1
+            .iter()
+            .filter(|x| find(&vec2, x).is_none())
+            .chain(vec2.iter().filter(|y| find(&vec1, y).is_none()))
+            .cloned()
+            .collect();
+        expected.sort();
+        let res = symmetric_difference(tree(&vec1), tree(&vec2));
+        res.assert_correctness();
+        assert_eq!(pairs(res), pairs(expected.into_iter().collect()));
+
+        // make a subset half of the time
+        let vec3: Vec<Keyed> = if rng.gen() {
+            vec1.iter().filter(|_| rng.gen_bool(0.9)).cloned().collect()
+        } else {
+            let len = rng.gen_range(0..20);
+            random_vec(&mut rng, len)
+        };
+        let expected = vec3.iter().all(|x| find(&vec1, x).is_some());
+        let (mut tree1, mut tree3) = (tree(&vec1), tree(&vec3));
+        assert_eq!(is_subset(&mut tree3, &mut tree1), expected);
+        tree1.assert_correctness();
+        tree3.assert_correctness();
+        assert_eq!(pairs(tree1), pairs(tree(&vec1)));
+        assert_eq!(pairs(tree3), pairs(tree(&vec3)));
+    }
+}
+
+/// Returns a random string, with multi-byte chars and line breaks.
+fn random_text(rng: &mut StdRng, max_len: usize) -> String {
+    const CHARS: [char; 6] = ['a', 'b', '\n', 'é', '中', '😀'];
+    let len = rng.gen_range(0..=max_len);
+    (0..len)
+        .map(|_| CHARS[rng.gen_range(0..CHARS.len())])
+        .collect()
+}
+
+/// Runs random operations on a [`Rope`] and on a vector of its chars, and compares their results.
+///
+/// [`Rope`]: grove::collections::Rope
+pub fn check_rope<T>(num_rounds: u32)
+where
+    T: ConcatenableTree<grove::collections::rope::RopeData>
+        + AsBasicTree<grove::collections::rope::RopeData>,
+    for<'a> &'a mut T: SplittableTreeRef<grove::collections::rope::RopeData, T = T>,
+{
+    use grove::collections::Rope;
+
+    let mut rng = test_rng();
+    let mut reference: Vec<char> = random_text(&mut rng, 2000).chars().collect();
+    let mut rope: Rope<T> = Rope::from(reference.iter().collect::<String>().as_str());
+
+    for _ in 0..num_rounds {
+        let len = reference.len();
+        match rng.gen_range(0..6) {
+            0 => {
+                let index = rng.gen_range(0..=len);
+                let max_len = if rng.gen_range(0..10) == 0 { 1000 } else { 10 };
+                let text = random_text(&mut rng, max_len);
+                rope.insert_str(index, &text);
+                reference.splice(index..index, text.chars());
+            }
+            1 => {
+                let start = rng.gen_range(0..=len);
+                let end = rng.gen_range(start..=len.min(start + 50));
+                rope.remove(start..end);
+                reference.drain(start..end);
+            }
+            2 => {
+                let start = rng.gen_range(0..=len);
+                let end = rng.gen_range(start..=len);
+                let slice = rope.slice(start..end);
+                assert_eq!(slice.len_chars(), end - start);
+                assert_eq!(
+                    slice.to_string(),
+                    reference[start..end].iter().collect::<String>()
+                );
+            }
+            3 => {
+                let at = rng.gen_range(0..=len);
+                let mut tail = rope.split_off(at);
+                assert_eq!(tail.len_chars(), len - at);
+                // put the tail back in front, to move the text around
+                tail.append(rope);
+                rope = tail;
+                reference.rotate_left(at);
+            }
+            4 => {
+                let index = rng.gen_range(0..=len);
+                let prefix: String = reference[..index].iter().collect();
+                let byte_index = rope.char_to_byte(index);
+                assert_eq!(byte_index, prefix.len());
+                assert_eq!(rope.byte_to_char(byte_index), index);
+                let utf16_index = rope.char_to_utf16(index);
+                assert_eq!(utf16_index, prefix.encode_utf16().count());
+                assert_eq!(rope.utf16_to_char(utf16_index), index);
+                let line = rope.char_to_line(index);
+                assert_eq!(line, prefix.matches('\n').count());
+                let line_start = rope.line_to_char(line);
+                assert!(line_start <= index);
+                assert!(!reference[line_start..index].contains(&'\n'));
+                assert!(line_start == 0 || reference[line_start - 1] == '\n');
+            }
+            _ => {
+                let text: String = reference.iter().collect();
+                assert_eq!(rope.to_string(), text);
+                assert_eq!(rope.len_lines(), text.matches('\n').count() + 1);
+                assert_eq!(rope.line_to_char(rope.len_lines()), len);
+                assert_eq!(rope.len_bytes(), text.len());
+                assert!(rope
+                    .chunks()
+                    .all(|chunk| !chunk.is_empty() && chunk.len() <= 512));
+                rope.tree().assert_correctness();
+            }
+        }
+        assert_eq!(rope.len_chars(), reference.len());
+    }
+}
+
+/// A run of equal values, used for checking trees whose values represent several elements.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Run {
+    pub value: i32,
+    pub len: usize,
+}
+
+/// The number of elements in a segment of runs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct RunSize {
+    pub size: usize,
+}
+
+impl std::ops::Add for RunSize {
+    type Output = RunSize;
+    fn add(self, other: RunSize) -> RunSize {
+        RunSize {
+            size: self.size + other.size,
+        }
+    }
+}
+
+impl SizedSummary for RunSize {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl ToSummary<RunSize> for Run {
+    fn to_summary(&self) -> RunSize {
+        RunSize { size: self.len }
+    }
+}
+
+impl Acts<Run> for example_data::RevAction {
+    fn act_inplace(&self, _run: &mut Run) {}
+}
+
+impl Acts<RunSize> for example_data::RevAction {
+    fn act_inplace(&self, _size: &mut RunSize) {}
+}
+
+impl SplittableValue for Run {
+    fn split_at(&self, offset: usize) -> (Run, Run) {
+        assert!(0 < offset && offset < self.len);
+        (
+            Run {
+                len: offset,
+                ..*self
+            },
+            Run {
+                len: self.len - offset,
+                ..*self
+            },
+        )
+    }
+
+    fn try_merge(&self, other: &Run) -> Option<Run> {
+        if self.value == other.value {
+            Some(Run {
+                len: self.len + other.len,
+                ..*self
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// Trees of [`Run`]s, whose summaries are the number of elements in the runs.
+pub type RunData = (Run, RunSize, example_data::RevAction);
+
+/// Checks [`SomeTree::slice_exact`] and [`SomeTree::merge_at`] on a tree of runs,
+/// against a vector of the elements of the runs.
+pub fn check_exact_slices<T>(num_rounds: u32)
+where
+    T: ConcatenableTree<RunData> + AsBasicTree<RunData>,
+    for<'a> &'a mut T: SplittableTreeRef<RunData, T = T> + ModifiableTreeRef<RunData>,
+{
+    fn elements<T>(tree: &mut T) -> Vec<i32>
+    where
+        T: SomeTree<RunData>,
+        for<'a> &'a mut T: SomeTreeRef<RunData>,
+    {
+        tree.iter()
+            .flat_map(|run| (0..run.len).map(move |_| run.value))
+            .collect()
+    }
+
+    fn shape<T: AsBasicTree<RunData>>(tree: &T) -> String {
+        tree.as_basic_tree()
+            .representation(&|_| String::new(), false)
+    }
+
+    let mut rng = test_rng();
+    let runs: Vec<Run> = (0..INITIAL_SIZE)
+        .map(|_| Run {
+            value: rng.gen_range(0..5),
+            len: rng.gen_range(1..10),
+        })
+        .collect();
+    let mut tree: T = runs.iter().cloned().collect();
+    let mut reference = elements(&mut tree);
+
+    for _ in 0..num_rounds {
+        let len = reference.len();
+        let start = rng.gen_range(0..=len);
+        let end = rng.gen_range(start..=len);
+        match rng.gen_range(0..4) {
+            0 => {
+                let mut slice = tree.slice_exact(start..end);
+                assert_eq!(slice.summary().size, end - start);
+                let values: Vec<i32> = slice
+                    .iter()
+                    .flat_map(|run| (0..run.len).map(move |_| run.value))
+                    .collect();
+                assert_eq!(values, reference[start..end]);
+            }
+            1 => {
+                tree.slice_exact(start..end)
+                    .act(example_data::RevAction { to_reverse: true });
+                reference[start..end].reverse();
+            }
+            2 => {
+                let right = tree.slice_exact(start..start).split_right().unwrap();
+                assert_eq!(right.subtree_summary().size, len - start);
+                tree.concatenate_left(right);
+                reference.rotate_left(start);
+            }
+            _ => {
+                let num_values = tree.iter().count();
+                if start > 0 {
+                    // reading the values at the boundary first, since splay trees splay on reads
+                    tree.iter_indexed(start - 1..=start).for_each(drop);
+                }
+                let old_shape = shape(&tree);
+                let merged = tree.merge_at(start);
+                if !merged {
+                    // a failed merge shouldn't change the tree
+                    assert_eq!(shape(&tree), old_shape);
+                }
+                assert_eq!(tree.iter().count(), num_values - merged as usize);
+            }
+        }
+        tree.assert_correctness();
+        assert_eq!(elements(&mut tree), reference);
+    }
+}
+
+/// Checks an interval map against a vector of intervals, sorted by their start points.
+pub fn check_interval_map<T>(num_rounds: u32)
+where
+    T: SomeTree<grove::collections::interval::IntervalData<i32, u32>>
+        + AsBasicTree<grove::collections::interval::IntervalData<i32, u32>>,
+    for<'a> &'a mut T: ModifiableTreeRef<grove::collections::interval::IntervalData<i32, u32>>,
+{
+    use grove::collections::IntervalMap;
+
+    fn random_interval(rng: &mut StdRng) -> Range<i32> {
+        let start = rng.gen_range(0..1000);
+        start..start + rng.gen_range(1..100)
+    }
+
+    let mut rng = test_rng();
+    let mut reference: Vec<(Range<i32>, u32)> = (0..INITIAL_SIZE as u32)
+        .map(|value| (random_interval(&mut rng), value))
+        .collect();
+    let mut map: IntervalMap<i32, u32, T> = reference.iter().cloned().collect();
+    reference.sort_by_key(|(range, _)| range.start);
+
+    for round in 0..num_rounds {
+        match rng.gen_range(0..5) {
+            0 => {
+                let range = random_interval(&mut rng);
+                map.insert(range.clone(), round);
+                let index = reference.partition_point(|(other, _)| other.start <= range.start);
+                reference.insert(index, (range, round));
+            }
+            1 => {
+                let range = if !reference.is_empty() && rng.gen() {
+                    reference[rng.gen_range(0..reference.len())].0.clone()
+                } else {
+                    random_interval(&mut rng)
+                };
+                let expected = reference
+                    .iter()
+                    .position(|(other, _)| *other == range)
+                    .map(|index| reference.remove(index).1);
+                assert_eq!(map.remove(&range), expected);
+            }
+            2 => {
+                let query = random_interval(&mut rng);
+                let expected: Vec<_> = reference
+                    .iter()
+                    .filter(|(range, _)| range.start < query.end && query.start < range.end)
+                    .map(|(range, value)| (range.clone(), *value))
+                    .collect();
+                let found: Vec<_> = map
+                    .overlapping(query.clone())
+                    .map(|(range, value)| (range.clone(), *value))
+                    .collect();
+                assert_eq!(found, expected);
+                assert_eq!(map.any_overlap(query), !expected.is_empty());
+            }
+            3 => {
+                let point = rng.gen_range(-10..1110);
+                let expected: Vec<_> = reference
+                    .iter()
+                    .filter(|(range, _)| range.contains(&point))
+                    .map(|(range, value)| (range.clone(), *value))
+                    .collect();
+                let found: Vec<_> = map
+                    .stabbing(point)
+                    .map(|(range, value)| (range.clone(), *value))
+                    .collect();
+                assert_eq!(found, expected);
+            }
+            _ => {
+                let values: Vec<_> = map
+                    .iter()
+                    .map(|(range, value)| (range.clone(), *value))
+                    .collect();
+                assert_eq!(values, reference);
+            }
+        }
+        assert_eq!(map.len(), reference.len());
+        map.tree().assert_correctness();
+    }
+}
+
+/// Checks a range map against a vector with the value of every key.
+/// Also checks that touching ranges with equal values are always merged.
+pub fn check_range_map<T>(num_rounds: u32)
+where
+    T: ConcatenableTree<grove::collections::range_map::RangeData<i32, u8>>,
+    for<'a> &'a mut T: SplittableTreeRef<grove::collections::range_map::RangeData<i32, u8>, T = T>,
+{
+    use grove::collections::RangeMap;
+
+    const NUM_KEYS: i32 = 300;
+    let mut rng = test_rng();
+    let mut reference: Vec<Option<u8>> = vec![None; NUM_KEYS as usize];
+    let mut map: RangeMap<i32, u8, T> = RangeMap::new();
+
+    for _ in 0..num_rounds {
+        let start = rng.gen_range(0..=NUM_KEYS);
+        let end = rng.gen_range(start..=NUM_KEYS.min(start + 40));
+        let keys = start as usize..end as usize;
+        let value = rng.gen_range(0..3);
+        match rng.gen_range(0..5) {
+            0 => {
+                map.assign(start..end, value);
+                reference[keys].fill(Some(value));
+            }
+            1 => {
+                map.insert_range(start..end, value);
+                for key in &mut reference[keys] {
+                    key.get_or_insert(value);
+                }
+            }
+            2 => {
+                map.remove_range(start..end);
+                reference[keys].fill(None);
+            }
+            3 => {
+                let gaps: Vec<Range<i32>> = map.gaps(start..end).collect();
+                let mut expected: Vec<Range<i32>> = vec![];
+                for key in start..end {
+                    if reference[key as usize].is_none() {
+                        match expected.last_mut() {
+                            Some(gap) if gap.end == key => gap.end += 1,
+                            _ => expected.push(key..key + 1),
+                        }
+                    }
+                }
+                assert_eq!(gaps, expected);
+            }
+            _ => {
+                let key = rng.gen_range(-10..NUM_KEYS + 10);
+                let expected = reference.get(key as usize).cloned().flatten();
+                assert_eq!(map.get(key).cloned(), expected);
+            }
+        }
+
+        // check the representation against the reference
+        let ranges: Vec<(Range<i32>, u8)> = map
+            .iter()
+            .map(|(range, value)| (range.clone(), *value))
+            .collect();
+        let mut values: Vec<Option<u8>> = vec![None; NUM_KEYS as usize];
+        for window in ranges.windows(2) {
+            let ((range1, value1), (range2, value2)) = (&window[0], &window[1]);
+            assert!(range1.end <= range2.start);
+            assert!(range1.end < range2.start || value1 != value2);
+        }
+        for (range, value) in ranges {
+            assert!(range.start < range.end);
+            for key in range {
+                values[key as usize] = Some(value);
+            }
+        }
+        assert_eq!(values, reference);
+        let covered = reference.iter().filter(|value| value.is_some()).count();
+        assert_eq!(map.covered_len(), covered as i32);
+        map.tree().assert_correctness();
+    }
+}
+
+/// Checks a range set against a vector that says whether every key is in the set.
+pub fn check_range_set<T>(num_rounds: u32)
+where
+    T: ConcatenableTree<grove::collections::range_map::RangeData<u32, ()>>,
+    for<'a> &'a mut T: SplittableTreeRef<grove::collections::range_map::RangeData<u32, ()>, T = T>,
+{
+    use grove::collections::RangeSet;
+
+    const NUM_KEYS: u32 = 300;
+    let mut rng = test_rng();
+    let mut reference = vec![false; NUM_KEYS as usize];
+    let mut set: RangeSet<u32, T> = RangeSet::new();
+
+    for _ in 0..num_rounds {
+        let start = rng.gen_range(0..=NUM_KEYS);
+        let end = rng.gen_range(start..=NUM_KEYS.min(start + 40));
+        let keys = start as usize..end as usize;
+        match rng.gen_range(0..3) {
+            0 => {
+                set.insert_range(start..end);
+                reference[keys].fill(true);
+            }
+            1 => {
+                set.remove_range(start..end);
+                reference[keys].fill(false);
+            }
+            _ => {
+                let key = rng.gen_range(0..NUM_KEYS + 10);
+                let expected = reference.get(key as usize).cloned().unwrap_or(false);
+                assert_eq!(set.contains(key), expected);
+            }
+        }
+
+        let ranges: Vec<Range<u32>> = set.iter().cloned().collect();
+        let mut expected: Vec<Range<u32>> = vec![];
+        for key in 0..NUM_KEYS {
+            if reference[key as usize] {
+                match expected.last_mut() {
+                    Some(range) if range.end == key => range.end += 1,
+                    _ => expected.push(key..key + 1),
+                }
+            }
+        }
+        assert_eq!(ranges, expected);
+        let gaps: Vec<Range<u32>> = set.gaps(0..NUM_KEYS).collect();
+        let covered: u32 = expected.iter().map(|range| range.end - range.start).sum();
+        let uncovered: u32 = gaps.iter().map(|gap| gap.end - gap.start).sum();
+        assert_eq!(set.covered_len(), covered);
+        assert_eq!(covered + uncovered, NUM_KEYS);
+        set.tree().assert_correctness();
+    }
+}
+
+/// Checks [`SomeTree::max_right`] and [`SomeTree::min_left`] against a linear search,
+/// with a prefix sum predicate and a maximum predicate.
+pub fn check_max_right_min_left<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum>,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+{
+    let mut rng = test_rng();
+    // values are nonnegative, so that bounding the sum is monotone
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE).map(|_| rng.gen_range(0..100)).collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    let round = |rng: &mut StdRng, tree: &mut T, reference: &mut Vec<i32>| {
+        let len = reference.len();
+        let bound = rng.gen_range(0..2000);
+        let threshold = rng.gen_range(0..100);
+        let index = rng.gen_range(0..=len);
+
+        let mut sum = 0;
+        let expected = (index..len)
+            .find(|&i| {
+                sum += reference[i];
+                sum > bound
+            })
+            .unwrap_or(len);
+        assert_eq!(tree.max_right(index, |s| s.sum <= bound), expected);
+        let expected = (index..len)
+            .find(|&i| reference[i] >= threshold)
+            .unwrap_or(len);
+        let below = |s: &NumSummary| s.max < Some(threshold);
+        assert_eq!(tree.max_right(index, below), expected);
+
+        let mut sum = 0;
+        let expected = (0..index)
+            .rev()
+            .find(|&i| {
+                sum += reference[i];
+                sum > bound
+            })
+            .map_or(0, |i| i + 1);
+        assert_eq!(tree.min_left(index, |s| s.sum <= bound), expected);
+        let expected = (0..index)
+            .rev()
+            .find(|&i| reference[i] >= threshold)
+            .map_or(0, |i| i + 1);
+        assert_eq!(tree.min_left(index, below), expected);
+    };
+    run_vec_rounds(num_rounds, 0..100, rng, &mut tree, &mut reference, round);
+}
+
+/// Returns a random bound on values in the range `0..max`.
+fn random_bound<R: Rng>(rng: &mut R, max: i32) -> std::ops::Bound<i32> {
+    use std::ops::Bound;
+    match rng.gen_range(0..3) {
+        0 => Bound::Included(rng.gen_range(0..max)),
+        1 => Bound::Excluded(rng.gen_range(0..max)),
+        _ => Bound::Unbounded,
+    }
+}
+
+/// Returns the values in the segment of the locator.
+fn located<T, L>(tree: &mut T, locator: L) -> Vec<i32>
+where
+    T: SomeTree<StdNum>,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+    L: grove::locators::Locator<StdNum>,
+{
+    tree.iter_locator(locator).cloned().collect()
+}
+
+/// Checks the locators of pairs of bounds and of owned ranges, by keys and by indices,
+/// on a sorted tree.
+pub fn check_bound_locators<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum>,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+{
+    use grove::locators::ByKey;
+    use std::ops::{Bound, RangeBounds};
+
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE).map(|_| rng.gen_range(0..100)).collect();
+    reference.sort_unstable();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    for _ in 0..num_rounds {
+        let keys = (random_bound(&mut rng, 100), random_bound(&mut rng, 100));
+        let expected: Vec<i32> = reference
+            .iter()
+            .cloned()
+            .filter(|key| keys.contains(key))
+            .collect();
+        let borrowed = ByKey((keys.0.as_ref(), keys.1.as_ref()));
+        assert_eq!(located(&mut tree, borrowed), expected);
+        let owned = ByKey(keys);
+        assert_eq!(located(&mut tree, &owned), expected);
+
+        let low = rng.gen_range(0..100);
+        let high = rng.gen_range(0..100);
+        let expected: Vec<i32> = reference
+            .iter()
+            .cloned()
+            .filter(|key| (low..high).contains(key))
+            .collect();
+        let owned = ByKey(low..high);
+        assert_eq!(located(&mut tree, &owned), expected);
+        let expected: Vec<i32> = reference
+            .iter()
+            .cloned()
+            .filter(|key| (low..=high).contains(key))
+            .collect();
+        let owned = ByKey(low..=high);
+        assert_eq!(located(&mut tree, &owned), expected);
+
+        let len = reference.len() as i32;
+        let indices = (
+            random_bound(&mut rng, len + 1).map(|i| i as usize),
+            random_bound(&mut rng, len + 1).map(|i| i as usize),
+        );
+        let expected: Vec<i32> = (0..reference.len())
+            .filter(|index| indices.contains(index))
+            .map(|index| reference[index])
+            .collect();
+        assert_eq!(located(&mut tree, indices), expected);
+        let by_reference: &(Bound<usize>, Bound<usize>) = &indices;
+        assert_eq!(located(&mut tree, by_reference), expected);
+
+        // keep the tree sorted
+        let value = rng.gen_range(0..100);
+        let index = reference.partition_point(|&key| key <= value);
+        tree.slice(index..index).insert(value).unwrap();
+        reference.insert(index, value);
+        tree.assert_correctness();
+    }
+}
+
+/// Checks visiting, iterating over and acting on the values that are bigger than a threshold,
+/// using a subtree filter.
+pub fn check_matching<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum> + AsBasicTree<StdNum>,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+{
+    use grove::locators::FilterResult::{self, *};
+
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE).map(|_| rng.gen_range(0..100)).collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    let round = |rng: &mut StdRng, tree: &mut T, reference: &mut Vec<i32>| {
+        let len = reference.len();
+        let threshold = rng.gen_range(-50..150);
+        let bigger = |summary: &NumSummary| -> FilterResult {
+            if summary.max <= Some(threshold) {
+                Skip
+            } else if summary.min > Some(threshold) {
+                TakeAll
+            } else {
+                Descend
+            }
+        };
+        match rng.gen_range(0..3) {
+            0 => {
+                let start = rng.gen_range(0..=len);
+                let end = rng.gen_range(start..=len);
+                tree.act_segment(RevAffineAction::reversal(), start..end);
+                reference[start..end].reverse();
+            }
+            1 => {
+                let action = RevAffineAction {
+                    to_reverse: false,
+                    mul: 1,
+                    add: rng.gen_range(-10..10),
+                };
+                tree.act_matching(action, bigger);
+                for value in reference.iter_mut() {
+                    if *value > threshold {
+                        *value += action.add;
+                    }
+                }
+            }
+            _ => {
+                let mut found = vec![];
+                tree.for_each_matching(bigger, |&value| found.push(value));
+                let expected: Vec<i32> = reference
+                    .iter()
+                    .cloned()
+                    .filter(|&value| value > threshold)
+                    .collect();
+                assert_eq!(found, expected);
+                assert!(tree.iter_matching(bigger).eq(expected.iter()));
+            }
+        }
+    };
+    run_vec_rounds(num_rounds, 0..100, rng, &mut tree, &mut reference, round);
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), reference);
+}
+
+/// Returns a random action of adding, chmin or chmax, or a combination of them.
+fn random_beats_action<R: Rng>(rng: &mut R) -> ChminChmaxAction {
+    match rng.gen_range(0..4) {
+        0 => ChminChmaxAction::add(rng.gen_range(-20..20)),
+        1 => ChminChmaxAction::chmin(rng.gen_range(-100..100)),
+        2 => ChminChmaxAction::chmax(rng.gen_range(-100..100)),
+        _ => {
+            let lower = rng.gen_range(-100..100);
+            ChminChmaxAction {
+                add: rng.gen_range(-20..20),
+                lower: Some(lower),
+                upper: Some(rng.gen_range(lower..100)),
+            }
+        }
+    }
+}
+
+/// Returns the summary of the values, computed directly.
+fn beats_summary(values: &[i32]) -> BeatsSummary {
+    values
+        .iter()
+        .fold(Default::default(), |acc, value| acc + value.to_summary())
+}
+
+/// Checks segment tree beats actions, which can't always be applied on whole subtrees.
+pub fn check_beats<T>(num_rounds: u32)
+where
+    T: SomeTree<ChminChmaxSum>,
+    for<'a> &'a mut T: ModifiableTreeRef<ChminChmaxSum>,
+{
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE)
+        .map(|_| rng.gen_range(-100..100))
+        .collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    let round = |rng: &mut StdRng, tree: &mut T, reference: &mut Vec<i32>| {
+        let range = random_range(rng, reference.len());
+        if rng.gen() {
+            let action = random_beats_action(rng);
+            tree.act_segment(action, range.clone());
+            apply_to_reference(&mut reference[range], action);
+        } else {
+            let expected = beats_summary(&reference[range.clone()]);
+            assert_eq!(tree.segment_summary(range), expected);
+        }
+    };
+    run_vec_rounds(num_rounds, -100..100, rng, &mut tree, &mut reference, round);
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), reference);
+}
+
+/// Checks segment tree beats actions on an [`ArenaTreap`] and a [`PersistentTreap`],
+/// whose nodes are separate from the other trees' nodes.
+///
+/// [`ArenaTreap`]: grove::arena::treap::ArenaTreap
+/// [`PersistentTreap`]: grove::persistent::treap::PersistentTreap
+pub fn check_beats_arena_and_persistent(num_rounds: u32) {
+    use grove::{arena::treap::ArenaTreap, persistent::treap::PersistentTreap};
+
+    let mut rng = test_rng();
+    let reference: Vec<i32> = (0..INITIAL_SIZE)
+        .map(|_| rng.gen_range(-100..100))
+        .collect();
+    let mut arena: ArenaTreap<ChminChmaxSum> = reference.iter().cloned().collect();
+    let mut persistent: PersistentTreap<ChminChmaxSum> = reference.iter().cloned().collect();
+    let mut snapshots = vec![(persistent.clone(), reference.clone())];
+    let mut reference = reference;
+
+    for _ in 0..num_rounds {
+        let range = random_range(&mut rng, reference.len());
+        if rng.gen_bool(0.5) {
+            let action = random_beats_action(&mut rng);
+            arena.act_segment(action, range.clone());
+            persistent.act_segment(action, range.clone());
+            apply_to_reference(&mut reference[range], action);
+            snapshots.push((persistent.clone(), reference.clone()));
+        } else {
+            let expected = beats_summary(&reference[range.clone()]);
+            assert_eq!(arena.segment_summary(range.clone()), expected);
+            assert_eq!(persistent.segment_summary(range), expected);
+        }
+        arena.assert_correctness();
+    }
+    assert_eq!(arena.iter().cloned().collect::<Vec<_>>(), reference);
+    for (snapshot, values) in snapshots {
+        snapshot.assert_correctness();
+        assert_eq!(snapshot.iter().collect::<Vec<_>>(), values);
+    }
+}
+
+/// Checks segment tree beats actions on the paths of a [`grove::link_cut::LinkCutForest`].
+pub fn check_beats_link_cut_forest(num_rounds: u32) {
+    use grove::link_cut::LinkCutForest;
+
+    const NUM_VERTICES: usize = 40;
+    let mut rng = test_rng();
+    let mut forest: LinkCutForest<ChminChmaxSum> = LinkCutForest::new();
+    let mut naive = NaiveForest {
+        values: (0..NUM_VERTICES)
+            .map(|_| rng.gen_range(-100..100))
+            .collect(),
+        parent: vec![None; NUM_VERTICES],
+    };
+    let vertices: Vec<_> = naive
+        .values
+        .iter()
+        .map(|&value| forest.add_vertex(value))
+        .collect();
+
+    for _ in 0..num_rounds {
+        let u = rng.gen_range(0..NUM_VERTICES);
+        let v = rng.gen_range(0..NUM_VERTICES);
+        match rng.gen_range(0..4) {
+            0 => {
+                let can_link = naive.parent[u].is_none() && naive.root(u) != naive.root(v);
+                assert_eq!(forest.link(vertices[u], vertices[v]).is_some(), can_link);
+                if can_link {
+                    naive.parent[u] = Some(v);
+                }
+            }
+            1 => {
+                if let Some(p) = naive.parent[u] {
+                    forest.cut(vertices[u], vertices[p]).unwrap();
+                    naive.parent[u] = None;
+                }
+            }
+            _ => {
+                let action = random_beats_action(&mut rng);
+                forest.root_path_act(vertices[u], action);
+                for w in naive.ancestors(u) {
+                    action.act_inplace(&mut naive.values[w]);
+                }
+            }
+        }
+        let expected = beats_summary(&naive.values_of(naive.ancestors(u)));
+        assert_eq!(forest.root_path_summary(vertices[u]), expected);
+        assert_eq!(*forest.get(vertices[v]), naive.values[v]);
+        forest.assert_correctness();
+    }
+}
+
+/// Checks segment tree beats actions on the components and subtrees
+/// of a [`grove::euler_tour::EulerTourForest`].
+pub fn check_beats_euler_tour_forest(num_rounds: u32) {
+    use grove::euler_tour::EulerTourForest;
+
+    const NUM_VERTICES: usize = 40;
+    let mut rng = test_rng();
+    let mut forest: EulerTourForest<ChminChmaxSum, _> = EulerTourForest::with_seed(rng.gen());
+
+    // a single path, where the action can't be applied on the summary of the whole tour
+    let path: Vec<_> = [3, 1, 4, 1, 5, 9, 2, 6]
+        .iter()
+        .map(|&value| forest.add_vertex(value))
+        .collect();
+    for edge in path.windows(2) {
+        forest.link(edge[1], edge[0]).unwrap();
+    }
+    forest.component_act(path[0], ChminChmaxAction::chmin(4));
+    assert_eq!(
+        forest.component_summary(path[0]),
+        beats_summary(&[3, 1, 4, 1, 4, 4, 2, 4])
+    );
+    forest.assert_correctness();
+
+    let mut naive = NaiveForest {
+        values: (0..NUM_VERTICES)
+            .map(|_| rng.gen_range(-100..100))
+            .collect(),
+        parent: vec![None; NUM_VERTICES],
+    };
+    let vertices: Vec<_> = naive
+        .values
+        .iter()
+        .map(|&value| forest.add_vertex(value))
+        .collect();
+
+    for _ in 0..num_rounds {
+        let u = rng.gen_range(0..NUM_VERTICES);
+        let v = rng.gen_range(0..NUM_VERTICES);
+        let connected = naive.root(u) == naive.root(v);
+        match rng.gen_range(0..4) {
+            0 => {
+                assert_eq!(forest.link(vertices[u], vertices[v]).is_some(), !connected);
+                if !connected {
+                    naive.make_root(u);
+                    naive.parent[u] = Some(v);
+                }
+            }
+            1 => {
+                if let Some(p) = naive.parent[u] {
+                    forest.cut(vertices[u], vertices[p]).unwrap();
+                    naive.parent[u] = None;
+                }
+            }
+            2 => {
+                let action = random_beats_action(&mut rng);
+                let res = forest.subtree_act(vertices[u], vertices[v], action);
+                if naive.parent[u] == Some(v) {
+                    res.unwrap();
+                    for w in naive.subtree(u) {
+                        action.act_inplace(&mut naive.values[w]);
+                    }
+                    let expected = beats_summary(&naive.values_of(naive.subtree(u)));
+                    assert_eq!(
+                        forest.subtree_summary(vertices[u], vertices[v]),
+                        Some(expected)
+                    );
+                } else {
+                    assert_eq!(res, None);
+                }
+            }
+            _ => {
+                let action = random_beats_action(&mut rng);
+                forest.component_act(vertices[u], action);
+                for w in naive.subtree(naive.root(u)) {
+                    action.act_inplace(&mut naive.values[w]);
+                }
+            }
+        }
+        let root = naive.root(u);
+        let expected = beats_summary(&naive.values_of(naive.subtree(root)));
+        assert_eq!(forest.component_summary(vertices[u]), expected);
+        assert_eq!(*forest.get(vertices[u]), naive.values[u]);
+        forest.assert_correctness();
+    }
+}
+
+/// The three largest values of a segment, in decreasing order, and the segment's size.
+/// Used for checking summaries that aren't [`Copy`].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct TopThree {
+    pub size: usize,
+    pub top: Vec<i32>,
+}
+
+impl std::ops::Add for TopThree {
+    type Output = TopThree;
+    fn add(mut self, other: TopThree) -> TopThree {
+        self.top.extend(other.top);
+        self.top.sort_unstable_by(|a, b| b.cmp(a));
+        self.top.truncate(3);
+        TopThree {
+            size: self.size + other.size,
+            top: self.top,
+        }
+    }
+}
+
+impl SizedSummary for TopThree {
+    fn size(&self) -> usize {
+        self.size
+    }
+}
+
+impl ToSummary<TopThree> for i32 {
+    fn to_summary(&self) -> TopThree {
+        TopThree {
+            size: 1,
+            top: vec![*self],
+        }
+    }
+}
+
+/// Adds a constant to values, and possibly reverses them.
+/// Used for checking actions that aren't [`Copy`].
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ShiftAction {
+    pub add: i32,
+    pub to_reverse: bool,
+}
+
+impl std::ops::Add for ShiftAction {
+    type Output = ShiftAction;
+    fn add(self, other: ShiftAction) -> ShiftAction {
+        ShiftAction {
+            add: self.add + other.add,
+            to_reverse: self.to_reverse ^ other.to_reverse,
+        }
+    }
+}
+
+impl Action for ShiftAction {
+    fn is_identity(&self) -> bool {
+        self.add == 0 && !self.to_reverse
+    }
+
+    fn to_reverse(&self) -> bool {
+        self.to_reverse
+    }
+}
+
+impl Acts<i32> for ShiftAction {
+    fn act_inplace(&self, value: &mut i32) {
+        *value += self.add;
+    }
+}
+
+impl Acts<TopThree> for ShiftAction {
+    fn act_inplace(&self, summary: &mut TopThree) {
+        for value in summary.top.iter_mut() {
+            *value += self.add;
+        }
+    }
+}
+
+/// Trees of numbers, whose summaries are [`TopThree`]s.
+pub type TopThreeData = (i32, TopThree, ShiftAction);
+
+/// Checks trees whose summaries and actions are only [`Clone`], against a vector.
+pub fn check_clone_only_data<T>(num_rounds: u32)
+where
+    T: SomeTree<TopThreeData>,
+    for<'a> &'a mut T: ModifiableTreeRef<TopThreeData>,
+{
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE)
+        .map(|_| rng.gen_range(-MAX_ADD..=MAX_ADD))
+        .collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    let round = |rng: &mut StdRng, tree: &mut T, reference: &mut Vec<i32>| {
+        let range = random_range(rng, reference.len());
+        if rng.gen() {
+            let action = ShiftAction {
+                add: rng.gen_range(-MAX_ADD..=MAX_ADD),
+                to_reverse: rng.gen(),
+            };
+            apply_to_reference(&mut reference[range.clone()], action.clone());
+            tree.act_segment(action, range);
+        } else {
+            let expected = reference[range.clone()]
+                .iter()
+                .fold(TopThree::default(), |acc, value| acc + value.to_summary());
+            assert_eq!(tree.segment_summary(range), expected);
+        }
+    };
+    let values = -MAX_ADD..MAX_ADD + 1;
+    run_vec_rounds(num_rounds, values, rng, &mut tree, &mut reference, round);
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), reference);
+}
+
+/// Checks the queries that only need immutable access, [`BasicTree::get`] and
+/// [`BasicTree::iter_imm`], on trees with pending actions, against a vector.
+///
+/// [`BasicTree::get`]: grove::basic_tree::BasicTree::get
+/// [`BasicTree::iter_imm`]: grove::basic_tree::BasicTree::iter_imm
+pub fn check_imm_queries<T>(num_rounds: u32, allow_reversals: bool)
+where
+    T: SomeTree<StdNum> + AsBasicTree<StdNum>,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+{
+    let rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE as i32).collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    let round = |rng: &mut StdRng, tree: &mut T, reference: &mut Vec<i32>| {
+        let len = reference.len();
+        if rng.gen() {
+            let range = random_range(rng, len);
+            let action = random_action(rng, allow_reversals);
+            tree.act_segment(action, range.clone());
+            apply_to_reference(&mut reference[range], action);
+        } else {
+            let index = rng.gen_range(0..len + 10);
+            let value = tree.as_basic_tree().get(index);
+            assert_eq!(
+                value.as_ref().map(|value| value.to_value()),
+                reference.get(index).cloned()
+            );
+            if let Some(value) = value {
+                assert_eq!(value.summary(), value.to_value().to_summary());
+            }
+        }
+        let values: Vec<i32> = tree
+            .as_basic_tree()
+            .iter_imm()
+            .map(|value| value.to_value())
+            .collect();
+        assert_eq!(&values, reference);
+    };
+    let values = -MAX_ADD..MAX_ADD + 1;
+    run_vec_rounds(num_rounds, values, rng, &mut tree, &mut reference, round);
+}
+
+/// A value that isn't [`Clone`].
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Unclonable(pub i32);
+
+/// Checks that trees of values that aren't [`Clone`] can be read from several threads at once,
+/// behind a [`std::sync::RwLock`].
+pub fn check_imm_queries_without_clone() {
+    use example_data::SizeData;
+    use grove::{avl::AVLTree, locators::ByKey, treap::Treap};
+    use std::sync::RwLock;
+
+    let values = || (0..INITIAL_SIZE as i32).map(|x| Unclonable(2 * x));
+    let treap: RwLock<Treap<SizeData<Unclonable>>> = RwLock::new(values().collect());
+    std::thread::scope(|scope| {
+        for reader in 0..4 {
+            let treap = &treap;
+            scope.spawn(move || {
+                let treap = treap.read().unwrap();
+                for index in (reader..INITIAL_SIZE).step_by(4) {
+                    let key = Unclonable(2 * index as i32);
+                    assert_eq!(treap.get(index).unwrap().get(), Some(&key));
+                    assert_eq!(treap.get(ByKey((&key,))).unwrap().get(), Some(&key));
+                    let missing = Unclonable(2 * index as i32 + 1);
+                    assert!(treap.search_imm(ByKey((&missing,))).is_empty());
+                    assert_eq!(treap.search_imm(ByKey((&key,))).left_summary().size, index);
+                }
+                assert!(treap.get(INITIAL_SIZE).is_none());
+                let expected: Vec<_> = values().collect();
+                assert!(treap
+                    .iter_imm()
+                    .map(|value| value.get().unwrap())
+                    .eq(&expected));
+            });
+        }
+    });
+
+    let avl: AVLTree<SizeData<Unclonable>> = values().collect();
+    let expected: Vec<_> = values().collect();
+    assert!(avl
+        .iter_imm()
+        .map(|value| value.get().unwrap())
+        .eq(&expected));
+    assert_eq!(avl.get(7).unwrap().get(), Some(&Unclonable(14)));
+}
+
+/// Consumes the iterator from both ends at random, and checks that it returns the expected values,
+/// and that its length stays correct.
+fn check_double_ended<I, R>(rng: &mut R, mut iter: I, expected: &[i32])
+where
+    I: DoubleEndedIterator<Item = i32> + ExactSizeIterator,
+    R: Rng,
+{
+    let mut expected = expected
+        .iter()
+        .cloned()
+        .collect::<std::collections::VecDeque<_>>();
+    loop {
+        assert_eq!(iter.len(), expected.len());
+        let (value, expected_value) = if rng.gen() {
+            (iter.next(), expected.pop_front())
+        } else {
+            (iter.next_back(), expected.pop_back())
+        };
+        assert_eq!(value, expected_value);
+        if value.is_none() {
+            break;
+        }
+    }
+}
+
+/// Tests iterating over segments from both ends, after applying actions with reversals.
+pub fn check_double_ended_iteration<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum> + IntoIterator<Item = i32>,
+    T::IntoIter: DoubleEndedIterator,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+{
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE as i32).collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    for _ in 0..num_rounds {
+        let range = random_range(&mut rng, reference.len());
+        let action = random_action(&mut rng, true);
+        tree.act_segment(action, range.clone());
+        apply_to_reference(&mut reference[range], action);
+
+        let range = random_range(&mut rng, reference.len());
+        let iter = tree.iter_locator_sized(range.clone()).cloned();
+        check_double_ended(&mut rng, iter, &reference[range.clone()]);
+        assert!(tree
+            .iter_locator(range.clone())
+            .rev()
+            .eq(reference[range].iter().rev()));
+    }
+    tree.assert_correctness();
+    check_double_ended(&mut rng, tree.into_iter_sized(), &reference);
+}
+
+/// Tests the summaries and indices returned by [`SomeTree::iter_with_context`] and
+/// [`SomeTree::iter_indexed`] against [`SomeTree::segment_summary`].
+pub fn check_iter_with_context<T>(num_rounds: u32)
+where
+    T: SomeTree<StdNum>,
+    for<'a> &'a mut T: ModifiableTreeRef<StdNum>,
+{
+    let mut rng = test_rng();
+    let mut tree: T = (0..INITIAL_SIZE as i32).collect();
+    let len = INITIAL_SIZE;
+
+    for _ in 0..num_rounds {
+        let range = random_range(&mut rng, len);
+        tree.act_segment(random_action(&mut rng, true), range);
+
+        let range = random_range(&mut rng, len);
+        let mut expected = vec![];
+        for index in range.clone() {
+            expected.push((
+                tree.segment_summary(..index),
+                tree.slice(index..=index).iter().cloned().next().unwrap(),
+                tree.segment_summary(index + 1..),
+            ));
+        }
+        let mut expected: std::collections::VecDeque<_> = expected.into_iter().collect();
+        let mut iter = tree.iter_with_context(range.clone());
+        loop {
+            let (res, expected_res) = if rng.gen() {
+                (iter.next(), expected.pop_front())
+            } else {
+                (iter.next_back(), expected.pop_back())
+            };
+            assert_eq!(
+                res.map(|(left, value, right)| (left, *value, right)),
+                expected_res
+            );
+            if expected_res.is_none() {
+                break;
+            }
+        }
+
+        assert!(tree
+            .iter_indexed(range.clone())
+            .map(|(index, _)| index)
+            .eq(range));
+    }
+    tree.assert_correctness();
+}
+
+/// Tests [`SomeTree::for_each_mut`], [`SomeTree::map_segment`] and [`SomeTree::retain`],
+/// and that the summaries are rebuilt after them.
+pub fn check_for_each_mut_and_retain<T>(num_rounds: u32, allow_reversals: bool)
+where
+    T: ConcatenableTree<StdNum>,
+    for<'a> &'a mut T: SplittableTreeRef<StdNum, T = T> + ModifiableTreeRef<StdNum>,
+{
+    let mut rng = test_rng();
+    let mut reference: Vec<i32> = (0..INITIAL_SIZE as i32).collect();
+    let mut tree: T = reference.iter().cloned().collect();
+
+    for _ in 0..num_rounds {
+        let len = reference.len();
+        let range = random_range(&mut rng, len);
+        match rng.gen_range(0..5) {
+            0 => {
+                let action = random_action(&mut rng, allow_reversals);
+                tree.act_segment(action, range.clone());
+                apply_to_reference(&mut reference[range.clone()], action);
+            }
+            1 => {
+                let add = rng.gen_range(-MAX_ADD..=MAX_ADD);
+                tree.for_each_mut(range.clone(), |value| *value += add);
+                for value in reference[range.clone()].iter_mut() {
+                    *value += add;
+                }
+            }
+            2 => {
+                let mul = rng.gen_range(-3..=3);
+                // keeps the values small
+                tree.slice(range.clone()).map(|value| value % 1000 * mul);
+                for value in reference[range.clone()].iter_mut() {
+                    *value = *value % 1000 * mul;
+                }
+            }
+            3 => {
+                let modulus = rng.gen_range(2..5);
+                let pred = |value: &i32| value % modulus != 0;
+                if rng.gen() {
+                    tree.retain(range.clone(), pred);
+                } else {
+                    // the same segment, located by the summaries to its right
+                    let (start, end) = (range.start as i32, range.end as i32);
+                    let len = len as i32;
+                    let locator = move |_: &NumSummary, _: &i32, right: &NumSummary| {
+                        if right.size > len - 1 - start {
+                            locators::LocResult::GoRight
+                        } else if right.size < len - end {
+                            locators::LocResult::GoLeft
+                        } else {
+                            locators::LocResult::Accept
+                        }
+                    };
+                    tree.retain(locator, pred);
+                }
+                let removed: Vec<i32> = reference
+                    .splice(range.clone(), vec![])
+                    .filter(|value| value % modulus != 0)
+                    .collect();
+                reference.splice(range.start..range.start, removed);
+            }
+            _ => {
+                // refill the tree, so that it doesn't become empty
+                for _ in 0..rng.gen_range(0..20) {
+                    let index = rng.gen_range(0..=reference.len());
+                    let value = rng.gen_range(-MAX_ADD..=MAX_ADD);
+                    tree.slice(index..index).insert(value).unwrap();
+                    reference.insert(index, value);
+                }
+            }
+        }
+
+        let range = random_range(&mut rng, reference.len());
+        let expected = reference[range.clone()]
+            .iter()
+            .fold(NumSummary::default(), |summary, value| {
+                summary + value.to_summary()
+            });
+        assert_eq!(tree.segment_summary(range), expected);
+    }
+    tree.assert_correctness();
+    assert_eq!(tree.into_iter().collect::<Vec<_>>(), reference);
+}
@@ -4,7 +4,20 @@ pub mod common;
 pub use common::*;
 
 use grove::data::example_data::*;
-use grove::{avl::AVLTree, basic_tree::BasicTree, splay::SplayTree, treap::Treap};
+use grove::*;
+use grove::{
+    avl,
+    avl::AVLTree,
+    basic_tree::BasicTree,
+    red_black::RBTree,
+    scapegoat::ScapegoatTree,
+    splay::SplayTree,
+    treap,
+    treap::{RngPriorities, Treap},
+    weight_balanced,
+    weight_balanced::WBTree,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 const NUM_ROUNDS: u32 = if cfg!(not(miri)) { 10_000 } else { 100 }; // miri is too slow
 const NUM_ROUNDS_SLOW: u32 = if cfg!(not(miri)) { 100 } else { 10 }; // miri is too slow
@@ -24,6 +37,21 @@ fn splay_and_avl_consistency() {
     check_consistency::<StdNum, SplayTree<_>, AVLTree<_>>(NUM_ROUNDS);
 }
 
+#[test]
+fn splay_and_rb_consistency() {
+    check_consistency::<StdNum, SplayTree<_>, RBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_and_wb_consistency() {
+    check_consistency::<StdNum, SplayTree<_>, WBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_and_scapegoat_consistency() {
+    check_consistency_without_reversals::<StdNum, SplayTree<_>, ScapegoatTree<_>>(NUM_ROUNDS);
+}
+
 #[test]
 fn splay_and_treap_consistency_noncommutative() {
     check_consistency::<(i32, PolyNum<3>, RevAffineAction), SplayTree<_>, Treap<_>>(
@@ -38,11 +66,64 @@ fn splay_and_avl_consistency_noncommutative() {
     );
 }
 
+#[test]
+fn splay_and_rb_consistency_noncommutative() {
+    check_consistency::<(i32, PolyNum<3>, RevAffineAction), SplayTree<_>, RBTree<_>>(
+        NUM_ROUNDS_SLOW,
+    );
+}
+
+#[test]
+fn splay_and_wb_consistency_noncommutative() {
+    check_consistency::<(i32, PolyNum<3>, RevAffineAction), SplayTree<_>, WBTree<_>>(
+        NUM_ROUNDS_SLOW,
+    );
+}
+
+#[test]
+fn splay_and_scapegoat_consistency_noncommutative() {
+    check_consistency_without_reversals::<
+        (i32, PolyNum<3>, RevAffineAction),
+        SplayTree<_>,
+        ScapegoatTree<_>,
+    >(NUM_ROUNDS_SLOW);
+}
+
 #[test]
 fn treap_consistency_noncommutative() {
     check_consistency::<(i32, PolyNum<3>, RevAffineAction), Treap<_>, Treap<_>>(NUM_ROUNDS_SLOW);
 }
 
+#[test]
+fn splay_and_seeded_treap_consistency() {
+    check_consistency::<StdNum, SplayTree<_>, Treap<_, RngPriorities<StdRng>>>(NUM_ROUNDS);
+}
+
+#[test]
+#[cfg(debug_assertions)] // `representation` is only available in debug builds
+fn seeded_treap_is_reproducible() {
+    let build = || {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut tree: Treap<StdNum, _> = Treap::with_seed(42);
+        for i in 0..300 {
+            let index = rng.gen_range(0..=i);
+            tree.slice(index..index).insert(i as i32).unwrap();
+            if rng.gen_range(0..4) == 0 {
+                let index = rng.gen_range(0..=i);
+                tree.slice(index..=index).delete();
+            }
+        }
+        tree
+    };
+    let (tree1, tree2) = (build(), build());
+    let print = |node: &grove::basic_tree::BasicNode<_, u64>| format!("{}", node.alg_data());
+    assert_eq!(
+        tree1.representation(&print, false),
+        tree2.representation(&print, false)
+    );
+    tree1.assert_correctness();
+}
+
 #[test]
 fn splay_insert() {
     check_insert::<SplayTree<_>>(true);
@@ -53,6 +134,21 @@ fn avl_insert() {
     check_insert::<AVLTree<_>>(false);
 }
 
+#[test]
+fn rb_insert() {
+    check_insert::<RBTree<_>>(false);
+}
+
+#[test]
+fn wb_insert() {
+    check_insert::<WBTree<_>>(false);
+}
+
+#[test]
+fn scapegoat_insert() {
+    check_insert::<ScapegoatTree<_>>(false);
+}
+
 #[test]
 fn treap_insert() {
     check_insert::<Treap<_>>(true);
@@ -73,12 +169,441 @@ fn avl_delete() {
     check_delete::<AVLTree<_>>();
 }
 
+#[test]
+fn rb_delete() {
+    check_delete::<RBTree<_>>();
+}
+
+#[test]
+fn wb_delete() {
+    check_delete::<WBTree<_>>();
+}
+
+#[test]
+fn wb_set_operations() {
+    check_set_operations::<WBTree<_>>(
+        weight_balanced::union,
+        weight_balanced::intersection,
+        weight_balanced::difference,
+    );
+}
+
+#[test]
+fn treap_set_operations() {
+    check_set_operations::<Treap<_>>(treap::union, treap::intersection, treap::difference);
+    check_set_policies::<Treap<_>>(
+        treap::union_with,
+        treap::intersection_with,
+        treap::symmetric_difference,
+        treap::is_subset,
+    );
+}
+
+#[test]
+fn avl_set_operations() {
+    check_set_operations::<AVLTree<_>>(avl::union, avl::intersection, avl::difference);
+    check_set_policies::<AVLTree<_>>(
+        avl::union_with,
+        avl::intersection_with,
+        avl::symmetric_difference,
+        avl::is_subset,
+    );
+}
+
+#[test]
+fn persistent_treap_snapshots() {
+    check_persistent_treap(NUM_ROUNDS);
+}
+
+#[test]
+fn arena_treap_consistency() {
+    check_arena_treap(NUM_ROUNDS);
+}
+
+#[test]
+fn arena_treap_handles() {
+    check_arena_handles(NUM_ROUNDS);
+}
+
+#[test]
+#[should_panic(expected = "the node was freed")]
+fn arena_treap_freed_handle() {
+    let mut tree: grove::arena::treap::ArenaTreap<StdNum> = (0..10).collect();
+    let handle = tree.insert(5..5, 100).unwrap();
+    assert_eq!(tree.remove(handle), 100);
+    // this reuses the slot of the removed value
+    let new_handle = tree.insert(5..5, 200).unwrap();
+    assert_ne!(new_handle, handle);
+    tree.get(handle);
+}
+
+#[test]
+#[should_panic(expected = "the node belongs to a different arena")]
+fn arena_treap_foreign_handle() {
+    let mut tree: grove::arena::treap::ArenaTreap<StdNum> = (0..10).collect();
+    let mut other: grove::arena::treap::ArenaTreap<StdNum> = (0..10).collect();
+    // both trees have a node at this slot, but the handle belongs to `other`
+    let handle = other.insert(5..5, 100).unwrap();
+    tree.get(handle);
+}
+
+#[test]
+#[should_panic(expected = "the node belongs to a different arena")]
+fn link_cut_forest_foreign_vertex() {
+    use grove::link_cut::LinkCutForest;
+
+    let mut forest: LinkCutForest<StdNum> = LinkCutForest::new();
+    let mut other: LinkCutForest<StdNum> = LinkCutForest::new();
+    forest.add_vertex(1);
+    let v = other.add_vertex(2);
+    forest.get(v);
+}
+
+#[test]
+fn link_cut_forest() {
+    check_link_cut_forest(NUM_ROUNDS);
+}
+
+#[test]
+fn euler_tour_forest() {
+    check_euler_tour_forest(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_seq() {
+    check_seq::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_seq() {
+    check_seq::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_seq() {
+    check_seq::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_seq_get_mut_splays() {
+    use grove::collections::Seq;
+
+    let mut seq: Seq<i32, SplayTree<_>> = (0..100).collect();
+    for index in [17, 80, 3, 99, 0] {
+        *seq.get_mut(index).unwrap() += 1000;
+        // the value was splayed to the root
+        let root = seq.tree().as_basic_tree();
+        assert_eq!(root.left_subtree_summary().unwrap().size, index);
+        assert_eq!(seq[index], index as i32 + 1000);
+    }
+}
+
+#[test]
+fn treap_ordered_map() {
+    check_ordered_map::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_ordered_map() {
+    check_ordered_map::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_ordered_map() {
+    check_ordered_map::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn red_black_ordered_set() {
+    check_ordered_set::<RBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn scapegoat_ordered_set() {
+    check_ordered_set::<ScapegoatTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_rope() {
+    check_rope::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_rope() {
+    check_rope::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_rope_deep_display() {
+    use grove::collections::Rope;
+
+    // appending chunks one by one at the end makes the splay tree a path
+    const NUM_CHUNKS: usize = 100_000;
+    let chunk = "a".repeat(300);
+    let mut rope: Rope<SplayTree<_>> = Rope::new();
+    for _ in 0..NUM_CHUNKS {
+        rope.insert_str(rope.len_chars(), &chunk);
+    }
+    assert_eq!(rope.to_string().len(), 300 * NUM_CHUNKS);
+}
+
+#[test]
+fn treap_exact_slices() {
+    check_exact_slices::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_exact_slices() {
+    check_exact_slices::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_exact_slices() {
+    check_exact_slices::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_interval_map() {
+    check_interval_map::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_interval_map() {
+    check_interval_map::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn red_black_interval_map() {
+    check_interval_map::<RBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_range_map() {
+    check_range_map::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_range_map() {
+    check_range_map::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_range_set() {
+    check_range_set::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_range_set() {
+    check_range_set::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_max_right_min_left() {
+    check_max_right_min_left::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_max_right_min_left() {
+    check_max_right_min_left::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_max_right_min_left() {
+    check_max_right_min_left::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn red_black_max_right_min_left() {
+    check_max_right_min_left::<RBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_matching() {
+    check_matching::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_matching() {
+    check_matching::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_matching() {
+    check_matching::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn red_black_matching() {
+    check_matching::<RBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_beats() {
+    check_beats::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_beats() {
+    check_beats::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_beats() {
+    check_beats::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn red_black_beats() {
+    check_beats::<RBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn arena_and_persistent_beats() {
+    check_beats_arena_and_persistent(NUM_ROUNDS);
+}
+
+#[test]
+fn link_cut_forest_beats() {
+    check_beats_link_cut_forest(NUM_ROUNDS);
+}
+
+#[test]
+fn euler_tour_forest_beats() {
+    check_beats_euler_tour_forest(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_clone_only_data() {
+    check_clone_only_data::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_clone_only_data() {
+    check_clone_only_data::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_clone_only_data() {
+    check_clone_only_data::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn red_black_clone_only_data() {
+    check_clone_only_data::<RBTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn basic_imm_queries() {
+    check_imm_queries::<BasicTree<_>>(NUM_ROUNDS_SLOW, false);
+}
+
+#[test]
+fn treap_imm_queries() {
+    check_imm_queries::<Treap<_>>(NUM_ROUNDS, true);
+}
+
+#[test]
+fn avl_imm_queries() {
+    check_imm_queries::<AVLTree<_>>(NUM_ROUNDS, true);
+}
+
+#[test]
+fn imm_queries_without_clone() {
+    check_imm_queries_without_clone();
+}
+
+#[test]
+fn treap_double_ended_iteration() {
+    check_double_ended_iteration::<Treap<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn splay_double_ended_iteration() {
+    check_double_ended_iteration::<SplayTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn avl_double_ended_iteration() {
+    check_double_ended_iteration::<AVLTree<_>>(NUM_ROUNDS);
+}
+
+#[test]
+fn treap_iter_with_context() {
+    check_iter_with_context::<Treap<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn splay_iter_with_context() {
+    check_iter_with_context::<SplayTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn avl_iter_with_context() {
+    check_iter_with_context::<AVLTree<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn treap_for_each_mut_and_retain() {
+    check_for_each_mut_and_retain::<Treap<_>>(NUM_ROUNDS, true);
+}
+
+#[test]
+fn splay_for_each_mut_and_retain() {
+    check_for_each_mut_and_retain::<SplayTree<_>>(NUM_ROUNDS, true);
+}
+
+#[test]
+fn avl_for_each_mut_and_retain() {
+    check_for_each_mut_and_retain::<AVLTree<_>>(NUM_ROUNDS, true);
+}
+
+#[test]
+fn red_black_for_each_mut_and_retain() {
+    check_for_each_mut_and_retain::<RBTree<_>>(NUM_ROUNDS, true);
+}
+
+#[test]
+fn scapegoat_delete() {
+    check_delete::<ScapegoatTree<_>>();
+}
+
 #[test]
 fn treap_delete() {
     check_delete::<Treap<_>>();
 }
 
+#[test]
+fn delete_with_pending_actions() {
+    check_delete_with_pending_actions::<BasicTree<_>>(NUM_ROUNDS_SLOW, false);
+    check_delete_with_pending_actions::<Treap<_>>(NUM_ROUNDS_SLOW, true);
+    check_delete_with_pending_actions::<AVLTree<_>>(NUM_ROUNDS_SLOW, true);
+    check_delete_with_pending_actions::<RBTree<_>>(NUM_ROUNDS_SLOW, true);
+    check_delete_with_pending_actions::<WBTree<_>>(NUM_ROUNDS_SLOW, true);
+    check_delete_with_pending_actions::<ScapegoatTree<_>>(NUM_ROUNDS_SLOW, false);
+}
+
+#[test]
+fn walker_summaries() {
+    check_walker_summaries::<BasicTree<_>>();
+    check_walker_summaries::<Treap<_>>();
+    check_walker_summaries::<SplayTree<_>>();
+    check_walker_summaries::<AVLTree<_>>();
+    check_walker_summaries::<RBTree<_>>();
+    check_walker_summaries::<WBTree<_>>();
+    check_walker_summaries::<ScapegoatTree<_>>();
+}
+
 #[test]
 fn basic_delete() {
     check_delete::<BasicTree<_>>();
 }
+
+#[test]
+fn treap_bound_locators() {
+    check_bound_locators::<Treap<_>>(NUM_ROUNDS_SLOW);
+}
+
+#[test]
+fn avl_bound_locators() {
+    check_bound_locators::<AVLTree<_>>(NUM_ROUNDS_SLOW);
+}